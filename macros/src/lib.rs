@@ -0,0 +1,218 @@
+//! Attribute macros for [bunt-logger](https://docs.rs/bunt-logger), enabled by its `instrument`
+//! feature.
+
+use proc_macro::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+
+/// Logs entering and leaving a function through the global bunt-logger preferences.
+///
+/// Entering the function is logged with its arguments as [fields], recorded with their `Debug`
+/// implementations; leaving it, also through an early return, a `?` or a panic, is logged with the
+/// time that was spent in the function. Both records are logged at the trace level and with the
+/// module path of the function as their target. Works for `async` functions as well, where the time
+/// is measured from the first poll to completion.
+///
+/// The attribute takes the options:
+///
+/// - `level = "debug"`: the level of the records, one of `error`, `warn`, `info`, `debug` and
+///   `trace`
+/// - `skip(a, b)`: arguments that are not recorded, e.g. because they don't implement `Debug`
+///
+/// Arguments bound to patterns other than plain identifiers, and `self`, are not recorded.
+///
+/// [fields]: https://docs.rs/bunt-logger/*/bunt_logger/struct.Field.html
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{instrument, Level};
+///
+/// #[instrument(level = "debug", skip(data))]
+/// fn checksum(name: &str, data: &[u8]) -> u32 {
+///     data.iter().map(|&b| u32::from(b)).sum()
+/// }
+///
+/// # fn main() {
+/// bunt_logger::with().level(Level::Debug);
+/// checksum("payload", b"abc");
+/// # }
+/// ```
+#[proc_macro_attribute]
+pub fn instrument(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand(attr, item) {
+        Ok(tokens) => tokens,
+        Err(message) => format!("compile_error!({:?});", message)
+            .parse()
+            .expect("invalid compile_error! invocation"),
+    }
+}
+
+struct Options {
+    /// The name of the `Level` variant.
+    level: &'static str,
+    skip: Vec<String>,
+}
+
+fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream, &'static str> {
+    let options = parse_options(attr)?;
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        _ => return Err("#[instrument] can only be applied to functions with a body"),
+    };
+    let fn_index = tokens
+        .iter()
+        .position(|token| matches!(token, TokenTree::Ident(ident) if ident.to_string() == "fn"))
+        .ok_or("#[instrument] can only be applied to functions")?;
+    let name = match tokens.get(fn_index + 1) {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        _ => return Err("expected a function name"),
+    };
+    let args = tokens[fn_index + 2..]
+        .iter()
+        .find_map(|token| match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+                Some(group.stream())
+            }
+            _ => None,
+        })
+        .ok_or("expected function arguments")?;
+
+    let fields: Vec<String> = arg_names(args)
+        .into_iter()
+        .filter(|arg| !options.skip.contains(arg))
+        .map(|arg| format!("{0} = ?{0}", arg))
+        .collect();
+    let fields = if fields.is_empty() {
+        String::new()
+    } else {
+        format!(", {{ {} }}", fields.join(", "))
+    };
+    let level = format!("::bunt_logger::Level::{}", options.level);
+    let prelude = format!(
+        concat!(
+            "let __bunt_logger_span = ::bunt_logger::Span::enter({level}, module_path!(), {name:?});",
+            "if __bunt_logger_span.enabled() {{",
+            "    ::bunt_logger::log!(",
+            "        target: module_path!(), {level}, \"{{$bold}}{{}}{{/$}} entered\", {name:?}{fields}",
+            "    );",
+            "}}",
+        ),
+        level = level,
+        name = name,
+        fields = fields,
+    );
+
+    let mut stream: TokenStream = prelude
+        .parse()
+        .map_err(|_| "failed to expand #[instrument]")?;
+    stream.extend(body.stream());
+    let mut body_with_prelude = Group::new(Delimiter::Brace, stream);
+    body_with_prelude.set_span(body.span());
+
+    tokens.push(TokenTree::Group(body_with_prelude));
+    Ok(tokens.into_iter().collect())
+}
+
+fn parse_options(attr: TokenStream) -> Result<Options, &'static str> {
+    let mut options = Options {
+        level: "Trace",
+        skip: Vec::new(),
+    };
+    let tokens: Vec<TokenTree> = attr.into_iter().collect();
+    for option in split_top_level(&tokens) {
+        match option {
+            [] => {}
+            [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(value)]
+                if key.to_string() == "level" && eq.as_char() == '=' =>
+            {
+                options.level = match value.to_string().trim_matches('"') {
+                    "error" => "Error",
+                    "warn" => "Warn",
+                    "info" => "Info",
+                    "debug" => "Debug",
+                    "trace" => "Trace",
+                    _ => {
+                        return Err("expected one of `error`, `warn`, `info`, `debug` and `trace`")
+                    }
+                };
+            }
+            [TokenTree::Ident(key), TokenTree::Group(args)]
+                if key.to_string() == "skip" && args.delimiter() == Delimiter::Parenthesis =>
+            {
+                let args: Vec<TokenTree> = args.stream().into_iter().collect();
+                for arg in split_top_level(&args) {
+                    match arg {
+                        [TokenTree::Ident(arg)] => options.skip.push(arg.to_string()),
+                        [] => {}
+                        _ => return Err("expected argument names in `skip(...)`"),
+                    }
+                }
+            }
+            _ => return Err("expected `level = \"...\"` or `skip(...)`"),
+        }
+    }
+    Ok(options)
+}
+
+/// Returns the names of the arguments that are plain identifiers, except for `self`.
+fn arg_names(args: TokenStream) -> Vec<String> {
+    let tokens: Vec<TokenTree> = args.into_iter().collect();
+    split_top_level(&tokens)
+        .into_iter()
+        .filter_map(|mut arg| {
+            // Skip attributes and `mut`.
+            loop {
+                match arg {
+                    [TokenTree::Punct(hash), TokenTree::Group(_), rest @ ..]
+                        if hash.as_char() == '#' =>
+                    {
+                        arg = rest
+                    }
+                    [TokenTree::Ident(ident), rest @ ..] if ident.to_string() == "mut" => {
+                        arg = rest
+                    }
+                    _ => break,
+                }
+            }
+            match arg {
+                [TokenTree::Ident(name), TokenTree::Punct(colon), ..]
+                    if colon.as_char() == ':'
+                        && colon.spacing() == Spacing::Alone
+                        && name.to_string() != "self" =>
+                {
+                    Some(name.to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Splits `tokens` at commas outside of angle brackets.
+fn split_top_level(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                // The `>` of `->` doesn't close a bracket.
+                '>' if !matches!(
+                    i.checked_sub(1).map(|i| &tokens[i]),
+                    Some(TokenTree::Punct(prev)) if prev.as_char() == '-' && prev.spacing() == Spacing::Joint
+                ) =>
+                {
+                    depth = depth.saturating_sub(1)
+                }
+                ',' if depth == 0 => {
+                    parts.push(&tokens[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::ToSocketAddrs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use bunt::termcolor::{ColorChoice, WriteColor};
+use log::{Level, LevelFilter, SetLoggerError};
+
+#[cfg(feature = "regex")]
+use crate::{FilterAction, Regex};
+use crate::{
+    FlushGuard, Format, LoadConfigError, LocationMode, LogPrefs, Logger, Output, ParseFilterError,
+    ParseTemplateError, Record, Sink, Theme, TimeZone, TimestampFormat,
+};
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Returns a [`Builder`] for configuring the global preferences in one go.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{ColorChoice, Level};
+///
+/// # fn main() -> Result<(), bunt_logger::InitError> {
+/// let _guard = bunt_logger::builder()
+///     .level(Level::Debug)
+///     .stderr(ColorChoice::Auto)
+///     .init()?;
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn builder() -> Builder {
+    Builder {
+        prefs: LogPrefs::new(false),
+        facade: false,
+    }
+}
+
+/// A configuration that replaces the global preferences at once when
+/// [initialized](Builder::init), or [builds](Builder::build) an independent [`Logger`].
+///
+/// The builder starts from the default preferences, and its methods behave like those of
+/// [`LogPrefs`] with the same name. Unlike changes made through [`with`](crate::with), nothing
+/// takes effect until [`init`](Builder::init) is called, so records logged by other threads
+/// during startup never see a half-applied configuration.
+pub struct Builder {
+    prefs: LogPrefs,
+    facade: bool,
+}
+
+macro_rules! forward {
+    ($($(#[$attr:meta])* fn $name:ident$(<$param:ident: $bound:path>)?(
+        $($arg:ident: $ty:ty),*
+    );)*) => {
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub fn $name$(<$param: $bound>)?(mut self, $($arg: $ty),*) -> Self {
+                self.prefs.$name($($arg),*);
+                self
+            }
+        )*
+    };
+}
+
+macro_rules! try_forward {
+    ($($(#[$attr:meta])* fn $name:ident$(<$param:ident: $bound:path>)?(
+        $($arg:ident: $ty:ty),*
+    ) -> $err:ty;)*) => {
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub fn $name$(<$param: $bound>)?(mut self, $($arg: $ty),*) -> Result<Self, $err> {
+                self.prefs.$name($($arg),*)?;
+                Ok(self)
+            }
+        )*
+    };
+}
+
+impl Builder {
+    forward! {
+        /// See [`LogPrefs::quiet`].
+        fn quiet(quiet: bool);
+        /// See [`LogPrefs::disable`].
+        fn disable(disable: bool);
+        /// See [`LogPrefs::level`].
+        fn level(level: Level);
+        /// See [`LogPrefs::verbosity`].
+        fn verbosity(verbosity: i8);
+        /// See [`LogPrefs::more_verbose`].
+        fn more_verbose(steps: u8);
+        /// See [`LogPrefs::less_verbose`].
+        fn less_verbose(steps: u8);
+        /// See [`LogPrefs::base_level`].
+        fn base_level(level: Level);
+        /// See [`LogPrefs::module_level`].
+        fn module_level(module: &str, level: Level);
+        /// See [`LogPrefs::module_filter`].
+        fn module_filter(module: &str, filter: LevelFilter);
+        /// See [`LogPrefs::filter_message`].
+        #[cfg(feature = "regex")]
+        fn filter_message(pattern: Regex, action: FilterAction);
+        /// See [`LogPrefs::redact`].
+        #[cfg(feature = "regex")]
+        fn redact(pattern: Regex, replacement: &str);
+        /// See [`LogPrefs::max_message_len`].
+        fn max_message_len(max: usize);
+        /// See [`LogPrefs::sample`].
+        fn sample(level: Level, rate: f64);
+        /// See [`LogPrefs::timestamps`].
+        fn timestamps(format: TimestampFormat);
+        /// See [`LogPrefs::time_zone`].
+        fn time_zone(zone: TimeZone);
+        /// See [`LogPrefs::show_delta`].
+        fn show_delta(slow: Option<Duration>);
+        /// See [`LogPrefs::show_level`].
+        fn show_level(show: bool);
+        /// See [`LogPrefs::show_location`].
+        fn show_location(mode: LocationMode);
+        /// See [`LogPrefs::show_thread`].
+        fn show_thread(show: bool);
+        /// See [`LogPrefs::show_hostname`].
+        fn show_hostname(show: bool);
+        /// See [`LogPrefs::show_pid`].
+        fn show_pid(show: bool);
+        /// See [`LogPrefs::show_process_name`].
+        fn show_process_name(show: bool);
+        /// See [`LogPrefs::wrap_messages`].
+        fn wrap_messages(wrap: bool);
+        /// See [`LogPrefs::theme`].
+        fn theme(theme: Theme);
+        /// See [`LogPrefs::format`].
+        fn format(format: Format);
+        /// See [`LogPrefs::dedup`].
+        fn dedup(dedup: bool);
+        /// See [`LogPrefs::keep_recent`].
+        fn keep_recent(capacity: usize, filter: LevelFilter);
+        /// See [`LogPrefs::context_on_error`].
+        fn context_on_error(capacity: usize, filter: LevelFilter);
+        /// See [`LogPrefs::writer`].
+        fn writer(writer: Box<dyn WriteColor + Send>);
+        /// See [`LogPrefs::add_writer`].
+        fn add_writer(writer: Box<dyn WriteColor + Send>);
+        /// See [`LogPrefs::add_output`].
+        fn add_output(output: Output);
+        /// See [`LogPrefs::add_sink`].
+        fn add_sink(sink: Box<dyn Sink>);
+        /// See [`LogPrefs::on_error`].
+        fn on_error(on_error: fn(io::Error));
+        /// See [`LogPrefs::respect_color_env`].
+        fn respect_color_env(respect: bool);
+        /// See [`LogPrefs::stdout`].
+        fn stdout(color: ColorChoice);
+        /// See [`LogPrefs::stderr`].
+        fn stderr(color: ColorChoice);
+        /// See [`LogPrefs::route`].
+        fn route(level: Level, writer: Box<dyn WriteColor + Send>);
+        /// See [`LogPrefs::split_streams`].
+        fn split_streams(color: ColorChoice);
+    }
+
+    try_forward! {
+        /// See [`LogPrefs::template`].
+        fn template(template: &str) -> ParseTemplateError;
+        /// See [`LogPrefs::file`].
+        fn file<P: AsRef<Path>>(path: P) -> io::Error;
+        /// See [`LogPrefs::rotating_file`].
+        fn rotating_file<P: AsRef<Path>>(path: P, max_bytes: u64, max_backups: usize) -> io::Error;
+        /// See [`LogPrefs::gelf`].
+        fn gelf<A: ToSocketAddrs>(addr: A) -> io::Error;
+        /// See [`LogPrefs::parse_filters`].
+        fn parse_filters(spec: &str) -> ParseFilterError;
+        /// See [`LogPrefs::parse_env`].
+        fn parse_env(var: &str) -> ParseFilterError;
+        /// See [`LogPrefs::load_file`].
+        fn load_file<P: AsRef<Path>>(path: P) -> LoadConfigError;
+        /// See [`LogPrefs::load_config`].
+        fn load_config(config: &str) -> LoadConfigError;
+        /// See [`LogPrefs::watch_file`].
+        #[cfg(feature = "watch")]
+        fn watch_file<P: AsRef<Path>>(path: P) -> LoadConfigError;
+        /// See [`LogPrefs::reload_on_sighup`].
+        #[cfg(unix)]
+        fn reload_on_sighup() -> io::Error;
+    }
+
+    /// See [`LogPrefs::add_hook`].
+    #[inline]
+    pub fn add_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Record) -> bool + Send + Sync + 'static,
+    {
+        self.prefs.add_hook(hook);
+        self
+    }
+
+    /// See [`LogPrefs::redact_with`].
+    #[inline]
+    pub fn redact_with<F>(mut self, redact: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.prefs.redact_with(redact);
+        self
+    }
+
+    /// See [`LogPrefs::suspend_with`].
+    #[inline]
+    pub fn suspend_with<F>(mut self, suspend: F) -> Self
+    where
+        F: Fn(&mut dyn FnMut()) + Send + Sync + 'static,
+    {
+        self.prefs.suspend_with(suspend);
+        self
+    }
+
+    /// See [`LogPrefs::progress_bars`].
+    #[cfg(feature = "indicatif")]
+    #[inline]
+    pub fn progress_bars(mut self, bars: indicatif::MultiProgress) -> Self {
+        self.prefs.progress_bars(bars);
+        self
+    }
+
+    /// Sets whether [`init`](Self::init) also registers bunt-logger as the logger for the
+    /// [`log`](mod@log) crate; see [`LogPrefs::install_as_log_facade`]. Defaults to `false`.
+    #[inline]
+    pub fn log_facade(mut self, install: bool) -> Self {
+        self.facade = install;
+        self
+    }
+
+    /// Creates an independent [`Logger`] with the configuration. The `log` facade setting is
+    /// ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{info_to, ColorChoice, Level};
+    ///
+    /// # fn main() {
+    /// let logger = bunt_logger::builder()
+    ///     .level(Level::Debug)
+    ///     .stderr(ColorChoice::Auto)
+    ///     .build();
+    /// info_to!(logger, "Ready");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn build(self) -> Logger {
+        Logger::from_prefs(self.prefs)
+    }
+
+    /// Replaces the global preferences with the configuration, returning a guard that flushes
+    /// the logging targets when dropped.
+    ///
+    /// Records captured by [`capture`](fn@crate::capture) keep being captured. This fails if a
+    /// builder was initialized before, or if the `log` facade was requested but another logger is
+    /// already registered.
+    pub fn init(self) -> Result<FlushGuard, InitError> {
+        if INITIALIZED.swap(true, Ordering::SeqCst) {
+            return Err(InitError::AlreadyInitialized);
+        }
+
+        let mut prefs = crate::with();
+        let mut next = self.prefs;
+        next.global = true;
+        next.captures = std::mem::take(&mut prefs.captures);
+        prefs.restore(next);
+
+        if self.facade {
+            prefs
+                .install_as_log_facade()
+                .map_err(InitError::LogFacade)?;
+        }
+        Ok(FlushGuard::new())
+    }
+}
+
+/// The error returned by [`Builder::init`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InitError {
+    /// A builder was already initialized.
+    AlreadyInitialized,
+    /// Another logger is already registered with the `log` crate.
+    LogFacade(SetLoggerError),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyInitialized => write!(f, "the logger was already initialized"),
+            Self::LogFacade(err) => write!(f, "failed to install the log facade: {}", err),
+        }
+    }
+}
+
+impl Error for InitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::AlreadyInitialized => None,
+            Self::LogFacade(err) => Some(err),
+        }
+    }
+}
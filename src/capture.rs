@@ -0,0 +1,103 @@
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use log::Level;
+
+/// Starts capturing the records written with the global preferences.
+///
+/// Every record that passes the filters is rendered as plain text, in addition to being written
+/// to the logging targets, and can be inspected through the returned handle. Capturing stops once
+/// all clones of the handle are dropped.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, warn, Level};
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Info { return; }
+/// let capture = bunt_logger::capture();
+///
+/// info!("Starting {[bold]}", "server");
+/// warn!("{$yellow}Low disk space{/$}");
+///
+/// assert_eq!(capture.captured_lines(), vec!["Starting server", "Low disk space"]);
+/// assert!(capture.contains(Level::Warn, "disk"));
+/// assert!(!capture.contains(Level::Info, "disk"));
+/// # }
+/// ```
+#[inline]
+pub fn capture() -> TestCapture {
+    let capture = TestCapture::new();
+    crate::read()
+        .captures
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(capture.downgrade());
+    capture
+}
+
+/// A handle to records captured in memory; see [`capture`].
+#[derive(Debug, Clone, Default)]
+pub struct TestCapture {
+    records: Arc<Mutex<Vec<(Level, String)>>>,
+}
+
+impl TestCapture {
+    #[inline]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the rendered lines captured so far, without their trailing newlines.
+    #[inline]
+    pub fn captured_lines(&self) -> Vec<String> {
+        self.lock().iter().map(|(_, line)| line.clone()).collect()
+    }
+
+    /// Returns the levels and rendered lines captured so far.
+    #[inline]
+    pub fn captured(&self) -> Vec<(Level, String)> {
+        self.lock().clone()
+    }
+
+    /// Returns whether a record of `level` containing `pattern` was captured.
+    #[inline]
+    pub fn contains(&self, level: Level, pattern: &str) -> bool {
+        self.lock()
+            .iter()
+            .any(|(l, line)| *l == level && line.contains(pattern))
+    }
+
+    /// Discards the records captured so far.
+    #[inline]
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Vec<(Level, String)>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[inline]
+    fn downgrade(&self) -> CaptureTarget {
+        CaptureTarget(Arc::downgrade(&self.records))
+    }
+}
+
+/// The side of a capture held by the preferences, which doesn't keep the capture alive.
+#[derive(Debug, Clone)]
+pub(crate) struct CaptureTarget(Weak<Mutex<Vec<(Level, String)>>>);
+
+impl CaptureTarget {
+    /// Records a line, or returns `false` if the capture was dropped.
+    pub(crate) fn push(&self, level: Level, line: &str) -> bool {
+        match self.0.upgrade() {
+            Some(records) => {
+                let mut records = records.lock().unwrap_or_else(|e| e.into_inner());
+                records.push((level, line.to_owned()));
+                true
+            }
+            None => false,
+        }
+    }
+}
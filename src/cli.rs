@@ -0,0 +1,45 @@
+use clap::{ArgAction, Args};
+
+/// The `-v`/`--verbose` and `-q`/`--quiet` flags of a command-line interface, for
+/// [`LogPrefs::verbosity`](crate::LogPrefs::verbosity).
+///
+/// Both flags may be repeated, and may be given after subcommands.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{LevelFilter, Verbosity};
+/// use clap::Parser;
+///
+/// /// Copies files.
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(flatten)]
+///     verbosity: Verbosity,
+///     files: Vec<String>,
+/// }
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Debug { return; }
+/// let cli = Cli::parse_from(["cp", "-vq", "a.txt", "-v"]);
+/// bunt_logger::with().verbosity(cli.verbosity.value());
+/// assert_eq!(bunt_logger::max_level(), LevelFilter::Debug);
+/// # }
+/// ```
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verbosity {
+    /// Logs more, up to every trace message; may be repeated
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Logs less, down to nothing; may be repeated
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub quiet: u8,
+}
+
+impl Verbosity {
+    /// Returns the number of `-v` flags minus the number of `-q` flags.
+    #[inline]
+    pub fn value(&self) -> i8 {
+        let value = i16::from(self.verbose) - i16::from(self.quiet);
+        value.clamp(i16::from(i8::MIN), i16::from(i8::MAX)) as i8
+    }
+}
@@ -0,0 +1,208 @@
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use std::time::UNIX_EPOCH;
+use std::time::{Duration, SystemTime};
+
+use once_cell::sync::Lazy;
+
+/// The clock of records, or `None` for the system clock.
+static CLOCK: Lazy<RwLock<Option<Arc<dyn Clock>>>> = Lazy::new(|| RwLock::new(None));
+
+/// A source of the time at which records are logged; see [`set_clock`].
+///
+/// Implement it to take the time from elsewhere, such as a time library, a simulation or a
+/// clock that is synchronized separately:
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+///
+/// use bunt_logger::Clock;
+///
+/// /// The system clock, corrected by an offset measured against a time server.
+/// struct Corrected(Duration);
+///
+/// impl Clock for Corrected {
+///     fn now(&self) -> SystemTime {
+///         SystemTime::now() + self.0
+///     }
+/// }
+///
+/// # fn main() {
+/// bunt_logger::set_clock(Corrected(Duration::from_millis(120)));
+/// # }
+/// ```
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The clock of the operating system, which is used by default.
+///
+/// With the `wasm` feature on `wasm32`, where the standard library has no clock, it's the clock
+/// of the JavaScript runtime, `Date.now()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        system_now()
+    }
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+#[inline]
+fn system_now() -> SystemTime {
+    SystemTime::now()
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[inline]
+fn system_now() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+/// The clock of the `chrono` crate, [`Utc::now`](chrono::Utc::now), e.g. to share a time
+/// source with code that takes its timestamps from `chrono`.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::ChronoClock;
+///
+/// # fn main() {
+/// bunt_logger::set_clock(ChronoClock);
+/// # }
+/// ```
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChronoClock;
+
+#[cfg(feature = "chrono")]
+impl Clock for ChronoClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        chrono::Utc::now().into()
+    }
+}
+
+/// The clock of the `time` crate,
+/// [`OffsetDateTime::now_utc`](time::OffsetDateTime::now_utc), e.g. to share a time source with
+/// code that takes its timestamps from `time`.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::TimeClock;
+///
+/// # fn main() {
+/// bunt_logger::set_clock(TimeClock);
+/// # }
+/// ```
+#[cfg(feature = "time")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeClock;
+
+#[cfg(feature = "time")]
+impl Clock for TimeClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        time::OffsetDateTime::now_utc().into()
+    }
+}
+
+/// A clock that only moves when told to, so that tests get the same timestamps on every run.
+///
+/// Clones share the same time, so a clone can be [installed](set_clock) while the original moves
+/// it.
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use bunt_logger::{info, MockClock, TimestampFormat};
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Info { return; }
+/// let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_629_106_200));
+/// bunt_logger::set_clock(clock.clone());
+/// bunt_logger::with().timestamps(TimestampFormat::Seconds);
+/// let capture = bunt_logger::capture();
+///
+/// info!("Started");
+/// clock.advance(Duration::from_secs(5));
+/// info!("Done");
+///
+/// assert_eq!(
+///     capture.captured_lines(),
+///     vec!["2021-08-16T09:30:00Z Started", "2021-08-16T09:30:05Z Done"]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    time: Arc<Mutex<SystemTime>>,
+}
+
+impl MockClock {
+    /// Returns a clock that stands at `time`.
+    #[inline]
+    pub fn new(time: SystemTime) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Moves the clock to `time`, which may be earlier than its current time.
+    #[inline]
+    pub fn set(&self, time: SystemTime) {
+        *self.lock() = time;
+    }
+
+    /// Moves the clock forward by `duration`.
+    #[inline]
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, SystemTime> {
+        self.time.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Clock for MockClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        *self.lock()
+    }
+}
+
+/// Sets the clock that timestamps records from now on, e.g. a [`MockClock`] in tests. The
+/// [`SystemClock`] is used until then.
+///
+/// The clock also sets the start of [elapsed](crate::TimestampFormat::Elapsed) timestamps and
+/// [deltas](crate::LogPrefs::show_delta) if it is set before the global preferences are first
+/// used.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::SystemClock;
+///
+/// # fn main() {
+/// bunt_logger::set_clock(SystemClock);
+/// # }
+/// ```
+#[inline]
+pub fn set_clock<C: Clock + 'static>(clock: C) {
+    let mut current = CLOCK.write().unwrap_or_else(|err| err.into_inner());
+    *current = Some(Arc::new(clock));
+}
+
+/// Returns the current time of the clock.
+#[inline]
+pub(crate) fn now() -> SystemTime {
+    let clock = CLOCK.read().unwrap_or_else(|err| err.into_inner());
+    match &*clock {
+        Some(clock) => clock.now(),
+        None => system_now(),
+    }
+}
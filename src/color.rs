@@ -0,0 +1,238 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use bunt::termcolor::{Color, ColorChoice, ColorSpec, WriteColor};
+use once_cell::sync::Lazy;
+
+/// A standard stream that a color choice is made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    #[inline]
+    fn is_terminal(self) -> bool {
+        match self {
+            Self::Stdout => io::stdout().is_terminal(),
+            Self::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+/// Resolves [`ColorChoice::Auto`] for `stream` according to the `CLICOLOR_FORCE`, `NO_COLOR`, and
+/// `CLICOLOR` conventions, disabling color when the stream isn't a terminal. Other choices are
+/// returned unchanged.
+pub(crate) fn resolve(choice: ColorChoice, stream: Stream) -> ColorChoice {
+    if choice != ColorChoice::Auto {
+        return choice;
+    }
+
+    if env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        ColorChoice::Always
+    } else if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+        || env::var_os("CLICOLOR").is_some_and(|v| v == "0")
+        || !stream.is_terminal()
+    {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    }
+}
+
+/// The colors that a terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Depth {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The color depth of the terminal, detected from `COLORTERM` and `TERM`.
+pub(crate) static DEPTH: Lazy<Depth> = Lazy::new(|| {
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        Depth::TrueColor
+    } else if term == "dumb" {
+        Depth::None
+    } else if term.contains("256color") {
+        Depth::Ansi256
+    } else if cfg!(windows) && term.is_empty() {
+        // The Windows console supports 24-bit colors since Windows 10.
+        Depth::TrueColor
+    } else if cfg!(target_arch = "wasm32") && term.is_empty() {
+        // There is no terminal, and the CSS of the console writer takes any color.
+        Depth::TrueColor
+    } else {
+        Depth::Ansi16
+    }
+});
+
+/// The approximate RGB values of the 16 ANSI colors, in the order of their codes.
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// A writer that replaces the colors of styles with the closest ones the terminal can display.
+pub(crate) struct Downgrade<'a> {
+    writer: &'a mut dyn WriteColor,
+    depth: Depth,
+}
+
+impl<'a> Downgrade<'a> {
+    #[inline]
+    pub(crate) fn new(writer: &'a mut dyn WriteColor, depth: Depth) -> Self {
+        Self { writer, depth }
+    }
+
+    /// Returns `spec` with its colors converted to the depth of the terminal.
+    fn convert(&self, spec: &ColorSpec) -> ColorSpec {
+        let mut spec = spec.clone();
+        let fg = spec.fg().copied().and_then(|color| self.color(color));
+        let bg = spec.bg().copied().and_then(|color| self.color(color));
+        spec.set_fg(fg).set_bg(bg);
+        spec
+    }
+
+    /// Converts `color`, or returns `None` if the terminal has no colors.
+    fn color(&self, color: Color) -> Option<Color> {
+        match (self.depth, color) {
+            (Depth::None, _) => None,
+            (Depth::Ansi256, Color::Rgb(r, g, b)) => Some(Color::Ansi256(to_ansi256(r, g, b))),
+            (Depth::Ansi16, Color::Rgb(r, g, b)) => Some(to_ansi16(r, g, b)),
+            (Depth::Ansi16, Color::Ansi256(n)) => {
+                let (r, g, b) = ansi256_to_rgb(n);
+                Some(to_ansi16(r, g, b))
+            }
+            _ => Some(color),
+        }
+    }
+}
+
+impl io::Write for Downgrade<'_> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl WriteColor for Downgrade<'_> {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.writer.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        if self.depth == Depth::TrueColor {
+            return self.writer.set_color(spec);
+        }
+        let spec = self.convert(spec);
+        self.writer.set_color(&spec)
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.writer.reset()
+    }
+}
+
+/// Returns the closest color of the 6×6×6 cube or the grayscale ramp of the 256 ANSI colors.
+fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            249..=255 => 231,
+            _ => 232 + ((u16::from(r) - 8) * 24 / 241) as u8,
+        };
+    }
+    let level = |c: u8| ((u16::from(c) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Returns the RGB value of one of the 256 ANSI colors.
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI_16[usize::from(n)],
+        16..=231 => {
+            let level = |c: u8| if c == 0 { 0 } else { 55 + c * 40 };
+            let n = n - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (n - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Returns the RGB value of `color`, using the intense variant of the basic colors if `intense`
+/// is set, or `None` for colors that termcolor may add in the future.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) fn to_rgb(color: Color, intense: bool) -> Option<(u8, u8, u8)> {
+    let basic = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+        Color::Ansi256(n) => return Some(ansi256_to_rgb(n)),
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        _ => return None,
+    };
+    Some(ANSI_16[basic + if intense { 8 } else { 0 }])
+}
+
+/// Returns the closest of the 8 basic ANSI colors. The intense variants aren't used, since
+/// termcolor writes them as 256-color codes.
+fn to_ansi16(r: u8, g: u8, b: u8) -> Color {
+    const BASIC: [Color; 8] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+
+    // Grays would otherwise end up closest to a dark primary color.
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    if max - min < 40 {
+        return if max < 64 { Color::Black } else { Color::White };
+    }
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+    let closest = (0..BASIC.len())
+        .min_by_key(|&i| distance(ANSI_16[i]))
+        .unwrap_or(0);
+    BASIC[closest]
+}
@@ -0,0 +1,498 @@
+mod toml;
+#[cfg(feature = "watch")]
+mod watch;
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+#[cfg(any(unix, feature = "watch"))]
+use std::fs;
+use std::io;
+#[cfg(any(unix, feature = "watch"))]
+use std::path::Path;
+use std::str::FromStr;
+
+use bunt::termcolor::{Color, ColorChoice, ColorSpec};
+use log::{Level, LevelFilter};
+
+use self::toml::{Entry, Table, Value};
+use crate::color::Stream;
+use crate::filter::Directives;
+use crate::template::Template;
+use crate::{
+    FileWriter, Format, Kind, LocationMode, LogPrefs, Output, RotatingFileWriter, Rotation, Theme,
+    TimeZone, TimestampFormat,
+};
+
+#[cfg(feature = "watch")]
+pub(crate) use self::watch::watch;
+
+/// The error returned when a configuration file can't be read or is invalid; see
+/// [`LogPrefs::load_file`](crate::LogPrefs::load_file).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadConfigError {
+    /// The configuration file or a file it names couldn't be opened.
+    Io(io::Error),
+    /// The configuration is malformed or contains an invalid setting.
+    Invalid {
+        /// The line of the configuration that the error is on, starting at `1`.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+}
+
+impl LoadConfigError {
+    #[inline]
+    pub(crate) fn invalid(line: usize, message: String) -> Self {
+        Self::Invalid { line, message }
+    }
+}
+
+impl fmt::Display for LoadConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to load the configuration: {}", err),
+            Self::Invalid { line, message } => {
+                write!(f, "invalid configuration on line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for LoadConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Invalid { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadConfigError {
+    #[inline]
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// The settings of a configuration file, validated before any of them are applied.
+#[derive(Default)]
+pub(crate) struct Config {
+    filter: Option<Directives>,
+    format: Option<Format>,
+    template: Option<Template>,
+    timestamps: Option<TimestampFormat>,
+    time_zone: Option<TimeZone>,
+    show_level: Option<bool>,
+    show_location: Option<LocationMode>,
+    show_thread: Option<bool>,
+    show_hostname: Option<bool>,
+    show_pid: Option<bool>,
+    show_process_name: Option<bool>,
+    wrap: Option<bool>,
+    dedup: Option<bool>,
+    max_message_len: Option<usize>,
+    theme: Option<Theme>,
+    outputs: Option<Vec<Output>>,
+}
+
+/// A logging target of the configuration, which is opened once all settings are valid.
+enum OutputSpec {
+    Stdout(ColorChoice),
+    Stderr(ColorChoice),
+    File(String),
+    RotatingFile {
+        path: String,
+        max_bytes: u64,
+        max_backups: usize,
+        rotation: Rotation,
+    },
+}
+
+/// Applies the configuration in the file at `path` to the global preferences at once, logging
+/// whether that succeeded.
+#[cfg(any(unix, feature = "watch"))]
+pub(crate) fn reload(path: &Path) {
+    let result = fs::read_to_string(path)
+        .map_err(Into::into)
+        .and_then(|text| {
+            let mut prefs = crate::with();
+            Config::parse(&text, &prefs).map(|config| config.apply(&mut prefs))
+        });
+    // The preferences are unlocked again before logging.
+    match result {
+        Ok(()) => crate::info!(
+            target: "config",
+            "Reloaded the configuration from {}",
+            path.display()
+        ),
+        Err(err) => crate::warn!(
+            target: "config",
+            "Failed to reload the configuration from {}: {}",
+            path.display(),
+            err
+        ),
+    }
+}
+
+impl Config {
+    /// Parses and validates the configuration in `text`, on top of `prefs`.
+    pub(crate) fn parse(text: &str, prefs: &LogPrefs) -> Result<Self, LoadConfigError> {
+        let mut config = Self::default();
+        let mut outputs = Vec::new();
+        for table in toml::parse(text)? {
+            match (table.name.as_deref(), table.array) {
+                (None, _) => config.parse_root(&table, prefs)?,
+                (Some("targets"), false) => {
+                    let filter = config.filter.get_or_insert_with(|| prefs.filter.clone());
+                    for entry in &table.entries {
+                        filter.insert(&entry.key, level_filter(entry)?);
+                    }
+                }
+                (Some("theme"), false) => config.theme = Some(parse_theme(&table, prefs)?),
+                (Some("outputs"), true) => outputs.push(parse_output(&table)?),
+                (Some(name), _) => {
+                    return Err(LoadConfigError::invalid(
+                        table.line,
+                        format!("unknown table `{}`", name),
+                    ))
+                }
+            }
+        }
+
+        if !outputs.is_empty() {
+            let time_zone = config.time_zone.unwrap_or(prefs.time_zone);
+            let outputs = outputs
+                .into_iter()
+                .map(|(spec, filter, format)| {
+                    let output = match spec {
+                        OutputSpec::Stdout(color) => Output::standard(
+                            Stream::Stdout,
+                            prefs.resolve_color(color, Stream::Stdout),
+                        ),
+                        OutputSpec::Stderr(color) => Output::standard(
+                            Stream::Stderr,
+                            prefs.resolve_color(color, Stream::Stderr),
+                        ),
+                        OutputSpec::File(path) => Output::new(Box::new(FileWriter::open(path)?)),
+                        OutputSpec::RotatingFile {
+                            path,
+                            max_bytes,
+                            max_backups,
+                            rotation,
+                        } => Output::new(Box::new(
+                            RotatingFileWriter::builder(path)
+                                .max_bytes(max_bytes)
+                                .max_backups(max_backups)
+                                .rotation(rotation)
+                                .time_zone(time_zone)
+                                .open()?,
+                        )),
+                    };
+                    let output = output.with_filter(filter);
+                    Ok(match format {
+                        Some(format) => output.with_format(format),
+                        None => output,
+                    })
+                })
+                .collect::<io::Result<_>>()?;
+            config.outputs = Some(outputs);
+        }
+        Ok(config)
+    }
+
+    fn parse_root(&mut self, table: &Table, prefs: &LogPrefs) -> Result<(), LoadConfigError> {
+        for entry in &table.entries {
+            match entry.key.as_str() {
+                "level" => {
+                    let filter = level_filter(entry)?;
+                    self.filter
+                        .get_or_insert_with(|| prefs.filter.clone())
+                        .set_default(filter);
+                }
+                "filters" => {
+                    let spec = string(entry)?;
+                    self.filter
+                        .get_or_insert_with(|| prefs.filter.clone())
+                        .parse(spec)
+                        .map_err(|err| LoadConfigError::invalid(entry.line, err.to_string()))?;
+                }
+                "format" => self.format = Some(format(entry)?),
+                "template" => {
+                    let template = Template::parse(string(entry)?)
+                        .map_err(|err| LoadConfigError::invalid(entry.line, err.to_string()))?;
+                    self.template = Some(template);
+                }
+                "timestamps" => {
+                    self.timestamps = Some(match string(entry)? {
+                        "off" => TimestampFormat::Off,
+                        "rfc3339" => TimestampFormat::Rfc3339,
+                        "seconds" => TimestampFormat::Seconds,
+                        "millis" => TimestampFormat::Millis,
+                        "elapsed" => TimestampFormat::Elapsed,
+                        custom => TimestampFormat::Custom(custom.to_owned()),
+                    });
+                }
+                "time_zone" => {
+                    self.time_zone = Some(match string(entry)? {
+                        "utc" => TimeZone::Utc,
+                        "local" => TimeZone::Local,
+                        _ => return Err(invalid_value(entry, "`utc` or `local`")),
+                    });
+                }
+                "show_level" => self.show_level = Some(boolean(entry)?),
+                "show_location" => {
+                    self.show_location = Some(match string(entry)? {
+                        "always" => LocationMode::Always,
+                        "debug_only" => LocationMode::DebugOnly,
+                        "never" => LocationMode::Never,
+                        _ => return Err(invalid_value(entry, "`always`, `debug_only` or `never`")),
+                    });
+                }
+                "show_thread" => self.show_thread = Some(boolean(entry)?),
+                "show_hostname" => self.show_hostname = Some(boolean(entry)?),
+                "show_pid" => self.show_pid = Some(boolean(entry)?),
+                "show_process_name" => self.show_process_name = Some(boolean(entry)?),
+                "wrap_messages" => self.wrap = Some(boolean(entry)?),
+                "dedup" => self.dedup = Some(boolean(entry)?),
+                "max_message_len" => self.max_message_len = Some(integer(entry)?),
+                _ => return Err(unknown_key(entry)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the settings to `prefs`.
+    pub(crate) fn apply(self, prefs: &mut LogPrefs) {
+        if let Some(filter) = self.filter {
+            prefs.filter = filter;
+            prefs.sync_max_level();
+        }
+        if let Some(format) = self.format {
+            prefs.format(format);
+        }
+        if let Some(template) = self.template {
+            prefs.template = Some(template);
+        }
+        if let Some(timestamps) = self.timestamps {
+            prefs.timestamps(timestamps);
+        }
+        if let Some(zone) = self.time_zone {
+            prefs.time_zone(zone);
+        }
+        if let Some(show) = self.show_level {
+            prefs.show_level(show);
+        }
+        if let Some(mode) = self.show_location {
+            prefs.show_location(mode);
+        }
+        if let Some(show) = self.show_thread {
+            prefs.show_thread(show);
+        }
+        if let Some(show) = self.show_hostname {
+            prefs.show_hostname(show);
+        }
+        if let Some(show) = self.show_pid {
+            prefs.show_pid(show);
+        }
+        if let Some(show) = self.show_process_name {
+            prefs.show_process_name(show);
+        }
+        if let Some(wrap) = self.wrap {
+            prefs.wrap_messages(wrap);
+        }
+        if let Some(dedup) = self.dedup {
+            prefs.dedup(dedup);
+        }
+        if let Some(max) = self.max_message_len {
+            prefs.max_message_len(max);
+        }
+        if let Some(theme) = self.theme {
+            prefs.theme(theme);
+        }
+        if let Some(outputs) = self.outputs {
+            prefs.writers = outputs;
+            prefs.default_target = false;
+        }
+    }
+}
+
+fn parse_theme(table: &Table, prefs: &LogPrefs) -> Result<Theme, LoadConfigError> {
+    let mut theme = prefs.theme.clone();
+    for entry in &table.entries {
+        theme = match entry.key.as_str() {
+            // The base theme replaces any styles set before it.
+            "base" => match string(entry)? {
+                "default" => Theme::default(),
+                "monochrome" => Theme::monochrome(),
+                _ => return Err(invalid_value(entry, "`default` or `monochrome`")),
+            },
+            "error" => theme.with_style(Level::Error, style(entry)?),
+            "warn" => theme.with_style(Level::Warn, style(entry)?),
+            "info" => theme.with_style(Level::Info, style(entry)?),
+            "debug" => theme.with_style(Level::Debug, style(entry)?),
+            "trace" => theme.with_style(Level::Trace, style(entry)?),
+            "success" => theme.with_kind_style(Kind::Success, style(entry)?),
+            "notice" => theme.with_kind_style(Kind::Notice, style(entry)?),
+            "fatal" => theme.with_kind_style(Kind::Fatal, style(entry)?),
+            "fields" => theme.with_field_style(style(entry)?),
+            "meta" => theme.with_meta_style(style(entry)?),
+            "scope" => theme.with_scope_style(style(entry)?),
+            "styled_messages" => theme.with_styled_messages(boolean(entry)?),
+            "icons" => theme.with_icons(boolean(entry)?),
+            "error_label" => theme.with_label(Level::Error, string(entry)?),
+            "warn_label" => theme.with_label(Level::Warn, string(entry)?),
+            "info_label" => theme.with_label(Level::Info, string(entry)?),
+            "debug_label" => theme.with_label(Level::Debug, string(entry)?),
+            "trace_label" => theme.with_label(Level::Trace, string(entry)?),
+            "success_label" => theme.with_kind_label(Kind::Success, string(entry)?),
+            "notice_label" => theme.with_kind_label(Kind::Notice, string(entry)?),
+            "fatal_label" => theme.with_kind_label(Kind::Fatal, string(entry)?),
+            "label_width" => theme.with_label_width(integer(entry)?),
+            _ => return Err(unknown_key(entry)),
+        };
+    }
+    Ok(theme)
+}
+
+fn parse_output(
+    table: &Table,
+) -> Result<(OutputSpec, LevelFilter, Option<Format>), LoadConfigError> {
+    let mut kind = None;
+    let mut color = ColorChoice::Auto;
+    let mut path = None;
+    let mut max_bytes = None;
+    let mut max_backups = None;
+    let mut rotation = Rotation::Never;
+    let mut filter = LevelFilter::Trace;
+    let mut output_format = None;
+    for entry in &table.entries {
+        match entry.key.as_str() {
+            "kind" => kind = Some(entry),
+            "color" => {
+                color = match string(entry)? {
+                    "auto" => ColorChoice::Auto,
+                    "always" => ColorChoice::Always,
+                    "never" => ColorChoice::Never,
+                    _ => return Err(invalid_value(entry, "`auto`, `always` or `never`")),
+                }
+            }
+            "path" => path = Some(string(entry)?.to_owned()),
+            "max_bytes" => max_bytes = Some(integer(entry)?),
+            "max_backups" => max_backups = Some(integer(entry)?),
+            "rotation" => {
+                rotation = match string(entry)? {
+                    "never" => Rotation::Never,
+                    "hourly" => Rotation::Hourly,
+                    "daily" => Rotation::Daily,
+                    _ => return Err(invalid_value(entry, "`never`, `hourly` or `daily`")),
+                }
+            }
+            "level" => filter = level_filter(entry)?,
+            "format" => output_format = Some(format(entry)?),
+            _ => return Err(unknown_key(entry)),
+        }
+    }
+
+    let missing = |key: &str| LoadConfigError::invalid(table.line, format!("missing `{}`", key));
+    let kind = kind.ok_or_else(|| missing("kind"))?;
+    let spec = match string(kind)? {
+        "stdout" => OutputSpec::Stdout(color),
+        "stderr" => OutputSpec::Stderr(color),
+        "file" => OutputSpec::File(path.ok_or_else(|| missing("path"))?),
+        "rotating_file" => OutputSpec::RotatingFile {
+            path: path.ok_or_else(|| missing("path"))?,
+            max_bytes: max_bytes.ok_or_else(|| missing("max_bytes"))?,
+            max_backups: max_backups.ok_or_else(|| missing("max_backups"))?,
+            rotation,
+        },
+        _ => {
+            return Err(invalid_value(
+                kind,
+                "`stdout`, `stderr`, `file` or `rotating_file`",
+            ))
+        }
+    };
+    Ok((spec, filter, output_format))
+}
+
+/// Parses a style such as `"bold red on white"` from the words `bold`, `dimmed`, `italic`,
+/// `underline` and `intense`, a foreground color, and a background color after `on`. Colors are
+/// names such as `red`, numbers of the 256 ANSI colors, or RGB values such as `255,127,0`.
+fn style(entry: &Entry) -> Result<ColorSpec, LoadConfigError> {
+    let mut spec = ColorSpec::new();
+    let mut words = string(entry)?.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "bold" => spec.set_bold(true),
+            "dimmed" => spec.set_dimmed(true),
+            "italic" => spec.set_italic(true),
+            "underline" => spec.set_underline(true),
+            "intense" => spec.set_intense(true),
+            "on" => {
+                let color = words.next().and_then(|color| Color::from_str(color).ok());
+                match color {
+                    Some(color) => spec.set_bg(Some(color)),
+                    None => return Err(invalid_value(entry, "a color after `on`")),
+                }
+            }
+            color => match Color::from_str(color) {
+                Ok(color) => spec.set_fg(Some(color)),
+                Err(err) => return Err(LoadConfigError::invalid(entry.line, err.to_string())),
+            },
+        };
+    }
+    Ok(spec)
+}
+
+fn format(entry: &Entry) -> Result<Format, LoadConfigError> {
+    match string(entry)? {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(invalid_value(entry, "`text` or `json`")),
+    }
+}
+
+fn level_filter(entry: &Entry) -> Result<LevelFilter, LoadConfigError> {
+    string(entry)?
+        .parse()
+        .map_err(|_| invalid_value(entry, "a level such as `info`, or `off`"))
+}
+
+fn string(entry: &Entry) -> Result<&str, LoadConfigError> {
+    match &entry.value {
+        Value::Str(s) => Ok(s),
+        _ => Err(invalid_value(entry, "a string")),
+    }
+}
+
+fn boolean(entry: &Entry) -> Result<bool, LoadConfigError> {
+    match entry.value {
+        Value::Bool(b) => Ok(b),
+        _ => Err(invalid_value(entry, "a boolean")),
+    }
+}
+
+fn integer<T: TryFrom<i64>>(entry: &Entry) -> Result<T, LoadConfigError> {
+    match entry.value {
+        Value::Int(n) => T::try_from(n).map_err(|_| invalid_value(entry, "a positive integer")),
+        _ => Err(invalid_value(entry, "a positive integer")),
+    }
+}
+
+fn invalid_value(entry: &Entry, expected: &str) -> LoadConfigError {
+    let found = match &entry.value {
+        Value::Str(s) => format!("{:?}", s),
+        value => value.type_name().to_owned(),
+    };
+    LoadConfigError::invalid(
+        entry.line,
+        format!("expected {} for `{}`, found {}", expected, entry.key, found),
+    )
+}
+
+fn unknown_key(entry: &Entry) -> LoadConfigError {
+    LoadConfigError::invalid(entry.line, format!("unknown key `{}`", entry.key))
+}
@@ -0,0 +1,405 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use toml::Spanned;
+
+use super::LoadConfigError;
+
+/// A value of a TOML document.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the name of the type of the value, for error messages.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::Str(_) => "a string",
+            Self::Int(_) => "an integer",
+            Self::Float(_) => "a float",
+            Self::Bool(_) => "a boolean",
+            Self::Array(_) => "an array",
+        }
+    }
+}
+
+/// A key-value pair, with the line it is on.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Entry {
+    pub(crate) key: String,
+    pub(crate) value: Value,
+    pub(crate) line: usize,
+}
+
+/// A table of a TOML document: the top-level table, a `[table]`, or an element of an
+/// `[[array]]`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Table {
+    /// The name in the header, or `None` for the top-level table.
+    pub(crate) name: Option<String>,
+    /// Whether the table is an element of an array of tables.
+    pub(crate) array: bool,
+    /// The line of the header.
+    pub(crate) line: usize,
+    pub(crate) entries: Vec<Entry>,
+}
+
+/// Parses a TOML document into its tables, the top-level one first, and the others in the order
+/// they are written.
+///
+/// Tables may be written as headers, inline or with dotted keys, but only one level deep, and
+/// dates are not supported.
+pub(crate) fn parse(text: &str) -> Result<Vec<Table>, LoadConfigError> {
+    let line = |offset: usize| text[..offset.min(text.len())].matches('\n').count() + 1;
+    let error = |err: toml::de::Error| {
+        let offset = err.span().map_or(0, |span| span.start);
+        // Some messages continue on a second line.
+        let message = err.message().lines().collect::<Vec<_>>().join(": ");
+        LoadConfigError::invalid(line(offset), message)
+    };
+
+    // The values are deserialized without their positions first, which checks the syntax and
+    // finds dates, which the positions can't be deserialized next to.
+    let document: toml::Table = toml::from_str(text).map_err(error)?;
+    if let Some(key) = document
+        .iter()
+        .find_map(|(key, value)| has_date(value).then_some(key))
+    {
+        let keys: Vec<Spanned<String>> = toml::from_str::<KeySpans>(text).map_err(error)?.0;
+        let start = keys
+            .iter()
+            .find(|spanned| spanned.get_ref() == key)
+            .map_or(0, |spanned| spanned.span().start);
+        return Err(LoadConfigError::invalid(
+            line(start),
+            "dates are not supported".to_owned(),
+        ));
+    }
+    let root = match toml::from_str::<Raw>(text).map_err(error)? {
+        Raw::Table(entries) => entries,
+        _ => unreachable!("a document is a table"),
+    };
+
+    let mut tables = vec![Table {
+        name: None,
+        array: false,
+        line: 1,
+        entries: Vec::new(),
+    }];
+    for (key, raw) in root {
+        let key_line = line(key.span().start);
+        let key = key.into_inner();
+        match raw {
+            Raw::Table(entries) => tables.push(Table {
+                name: Some(key),
+                array: false,
+                line: key_line,
+                entries: entries_of(entries, &line)?,
+            }),
+            Raw::Array(elements) if is_array_of_tables(&elements) => {
+                for element in elements {
+                    let element_line = line(element.span().start);
+                    if let Raw::Table(entries) = element.into_inner() {
+                        tables.push(Table {
+                            name: Some(key.clone()),
+                            array: true,
+                            line: element_line,
+                            entries: entries_of(entries, &line)?,
+                        });
+                    }
+                }
+            }
+            raw => tables[0].entries.push(Entry {
+                key,
+                value: raw.into_value(key_line)?,
+                line: key_line,
+            }),
+        }
+    }
+    tables[1..].sort_by_key(|table| table.line);
+    Ok(tables)
+}
+
+/// Converts the entries of a table, which may not contain tables themselves.
+fn entries_of(
+    entries: Vec<(Spanned<String>, Raw)>,
+    line: &dyn Fn(usize) -> usize,
+) -> Result<Vec<Entry>, LoadConfigError> {
+    entries
+        .into_iter()
+        .map(|(key, raw)| {
+            let line = line(key.span().start);
+            Ok(Entry {
+                key: key.into_inner(),
+                value: raw.into_value(line)?,
+                line,
+            })
+        })
+        .collect()
+}
+
+fn is_array_of_tables(elements: &[Spanned<Raw>]) -> bool {
+    !elements.is_empty()
+        && elements
+            .iter()
+            .all(|element| matches!(element.get_ref(), Raw::Table(_)))
+}
+
+/// Returns whether `value` is or contains a date.
+fn has_date(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Datetime(_) => true,
+        toml::Value::Array(values) => values.iter().any(has_date),
+        toml::Value::Table(table) => table.values().any(has_date),
+        _ => false,
+    }
+}
+
+/// The top-level keys of a document with their positions.
+struct KeySpans(Vec<Spanned<String>>);
+
+impl<'de> Deserialize<'de> for KeySpans {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeySpansVisitor;
+
+        impl<'de> Visitor<'de> for KeySpansVisitor {
+            type Value = KeySpans;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a TOML table")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeySpans, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = map.next_key()? {
+                    map.next_value::<IgnoredAny>()?;
+                    keys.push(key);
+                }
+                Ok(KeySpans(keys))
+            }
+        }
+
+        deserializer.deserialize_map(KeySpansVisitor)
+    }
+}
+
+/// A value as deserialized by `toml`, without dates, with the positions of the keys of tables,
+/// in the order they are written, and of the elements of arrays.
+///
+/// The values of tables have no position, since tables of dotted keys don't have one.
+enum Raw {
+    Value(Value),
+    Array(Vec<Spanned<Raw>>),
+    Table(Vec<(Spanned<String>, Raw)>),
+}
+
+impl Raw {
+    /// Converts a value on `line`, failing if it is or contains a table.
+    fn into_value(self, line: usize) -> Result<Value, LoadConfigError> {
+        match self {
+            Self::Value(value) => Ok(value),
+            Self::Array(elements) => elements
+                .into_iter()
+                .map(|element| element.into_inner().into_value(line))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Self::Table(_) => Err(LoadConfigError::invalid(
+                line,
+                "nested tables are not supported".to_owned(),
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Raw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RawVisitor)
+    }
+}
+
+struct RawVisitor;
+
+impl<'de> Visitor<'de> for RawVisitor {
+    type Value = Raw;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a TOML value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Raw, E> {
+        Ok(Raw::Value(Value::Bool(b)))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<Raw, E> {
+        Ok(Raw::Value(Value::Int(n)))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Raw, E> {
+        match i64::try_from(n) {
+            Ok(n) => Ok(Raw::Value(Value::Int(n))),
+            Err(_) => Err(E::custom("integer out of range")),
+        }
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<Raw, E> {
+        Ok(Raw::Value(Value::Float(n)))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Raw, E> {
+        Ok(Raw::Value(Value::Str(s.to_owned())))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Raw, E> {
+        Ok(Raw::Value(Value::Str(s)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Raw, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Raw::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Raw, A::Error> {
+        let mut entries: Vec<(Spanned<String>, Raw)> = Vec::new();
+        while let Some(key) = map.next_key()? {
+            entries.push((key, map.next_value()?));
+        }
+        // The keys of tables aren't necessarily deserialized in the order they are written.
+        entries.sort_by_key(|(key, _)| key.span().start);
+        Ok(Raw::Table(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, value: Value, line: usize) -> Entry {
+        Entry {
+            key: key.to_owned(),
+            value,
+            line,
+        }
+    }
+
+    fn error_line(text: &str) -> (usize, String) {
+        match parse(text) {
+            Err(LoadConfigError::Invalid { line, message }) => (line, message),
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn root_entries_and_tables() {
+        let tables = parse(
+            "# Comment\nlevel = \"info\" # trailing\ncount = -3\nratio = 0.5\n\n[targets]\nhyper = 'warn'\n",
+        )
+        .unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, None);
+        assert_eq!(
+            tables[0].entries,
+            vec![
+                entry("level", Value::Str("info".to_owned()), 2),
+                entry("count", Value::Int(-3), 3),
+                entry("ratio", Value::Float(0.5), 4),
+            ]
+        );
+        assert_eq!(tables[1].name.as_deref(), Some("targets"));
+        assert_eq!(tables[1].line, 6);
+        assert_eq!(
+            tables[1].entries,
+            vec![entry("hyper", Value::Str("warn".to_owned()), 7)]
+        );
+    }
+
+    #[test]
+    fn inline_and_dotted_tables() {
+        for text in [
+            "[targets]\nhyper = \"warn\"\n",
+            "targets = { hyper = \"warn\" }\n",
+            "targets.hyper = \"warn\"\n",
+        ] {
+            let tables = parse(text).unwrap();
+            assert_eq!(tables.len(), 2, "{}", text);
+            assert_eq!(tables[1].name.as_deref(), Some("targets"));
+            assert_eq!(tables[1].entries[0].key, "hyper");
+            assert_eq!(tables[1].entries[0].value, Value::Str("warn".to_owned()));
+        }
+    }
+
+    #[test]
+    fn arrays_of_tables_keep_their_order() {
+        let tables =
+            parse("[[outputs]]\nkind = \"stderr\"\n\n[theme]\nicons = true\n\n[[outputs]]\nkind = \"stdout\"\n")
+                .unwrap();
+        let names: Vec<_> = tables
+            .iter()
+            .map(|table| (table.name.as_deref(), table.array, table.line))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (None, false, 1),
+                (Some("outputs"), true, 1),
+                (Some("theme"), false, 4),
+                (Some("outputs"), true, 7),
+            ]
+        );
+        assert_eq!(
+            tables[3].entries,
+            vec![entry("kind", Value::Str("stdout".to_owned()), 8)]
+        );
+    }
+
+    #[test]
+    fn strings_and_arrays() {
+        let tables = parse(
+            "escaped = \"tab\\there \\\"quoted\\\" \\u00e9\"\nliteral = 'C:\\path'\nmulti = \"\"\"\nline\"\"\"\nlist = [\n  \"a\", # first\n  \"b\",\n]\nempty = []\n",
+        )
+        .unwrap();
+        let values: Vec<_> = tables[0].entries.iter().map(|e| &e.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                &Value::Str("tab\there \"quoted\" é".to_owned()),
+                &Value::Str("C:\\path".to_owned()),
+                &Value::Str("line".to_owned()),
+                &Value::Array(vec![Value::Str("a".to_owned()), Value::Str("b".to_owned())]),
+                &Value::Array(Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_have_their_line() {
+        assert_eq!(error_line("level = \"info\"\n\nshow_level = \n").0, 3);
+        assert_eq!(error_line("list = [1,\n2\n").0, 3);
+        assert_eq!(error_line("a = 1\na = 2\n").0, 2);
+        assert_eq!(error_line("a = \"unterminated\n").0, 1);
+    }
+
+    #[test]
+    fn unsupported_values() {
+        assert_eq!(
+            error_line("level = \"info\"\nwhen = 1979-05-27\n"),
+            (2, "dates are not supported".to_owned())
+        );
+        assert_eq!(
+            error_line("[theme]\ninfo = \"blue\"\nnested = { a = 1 }\n"),
+            (3, "nested tables are not supported".to_owned())
+        );
+        assert_eq!(
+            error_line("list = [1, { a = 1 }]\n"),
+            (1, "nested tables are not supported".to_owned())
+        );
+    }
+}
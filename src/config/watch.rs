@@ -0,0 +1,67 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the watched file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The watched file and the time it was last modified, if known.
+static WATCHED: Mutex<Option<(PathBuf, Option<SystemTime>)>> = Mutex::new(None);
+
+#[inline]
+fn lock() -> MutexGuard<'static, Option<(PathBuf, Option<SystemTime>)>> {
+    WATCHED.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Returns the time that the file at `path` was last modified.
+#[inline]
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Starts reloading the global preferences from the file at `path` when it changes, replacing the
+/// file watched before, if any. Fails if the thread that watches the file can't be spawned.
+pub(crate) fn watch(path: PathBuf) -> io::Result<()> {
+    let mut watched = lock();
+    let spawn = watched.is_none();
+    let modified = modified(&path);
+    *watched = Some((path, modified));
+    drop(watched);
+
+    if spawn {
+        let result = thread::Builder::new()
+            .name("bunt-logger-watch".to_owned())
+            .spawn(poll);
+        if let Err(err) = result {
+            // The next call tries to spawn the thread again.
+            *lock() = None;
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+fn poll() {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let path = {
+            let mut watched = lock();
+            let (path, last) = match watched.as_mut() {
+                Some(watched) => watched,
+                None => continue,
+            };
+            // The file may be missing for a moment while an editor replaces it.
+            let modified = match modified(path) {
+                Some(modified) if Some(modified) != *last => modified,
+                _ => continue,
+            };
+            *last = Some(modified);
+            path.clone()
+        };
+        super::reload(&path);
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::LevelFilter;
+
+use crate::{Message, Record};
+
+/// Records held back until an error occurs; see
+/// [`LogPrefs::context_on_error`](crate::LogPrefs::context_on_error).
+#[derive(Debug, Clone)]
+pub(crate) struct ErrorContext {
+    /// The most verbose level held back.
+    pub(crate) filter: LevelFilter,
+    capacity: usize,
+    /// Shared between clones.
+    records: Arc<Mutex<VecDeque<Record>>>,
+}
+
+impl ErrorContext {
+    #[inline]
+    pub(crate) fn new(capacity: usize, filter: LevelFilter) -> Self {
+        Self {
+            filter,
+            capacity,
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Holds back `record`, dropping the oldest record if the buffer is full.
+    pub(crate) fn push(&self, record: &Record) {
+        let mut records = self.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+    }
+
+    /// Takes the records held back so far, oldest first, with their messages dimmed.
+    pub(crate) fn take(&self) -> Vec<Record> {
+        let records = std::mem::take(&mut *self.lock());
+        records
+            .into_iter()
+            .map(|mut record| {
+                let mut message = Message::new();
+                let _ = bunt::write!(message, "{$dimmed}{}{/$}", record.message());
+                record.set_message(message);
+                record
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Record>> {
+        self.records.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use log::LevelFilter;
+
+/// How long a connection may stay idle before it is closed, so that one client can't block
+/// others.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const HELP: &str = "commands: level <level>, quiet on|off, filter <directives>, help";
+
+/// Listens on a Unix domain socket at `path` for commands that change the global preferences of a
+/// running process, returning a handle that stops listening and removes the socket when dropped.
+///
+/// Clients send one command per line and get back one line, `ok` or `error: ...`:
+///
+/// - `level <level>`: sets the default level, e.g. `level trace`, or `level off`
+/// - `quiet on|off`: sets whether all output is [silenced](crate::LogPrefs::quiet)
+/// - `filter <directives>`: applies level directives in the syntax of
+///   [`parse_filters`](crate::LogPrefs::parse_filters), e.g. `filter hyper=warn`
+/// - `help`: lists the commands
+///
+/// Applied commands are logged with the target `control`. Anyone who can connect to the socket
+/// can change the logging, so it should be placed in a directory that only the service can
+/// access. A socket left behind by a process that exited is replaced. Only available on Unix.
+///
+/// # Example
+/// ```rust,no_run
+/// # fn main() -> std::io::Result<()> {
+/// let _control = bunt_logger::control_socket("/run/my-app/log.sock")?;
+/// // $ echo "level debug" | nc -U /run/my-app/log.sock
+/// // ok
+/// # Ok(())
+/// # }
+/// ```
+pub fn control_socket<P: AsRef<Path>>(path: P) -> io::Result<ControlSocket> {
+    let path = path.as_ref().to_owned();
+    let listener = match UnixListener::bind(&path) {
+        Err(err) if err.kind() == io::ErrorKind::AddrInUse && is_stale(&path) => {
+            fs::remove_file(&path)?;
+            UnixListener::bind(&path)?
+        }
+        result => result?,
+    };
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    thread::Builder::new()
+        .name("bunt-logger-control".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Errors of single connections only affect that client.
+                if let Ok(stream) = stream {
+                    let _ = serve(stream);
+                }
+            }
+        })?;
+    Ok(ControlSocket { path, stop })
+}
+
+/// Whether the socket at `path` was left behind by a process that no longer listens on it.
+#[inline]
+fn is_stale(path: &Path) -> bool {
+    matches!(
+        UnixStream::connect(path),
+        Err(err) if err.kind() == io::ErrorKind::ConnectionRefused
+    )
+}
+
+/// Answers the commands of one client until it disconnects.
+fn serve(stream: UnixStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let command = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        match execute(command) {
+            Ok(None) => writeln!(writer, "ok")?,
+            Ok(Some(reply)) => writeln!(writer, "{}", reply)?,
+            Err(err) => writeln!(writer, "error: {}", err)?,
+        }
+    }
+    Ok(())
+}
+
+/// Applies `command` to the global preferences, returning the reply if it isn't `ok`.
+fn execute(command: &str) -> Result<Option<&'static str>, String> {
+    let mut parts = command.splitn(2, char::is_whitespace);
+    let name = parts.next().unwrap_or_default();
+    let arg = parts.next().map(str::trim).unwrap_or_default();
+    match (name, arg) {
+        ("help", "") => return Ok(Some(HELP)),
+        ("level", level) => {
+            level
+                .parse::<LevelFilter>()
+                .map_err(|_| format!("invalid level `{}`", level))?;
+            crate::with()
+                .parse_filters(level)
+                .map_err(|err| err.to_string())?;
+        }
+        ("quiet", "on") => {
+            crate::with().quiet(true);
+        }
+        ("quiet", "off") => {
+            crate::with().quiet(false);
+        }
+        ("filter", spec) if !spec.is_empty() => {
+            crate::with()
+                .parse_filters(spec)
+                .map_err(|err| err.to_string())?;
+        }
+        _ => return Err(format!("invalid command `{}`; {}", command, HELP)),
+    }
+    // The preferences are unlocked again before logging.
+    crate::info!(target: "control", "Applied `{}`", command);
+    Ok(None)
+}
+
+/// A handle to a control socket; see [`control_socket`].
+#[must_use = "the control socket is closed as soon as the handle is dropped"]
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlSocket {
+    /// Returns the path of the socket.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wakes up the listener thread so that it notices.
+        let _ = UnixStream::connect(&self.path);
+        let _ = fs::remove_file(&self.path);
+    }
+}
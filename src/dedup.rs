@@ -0,0 +1,54 @@
+use crate::{Message, Record};
+
+/// Collapses consecutive identical records; see [`LogPrefs::dedup`](crate::LogPrefs::dedup).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Dedup {
+    last: Option<Record>,
+    repeated: usize,
+}
+
+impl Dedup {
+    /// Returns whether `record` repeats the last record, counting the repetition if so.
+    pub(crate) fn repeats(&mut self, record: &Record) -> bool {
+        let repeats = self.last.as_ref().is_some_and(|last| same(last, record));
+        if repeats {
+            self.repeated += 1;
+        }
+        repeats
+    }
+
+    /// Makes `record` the last record.
+    #[inline]
+    pub(crate) fn set_last(&mut self, record: &Record) {
+        self.last = Some(record.clone());
+    }
+
+    /// Returns a record summarizing the repetitions of the last record, if there were any, and
+    /// resets the count.
+    pub(crate) fn summary(&mut self) -> Option<Record> {
+        if self.repeated == 0 {
+            return None;
+        }
+        let last = self.last.as_ref()?;
+        let mut message = Message::new();
+        let _ = bunt::write!(
+            message,
+            "{$dimmed}last message repeated {} more time{}{/$}",
+            self.repeated,
+            if self.repeated == 1 { "" } else { "s" },
+        );
+        self.repeated = 0;
+        Some(Record::new(last.level(), last.target(), message))
+    }
+}
+
+/// Returns whether two records render the same, apart from their timestamps.
+fn same(a: &Record, b: &Record) -> bool {
+    a.level() == b.level()
+        && a.kind() == b.kind()
+        && a.target() == b.target()
+        && a.indent() == b.indent()
+        && a.message() == b.message()
+        && a.fields() == b.fields()
+        && a.location() == b.location()
+}
@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+
+use log::kv::{self, VisitSource};
+use log::{Log, Metadata, Record};
+
+use crate::{Field, Location, Message, Value};
+
+/// The [`log::Log`] implementation registered by
+/// [`LogPrefs::install_as_log_facade`](crate::LogPrefs::install_as_log_facade).
+pub(crate) static FACADE: Facade = Facade;
+
+pub(crate) struct Facade;
+
+impl Log for Facade {
+    #[inline]
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        crate::Global.enabled(metadata.level(), metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let message = Message::from(record.args().to_string());
+            let mut fields = Fields(Vec::new());
+            let _ = record.key_values().visit(&mut fields);
+
+            let mut bunt_record = crate::Record::new(record.level(), record.target(), message);
+            let file = match record.file_static() {
+                Some(file) => Some(Cow::Borrowed(file)),
+                None => record.file().map(|file| Cow::Owned(file.to_owned())),
+            };
+            if let (Some(file), Some(line)) = (file, record.line()) {
+                bunt_record = bunt_record.with_location(Location::new(file, line));
+            }
+            let record = fields
+                .0
+                .into_iter()
+                .fold(bunt_record, crate::Record::with_field);
+            crate::read().log_record(&record);
+        }
+    }
+
+    #[inline]
+    fn flush(&self) {
+        crate::read().flush_or_report();
+    }
+}
+
+/// Collects the key-value pairs of a `log` record as fields.
+struct Fields(Vec<Field>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(value) = value.to_i64() {
+            Value::I64(value)
+        } else if let Some(value) = value.to_u64() {
+            Value::U64(value)
+        } else if let Some(value) = value.to_f64() {
+            Value::F64(value)
+        } else if let Some(value) = value.to_bool() {
+            Value::Bool(value)
+        } else if let Some(value) = value.to_borrowed_str() {
+            Value::Str(value.to_owned())
+        } else {
+            Value::Str(value.to_string())
+        };
+        self.0.push(Field::new(key.as_str().to_owned(), value));
+        Ok(())
+    }
+}
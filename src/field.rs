@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// A key-value pair attached to a record.
+///
+/// Fields are usually given in a trailing block in the logging macros:
+///
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// let addr = "127.0.0.1:8080";
+/// let options = vec!["nodelay"];
+/// info!("{$green}Connected{/$}", { addr = %addr, retries = 3, options = ?options });
+/// # }
+/// ```
+///
+/// A value prefixed with `%` is recorded with its [`Display`](fmt::Display) implementation, one
+/// prefixed with `?` with its [`Debug`](fmt::Debug) implementation, and other values must convert
+/// into a [`Value`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    key: Cow<'static, str>,
+    value: Value,
+}
+
+impl Field {
+    /// Creates a field.
+    #[inline]
+    pub fn new<K, V>(key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<Value>,
+    {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the key of the field.
+    #[inline]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the value of the field.
+    #[inline]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// The value of a [`Field`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A string, or a value recorded with its `Display` implementation.
+    Str(String),
+    /// A value recorded with its `Debug` implementation.
+    Debug(String),
+    /// A signed integer.
+    I64(i64),
+    /// An unsigned integer.
+    U64(u64),
+    /// A floating-point number.
+    F64(f64),
+    /// A boolean.
+    Bool(bool),
+}
+
+impl Value {
+    /// Records `value` with its `Display` implementation.
+    #[inline]
+    pub fn display<T: fmt::Display + ?Sized>(value: &T) -> Self {
+        Self::Str(value.to_string())
+    }
+
+    /// Records `value` with its `Debug` implementation.
+    #[inline]
+    pub fn debug<T: fmt::Debug + ?Sized>(value: &T) -> Self {
+        Self::Debug(format!("{:?}", value))
+    }
+}
+
+/// Strings are quoted if they are empty or contain whitespace, quotes, or `=`, so that
+/// `key=value` pairs remain unambiguous.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) | Self::Debug(s) => {
+                let quote =
+                    s.is_empty() || s.chars().any(|c| c.is_whitespace() || c == '"' || c == '=');
+                if quote {
+                    write!(f, "{:?}", s)
+                } else {
+                    f.write_str(s)
+                }
+            }
+            Self::I64(v) => write!(f, "{}", v),
+            Self::U64(v) => write!(f, "{}", v),
+            Self::F64(v) => write!(f, "{}", v),
+            Self::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident($target:ty): $($source:ty),+) => {
+        $(
+            impl From<$source> for Value {
+                #[inline]
+                fn from(value: $source) -> Self {
+                    Self::$variant(value as $target)
+                }
+            }
+        )+
+    };
+}
+
+impl_from!(I64(i64): i8, i16, i32, i64, isize);
+impl_from!(U64(u64): u8, u16, u32, u64, usize);
+impl_from!(F64(f64): f32, f64);
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<char> for Value {
+    #[inline]
+    fn from(value: char) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_owned())
+    }
+}
+
+impl From<&String> for Value {
+    #[inline]
+    fn from(value: &String) -> Self {
+        Self::Str(value.clone())
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
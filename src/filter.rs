@@ -0,0 +1,226 @@
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+
+use log::LevelFilter;
+
+/// A default level filter plus per-target overrides, matched by longest module-path prefix.
+#[derive(Debug, Clone)]
+pub(crate) struct Directives {
+    default: LevelFilter,
+    /// Sorted by descending target length, so the first match is the most specific one.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Directives {
+    #[inline]
+    pub(crate) fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            targets: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_default(&mut self, filter: LevelFilter) {
+        self.default = filter;
+    }
+
+    /// Sets the filter for `target` and every module nested under it, replacing any previous
+    /// directive for the same target.
+    pub(crate) fn insert(&mut self, target: &str, filter: LevelFilter) {
+        match self.targets.iter_mut().find(|(t, _)| t == target) {
+            Some(entry) => entry.1 = filter,
+            None => {
+                self.targets.push((target.to_owned(), filter));
+                self.targets.sort_by_key(|(t, _)| Reverse(t.len()));
+            }
+        }
+    }
+
+    /// Applies a comma-separated list of directives such as `info,my_crate=trace,hyper=off`.
+    ///
+    /// A bare level sets the default filter, `target=level` sets the filter for a module, and a
+    /// bare target enables everything for that module. Nothing is applied if any directive is
+    /// malformed.
+    pub(crate) fn parse(&mut self, spec: &str) -> Result<(), ParseFilterError> {
+        let mut default = None;
+        let mut targets = Vec::new();
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let mut parts = directive.splitn(2, '=');
+            let lhs = parts.next().unwrap_or_default().trim();
+            match parts.next().map(str::trim) {
+                Some(level) => {
+                    let filter = level
+                        .parse()
+                        .map_err(|_| ParseFilterError::new(directive))?;
+                    if lhs.is_empty() {
+                        return Err(ParseFilterError::new(directive));
+                    }
+                    targets.push((lhs, filter));
+                }
+                None => match lhs.parse() {
+                    Ok(filter) => default = Some(filter),
+                    Err(_) => targets.push((lhs, LevelFilter::Trace)),
+                },
+            }
+        }
+
+        if let Some(filter) = default {
+            self.set_default(filter);
+        }
+        for (target, filter) in targets {
+            self.insert(target, filter);
+        }
+        Ok(())
+    }
+
+    /// Returns the filter that applies to records from `target`.
+    pub(crate) fn filter_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| matches_prefix(prefix, target))
+            .map(|(_, filter)| *filter)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns the most verbose filter across all directives.
+    pub(crate) fn max_filter(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, filter)| *filter)
+            .fold(self.default, std::cmp::max)
+    }
+}
+
+/// Whether `prefix` names `target` itself or one of its parent modules.
+#[inline]
+fn matches_prefix(prefix: &str, target: &str) -> bool {
+    target.starts_with(prefix)
+        && (target.len() == prefix.len() || target[prefix.len()..].starts_with("::"))
+}
+
+/// The error returned when a filter specification contains a malformed directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFilterError {
+    directive: String,
+}
+
+impl ParseFilterError {
+    #[inline]
+    fn new(directive: &str) -> Self {
+        Self {
+            directive: directive.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter directive `{}`", self.directive)
+    }
+}
+
+impl Error for ParseFilterError {}
+
+/// What happens to records whose message matches a filter of
+/// [`LogPrefs::filter_message`](crate::LogPrefs::filter_message).
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterAction {
+    /// The record is logged, even if a later filter would deny it. Records that match no filter
+    /// are logged anyway, so this only matters before a `Deny` filter.
+    Allow,
+    /// The record is dropped.
+    Deny,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(spec: &str) -> Result<Directives, ParseFilterError> {
+        let mut directives = Directives::new(LevelFilter::Error);
+        directives.parse(spec)?;
+        Ok(directives)
+    }
+
+    #[test]
+    fn parse_directives() {
+        let directives = parse("info, my_crate=trace ,hyper = off,,").unwrap();
+        assert_eq!(directives.default, LevelFilter::Info);
+        assert_eq!(directives.filter_for("my_crate"), LevelFilter::Trace);
+        assert_eq!(directives.filter_for("hyper::client"), LevelFilter::Off);
+        assert_eq!(directives.filter_for("other"), LevelFilter::Info);
+        assert_eq!(directives.max_filter(), LevelFilter::Trace);
+
+        // Levels are case-insensitive, and a bare target enables everything.
+        let directives = parse("WARN,my_crate::db").unwrap();
+        assert_eq!(directives.default, LevelFilter::Warn);
+        assert_eq!(directives.filter_for("my_crate::db"), LevelFilter::Trace);
+        assert_eq!(directives.filter_for("my_crate"), LevelFilter::Warn);
+
+        let directives = parse("").unwrap();
+        assert_eq!(directives.default, LevelFilter::Error);
+        assert!(directives.targets.is_empty());
+    }
+
+    #[test]
+    fn reject_malformed_directives() {
+        for (spec, directive) in [
+            ("my_crate=loud", "my_crate=loud"),
+            ("info,my_crate=", "my_crate="),
+            ("=debug", "=debug"),
+            ("my_crate=info=warn", "my_crate=info=warn"),
+            ("debug, a=trace, b = 3", "b = 3"),
+        ] {
+            assert_eq!(parse(spec).unwrap_err(), ParseFilterError::new(directive));
+        }
+        assert_eq!(
+            ParseFilterError::new("a=b").to_string(),
+            "invalid filter directive `a=b`"
+        );
+    }
+
+    #[test]
+    fn apply_nothing_on_error() {
+        let mut directives = Directives::new(LevelFilter::Error);
+        directives.insert("my_crate", LevelFilter::Warn);
+        assert!(directives.parse("trace,my_crate=debug,other=nope").is_err());
+        assert_eq!(directives.default, LevelFilter::Error);
+        assert_eq!(directives.filter_for("my_crate"), LevelFilter::Warn);
+        assert_eq!(directives.filter_for("other"), LevelFilter::Error);
+    }
+
+    #[test]
+    fn replace_duplicate_targets() {
+        let directives = parse("my_crate=trace,my_crate=warn,debug,info").unwrap();
+        assert_eq!(
+            directives.targets,
+            vec![("my_crate".to_owned(), LevelFilter::Warn)]
+        );
+        assert_eq!(directives.default, LevelFilter::Info);
+
+        let mut directives = parse("my_crate=trace").unwrap();
+        directives.parse("my_crate=off").unwrap();
+        assert_eq!(directives.filter_for("my_crate::db"), LevelFilter::Off);
+        assert_eq!(directives.targets.len(), 1);
+    }
+
+    #[test]
+    fn prefer_longest_prefix() {
+        let directives = parse("a=warn,a::b::c=trace,a::b=debug").unwrap();
+        assert_eq!(directives.filter_for("a"), LevelFilter::Warn);
+        assert_eq!(directives.filter_for("a::x"), LevelFilter::Warn);
+        assert_eq!(directives.filter_for("a::b"), LevelFilter::Debug);
+        assert_eq!(directives.filter_for("a::b::d"), LevelFilter::Debug);
+        assert_eq!(directives.filter_for("a::b::c"), LevelFilter::Trace);
+        assert_eq!(directives.filter_for("a::b::c::e"), LevelFilter::Trace);
+
+        // Prefixes only match whole path segments.
+        assert_eq!(directives.filter_for("ab"), LevelFilter::Error);
+        assert_eq!(directives.filter_for("a::bc"), LevelFilter::Warn);
+        assert_eq!(directives.filter_for("b::a"), LevelFilter::Error);
+    }
+}
@@ -0,0 +1,87 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Flushes all logging targets of the global preferences, returning the first error.
+///
+/// Records suppressed by [`dedup`](crate::LogPrefs::dedup) are summarized first.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() -> std::io::Result<()> {
+/// info!("Written before exiting");
+/// bunt_logger::flush()?;
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn flush() -> io::Result<()> {
+    crate::read().flush()
+}
+
+/// Stops logging, waits up to `timeout` for the threads of all [`WorkerGuard`]s still alive to
+/// write their queued output, and flushes all logging targets, returning whether everything was
+/// written in time.
+///
+/// Meant for short-lived programs such as batch jobs, right before they exit: logging stays
+/// [disabled](crate::LogPrefs::disable) afterwards, and so do the stopped [`NonBlocking`] writers
+/// and sinks. Flush errors are passed to the [error handler](crate::LogPrefs::on_error).
+///
+/// [`WorkerGuard`]: crate::WorkerGuard
+/// [`NonBlocking`]: crate::NonBlocking
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// use bunt_logger::{error, ColorChoice, NonBlocking, StandardStream};
+///
+/// # fn main() {
+/// let stderr = StandardStream::stderr(ColorChoice::Auto);
+/// let (writer, _guard) = NonBlocking::new(Box::new(stderr));
+/// bunt_logger::with().writer(Box::new(writer));
+///
+/// error!("Job failed");
+/// if !bunt_logger::shutdown(Duration::from_secs(5)) {
+///     eprintln!("Some records may be lost");
+/// }
+/// # }
+/// ```
+pub fn shutdown(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    crate::with().disable(true);
+    // Summaries of deduplicated records are still written, as they bypass the filters.
+    let flushed = flush();
+    // The threads flush their own writers before they finish.
+    let stopped = crate::writer::stop_workers(deadline);
+    let written = flushed.is_ok();
+    if let Err(err) = flushed {
+        (crate::read().on_error)(err);
+    }
+    written && stopped
+}
+
+/// A guard that flushes the logging targets when dropped, returned by
+/// [`Builder::init`](crate::Builder::init).
+///
+/// Keep it alive until the end of `main`, so that buffered records are written before the process
+/// exits. Errors are passed to the [error handler](crate::LogPrefs::on_error).
+#[must_use = "the logging targets are flushed as soon as the guard is dropped"]
+pub struct FlushGuard {
+    _private: (),
+}
+
+impl FlushGuard {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl Drop for FlushGuard {
+    #[inline]
+    fn drop(&mut self) {
+        crate::read().flush_or_report();
+    }
+}
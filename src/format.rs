@@ -0,0 +1,141 @@
+use std::io::{self, Write};
+
+use crate::{Field, Record, Value};
+
+/// The output format of records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Styled text, laid out by default or according to
+    /// [`LogPrefs::template`](crate::LogPrefs::template).
+    Text,
+    /// One JSON object per record and line, with the keys `time` (if
+    /// [timestamps](crate::LogPrefs::timestamps) are enabled), `level`, `target`, `kind` (if
+    /// any), `host` (if [shown](crate::LogPrefs::show_hostname)), `pid` (if
+    /// [shown](crate::LogPrefs::show_pid)), `process` (if
+    /// [shown](crate::LogPrefs::show_process_name)), `thread` (if
+    /// [shown](crate::LogPrefs::show_thread)), `scope` (if the record has
+    /// [scopes](crate::enter_scope), joined with `/`), `message` (without styles),
+    /// `file` and `line` (if [shown](crate::LogPrefs::show_location)), and `fields` (if any).
+    Json,
+}
+
+impl Default for Format {
+    #[inline]
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+/// The optional keys of [`Format::Json`] that are written.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct JsonKeys {
+    pub(crate) location: bool,
+    pub(crate) hostname: bool,
+    pub(crate) pid: bool,
+    pub(crate) process_name: bool,
+    pub(crate) thread: bool,
+}
+
+/// Writes `record` as a JSON object, without a trailing newline.
+pub(crate) fn write_json(
+    record: &Record,
+    timestamp: Option<&str>,
+    keys: JsonKeys,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    write!(writer, "{{")?;
+    if let Some(timestamp) = timestamp {
+        write!(writer, "\"time\":")?;
+        write_json_str(timestamp, writer)?;
+        write!(writer, ",")?;
+    }
+    write!(writer, "\"level\":\"{}\",\"target\":", record.level())?;
+    write_json_str(record.target(), writer)?;
+    if let Some(kind) = record.kind() {
+        write!(writer, ",\"kind\":")?;
+        write_json_str(&format!("{:?}", kind).to_lowercase(), writer)?;
+    }
+    if keys.hostname {
+        write!(writer, ",\"host\":")?;
+        write_json_str(crate::process::host_name(), writer)?;
+    }
+    if keys.pid {
+        write!(writer, ",\"pid\":{}", crate::process::id())?;
+    }
+    if keys.process_name {
+        write!(writer, ",\"process\":")?;
+        write_json_str(crate::process::name(), writer)?;
+    }
+    if keys.thread {
+        write!(writer, ",\"thread\":")?;
+        write_json_str(&record.thread_name(), writer)?;
+    }
+    if let Some(scope) = record.scope_path() {
+        write!(writer, ",\"scope\":")?;
+        write_json_str(&scope, writer)?;
+    }
+    write!(writer, ",\"message\":")?;
+    write_json_str(&record.message().to_string(), writer)?;
+    if let Some(location) = record.location().filter(|_| keys.location) {
+        write!(writer, ",\"file\":")?;
+        write_json_str(location.file(), writer)?;
+        write!(writer, ",\"line\":{}", location.line())?;
+    }
+    if !record.fields().is_empty() {
+        write!(writer, ",\"fields\":")?;
+        write_json_fields(record.fields(), writer)?;
+    }
+    write!(writer, "}}")
+}
+
+/// Writes `fields` as a JSON object.
+pub(crate) fn write_json_fields(fields: &[Field], writer: &mut dyn Write) -> io::Result<()> {
+    write!(writer, "{{")?;
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        write_json_str(field.key(), writer)?;
+        write!(writer, ":")?;
+        write_json_value(field.value(), writer)?;
+    }
+    write!(writer, "}}")
+}
+
+/// Writes `value` as a JSON value; non-finite numbers become `null`.
+pub(crate) fn write_json_value(value: &Value, writer: &mut dyn Write) -> io::Result<()> {
+    match value {
+        Value::Str(s) | Value::Debug(s) => write_json_str(s, writer),
+        Value::I64(v) => write!(writer, "{}", v),
+        Value::U64(v) => write!(writer, "{}", v),
+        Value::F64(v) if v.is_finite() => write!(writer, "{}", v),
+        Value::F64(_) => write!(writer, "null"),
+        Value::Bool(v) => write!(writer, "{}", v),
+    }
+}
+
+/// Writes `s` as a quoted and escaped JSON string.
+pub(crate) fn write_json_str(s: &str, writer: &mut dyn Write) -> io::Result<()> {
+    write!(writer, "\"")?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if c.is_control() => "",
+            _ => continue,
+        };
+        writer.write_all(&s.as_bytes()[start..i])?;
+        if escaped.is_empty() {
+            write!(writer, "\\u{:04x}", c as u32)?;
+        } else {
+            writer.write_all(escaped.as_bytes())?;
+        }
+        start = i + c.len_utf8();
+    }
+    writer.write_all(&s.as_bytes()[start..])?;
+    write!(writer, "\"")
+}
@@ -0,0 +1,85 @@
+use std::env;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::IndentGuard;
+
+/// Whether the process runs in a GitHub Actions workflow.
+static GITHUB_ACTIONS: Lazy<bool> =
+    Lazy::new(|| env::var_os("GITHUB_ACTIONS").is_some_and(|v| v == "true"));
+
+/// The indentation of the open groups when not running in GitHub Actions.
+static OPEN: Mutex<Vec<IndentGuard>> = Mutex::new(Vec::new());
+
+/// Starts a collapsible group of log output, returning a guard that ends it when dropped.
+///
+/// In GitHub Actions, the group is marked with `::group::` and `::endgroup::` workflow commands
+/// on stdout, and can be collapsed in the log viewer; GitHub doesn't support nesting groups.
+/// Otherwise, the title is logged in bold at the info level, and the records of the group are
+/// indented. See also [`group!`](crate::group!) and [`end_group!`](crate::end_group!).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// {
+///     let _group = bunt_logger::group("Running tests");
+///     info!("test parse ... ok");
+/// }
+/// info!("{$green}All tests passed{/$}");
+/// # }
+/// ```
+#[inline]
+pub fn group<T: Display>(title: T) -> GroupGuard {
+    begin_group(title);
+    GroupGuard { _private: () }
+}
+
+/// A guard that ends a group when dropped; see [`group`].
+#[must_use = "the group is ended as soon as the guard is dropped"]
+pub struct GroupGuard {
+    _private: (),
+}
+
+impl Drop for GroupGuard {
+    #[inline]
+    fn drop(&mut self) {
+        end_group();
+    }
+}
+
+#[doc(hidden)]
+pub fn begin_group<T: Display>(title: T) {
+    if *GITHUB_ACTIONS {
+        command(format_args!("::group::{}", title));
+    } else {
+        crate::info!(target: "group", "{$bold}{}{/$}", title);
+        lock().push(crate::indent());
+    }
+}
+
+#[doc(hidden)]
+pub fn end_group() {
+    if *GITHUB_ACTIONS {
+        command(format_args!("::endgroup::"));
+    } else {
+        lock().pop();
+    }
+}
+
+/// Writes a workflow command to stdout, after the records that were logged before it.
+fn command(command: std::fmt::Arguments<'_>) {
+    let _ = crate::flush();
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", command);
+    let _ = stdout.flush();
+}
+
+#[inline]
+fn lock() -> std::sync::MutexGuard<'static, Vec<IndentGuard>> {
+    OPEN.lock().unwrap_or_else(|err| err.into_inner())
+}
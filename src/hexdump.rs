@@ -0,0 +1,64 @@
+use crate::Message;
+
+/// The number of bytes shown per line.
+const BYTES_PER_LINE: usize = 16;
+
+/// The number of bytes [`hexdump!`](crate::hexdump) shows unless `max` is given.
+#[doc(hidden)]
+pub const DEFAULT_MAX: usize = 1024;
+
+/// Returns the lines of a hex dump of at most `max` bytes of `bytes`: the offset, the bytes in hex
+/// and the bytes as ASCII, followed by a line with the number of omitted bytes if any.
+#[doc(hidden)]
+pub fn hexdump_lines(bytes: &[u8], max: usize) -> Vec<Message> {
+    let shown = &bytes[..bytes.len().min(max)];
+    let mut lines: Vec<Message> = shown
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(i, chunk)| line(i * BYTES_PER_LINE, chunk))
+        .collect();
+    if shown.len() < bytes.len() {
+        let mut message = Message::new();
+        let _ = bunt::write!(
+            message,
+            "{$dimmed}… {} more bytes{/$}",
+            bytes.len() - shown.len()
+        );
+        lines.push(message);
+    }
+    lines
+}
+
+fn line(offset: usize, chunk: &[u8]) -> Message {
+    let mut message = Message::new();
+    let _ = bunt::write!(message, "{$dimmed}{:08x}{/$} ", offset);
+    for (i, &byte) in chunk.iter().enumerate() {
+        if i == BYTES_PER_LINE / 2 {
+            let _ = bunt::write!(message, " ");
+        }
+        if printable(byte) {
+            let _ = bunt::write!(message, " {:02x}", byte);
+        } else {
+            let _ = bunt::write!(message, " {$yellow}{:02x}{/$}", byte);
+        }
+    }
+
+    // Pad a short last line, so that the ASCII column stays aligned.
+    let missing = BYTES_PER_LINE - chunk.len();
+    let gap = usize::from(chunk.len() <= BYTES_PER_LINE / 2);
+    let _ = bunt::write!(message, "{}  {$dimmed}|{/$}", " ".repeat(missing * 3 + gap));
+    for &byte in chunk {
+        if printable(byte) {
+            let _ = bunt::write!(message, "{}", char::from(byte));
+        } else {
+            let _ = bunt::write!(message, "{$yellow}.{/$}");
+        }
+    }
+    let _ = bunt::write!(message, "{$dimmed}|{/$}");
+    message
+}
+
+#[inline]
+fn printable(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte == b' '
+}
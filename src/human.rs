@@ -0,0 +1,76 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Displays a number of bytes in the largest binary unit that keeps it above one, e.g. `512 B`,
+/// `1.5 KiB` or `14.2 MiB`.
+///
+/// Like all values, it can be styled in the logging macros, and it honors the width and alignment
+/// of the format string.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, HumanBytes};
+///
+/// # fn main() {
+/// assert_eq!(HumanBytes(14_889_779).to_string(), "14.2 MiB");
+/// info!("Downloaded {[green]}", HumanBytes(14_889_779));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanBytes(pub u64);
+
+impl fmt::Display for HumanBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return f.pad(&format!("{} B", self.0));
+        }
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        f.pad(&format!("{:.1} {}", value, UNITS[unit]))
+    }
+}
+
+/// Displays a duration in the most readable unit, e.g. `850µs`, `3.2ms`, `1.25s` or `2m05s`.
+///
+/// Like all values, it can be styled in the logging macros, and it honors the width and alignment
+/// of the format string.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// use bunt_logger::{info, HumanDuration};
+///
+/// # fn main() {
+/// assert_eq!(HumanDuration(Duration::from_millis(1250)).to_string(), "1.25s");
+/// info!("Finished in {[bold]}", HumanDuration(Duration::from_secs(125)));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let duration = self.0;
+        let formatted = if duration < Duration::from_millis(1) {
+            format!("{}µs", duration.as_micros())
+        } else if duration < Duration::from_secs(1) {
+            format!("{:.1}ms", duration.as_secs_f64() * 1e3)
+        } else if duration < Duration::from_secs(60) {
+            format!("{:.2}s", duration.as_secs_f64())
+        } else {
+            format!(
+                "{}m{:02}s",
+                duration.as_secs() / 60,
+                duration.as_secs() % 60
+            )
+        };
+        f.pad(&formatted)
+    }
+}
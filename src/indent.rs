@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of spaces per indentation level.
+pub(crate) const INDENT_WIDTH: usize = 2;
+
+static INDENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Increases the indentation of all subsequent messages by one level, returning a guard that
+/// decreases it again when dropped.
+///
+/// The indentation is global rather than per thread, and is applied to the message only, after the
+/// timestamp and level label. See also [`indented!`](crate::indented).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// info!("Building");
+/// {
+///     let _indent = bunt_logger::indent();
+///     info!("Compiling {$bold}bunt-logger{/$}");
+/// }
+/// info!("{$green}Done{/$}");
+/// # }
+/// ```
+#[inline]
+pub fn indent() -> IndentGuard {
+    INDENT.fetch_add(1, Ordering::Relaxed);
+    IndentGuard { _private: () }
+}
+
+/// Returns the current indentation level.
+#[inline]
+pub(crate) fn current() -> usize {
+    INDENT.load(Ordering::Relaxed)
+}
+
+/// A guard that decreases the indentation when dropped; see [`indent`].
+#[must_use = "the indentation is decreased as soon as the guard is dropped"]
+pub struct IndentGuard {
+    _private: (),
+}
+
+impl Drop for IndentGuard {
+    #[inline]
+    fn drop(&mut self) {
+        INDENT.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::Instant;
+
+use crate::{Global, Level};
+
+/// Logs leaving a function instrumented with `#[instrument]` when dropped.
+#[doc(hidden)]
+pub struct Span {
+    level: Level,
+    target: &'static str,
+    name: &'static str,
+    start: Instant,
+    enabled: bool,
+}
+
+impl Span {
+    #[inline]
+    pub fn enter(level: Level, target: &'static str, name: &'static str) -> Self {
+        Self {
+            level,
+            target,
+            name,
+            start: Instant::now(),
+            enabled: level <= crate::STATIC_MAX_LEVEL && Global.enabled(level, target),
+        }
+    }
+
+    /// Returns whether the records of the span are logged.
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if self.enabled {
+            crate::log!(
+                target: self.target,
+                self.level,
+                "{$bold}{}{/$} finished in {}",
+                self.name,
+                crate::HumanDuration(self.start.elapsed()),
+            );
+        }
+    }
+}
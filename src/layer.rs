@@ -0,0 +1,164 @@
+use std::fmt;
+use std::sync::Arc;
+
+use tracing::field::{Field as TracingField, Visit};
+use tracing::span::{Attributes, Id, Record as SpanRecord};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{Field, Level, Location, Message, Record, Value};
+
+/// A [`tracing_subscriber::Layer`] that logs `tracing` events through bunt-logger, with the same
+/// theme, logging targets and filters as the macros of the global preferences.
+///
+/// The `message` of an event becomes the message of its record, and its other fields, those of
+/// the spans it's in, outermost first, followed by its own. The names of the spans become
+/// [scopes](crate::enter_scope) of the record, inside those of the thread.
+///
+/// Spans and events that the preferences disable are disabled for the whole subscriber. Changes
+/// to the preferences apply to callsites that were already registered.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::BuntLoggerLayer;
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// # fn main() {
+/// let subscriber = tracing_subscriber::registry().with(BuntLoggerLayer::new());
+/// tracing::subscriber::set_global_default(subscriber).unwrap();
+///
+/// let span = tracing::info_span!("request", id = 7);
+/// let _entered = span.enter();
+/// tracing::info!(status = 200, "Handled"); // [request] Handled id=7 status=200
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuntLoggerLayer {
+    _private: (),
+}
+
+impl BuntLoggerLayer {
+    /// Returns a layer that logs through the global preferences.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for BuntLoggerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    #[inline]
+    fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+        // The filters may change at any time, so callsites are checked every time.
+        Interest::sometimes()
+    }
+
+    #[inline]
+    fn enabled(&self, metadata: &Metadata<'_>, _: Context<'_, S>) -> bool {
+        crate::Global.enabled(level(metadata.level()), metadata.target())
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &SpanRecord<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let message = Message::from(fields.message.take().unwrap_or_default());
+        let mut record = Record::new(level(metadata.level()), metadata.target(), message);
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            record = record.with_location(Location::new(file, line));
+        }
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                record.push_scope(Arc::from(span.name()));
+                if let Some(span_fields) = span.extensions().get::<Fields>() {
+                    record
+                        .fields_mut()
+                        .extend(span_fields.fields.iter().cloned());
+                }
+            }
+        }
+        record.fields_mut().append(&mut fields.fields);
+        crate::read().log_record(&record);
+    }
+}
+
+#[inline]
+fn level(level: &tracing::Level) -> Level {
+    match *level {
+        tracing::Level::ERROR => Level::Error,
+        tracing::Level::WARN => Level::Warn,
+        tracing::Level::INFO => Level::Info,
+        tracing::Level::DEBUG => Level::Debug,
+        tracing::Level::TRACE => Level::Trace,
+    }
+}
+
+/// Collects the fields of a `tracing` span or event, and the message of an event.
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    fields: Vec<Field>,
+}
+
+impl Fields {
+    #[inline]
+    fn push(&mut self, field: &TracingField, value: Value) {
+        self.fields.push(Field::new(field.name(), value));
+    }
+}
+
+impl Visit for Fields {
+    fn record_i64(&mut self, field: &TracingField, value: i64) {
+        self.push(field, Value::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &TracingField, value: u64) {
+        self.push(field, Value::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &TracingField, value: f64) {
+        self.push(field, Value::F64(value));
+    }
+
+    fn record_bool(&mut self, field: &TracingField, value: bool) {
+        self.push(field, Value::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &TracingField, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+        } else {
+            self.push(field, Value::Str(value.to_owned()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &TracingField, value: &dyn fmt::Debug) {
+        // The message of events is recorded as `format_args!`, whose `Debug` output is the text.
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.push(field, Value::Debug(format!("{:?}", value)));
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::env;
+use std::io::{self, Write};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+
+/// A writer that keeps track of the column that a record is written up to, and lays out the
+/// message and what follows it: lines after newlines in the message are aligned with its start,
+/// behind a gutter if the message has a prefix, and if a width is given, lines are wrapped at
+/// spaces with a hanging indent. Spaces at the end of wrapped lines are dropped.
+pub(crate) struct Layout<'a> {
+    writer: &'a mut dyn WriteColor,
+    width: Option<usize>,
+    column: usize,
+    /// The column that the message starts in, while it's being written.
+    indent: Option<usize>,
+    /// The style of the gutter of continuation lines.
+    gutter: ColorSpec,
+    /// Whether the message contained a newline, so that wrapped lines get a gutter as well.
+    continued: bool,
+    /// The style that was set last, restored after the gutter.
+    style: Option<ColorSpec>,
+    /// Spaces that are written before the next word, unless the line is wrapped before it.
+    spaces: usize,
+    /// The word that is being written, which is kept until its end is known.
+    word: String,
+}
+
+impl<'a> Layout<'a> {
+    #[inline]
+    pub(crate) fn new(writer: &'a mut dyn WriteColor, width: Option<usize>) -> Self {
+        Self {
+            writer,
+            width,
+            column: 0,
+            indent: None,
+            gutter: ColorSpec::new(),
+            continued: false,
+            style: None,
+            spaces: 0,
+            word: String::new(),
+        }
+    }
+
+    /// Marks the start of the message, which the following lines are aligned with.
+    #[inline]
+    pub(crate) fn start_message(&mut self, gutter: &ColorSpec) {
+        self.indent = Some(self.column);
+        self.gutter = gutter.clone();
+    }
+
+    /// Marks the end of the message and what follows it, before the final newline.
+    #[inline]
+    pub(crate) fn end_message(&mut self) -> io::Result<()> {
+        self.write_word()?;
+        self.indent = None;
+        self.spaces = 0;
+        Ok(())
+    }
+
+    /// Starts a new line that is aligned with the message, behind a gutter if the message
+    /// continues on it after a prefix.
+    fn new_line(&mut self, indent: usize) -> io::Result<()> {
+        self.spaces = 0;
+        self.writer.write_all(b"\n")?;
+        if !self.continued || indent < 2 {
+            write!(self.writer, "{:1$}", "", indent)?;
+            self.column = indent;
+            return Ok(());
+        }
+
+        let padding = indent.saturating_sub(2);
+        write!(self.writer, "{:1$}", "", padding)?;
+        self.writer.set_color(&self.gutter)?;
+        self.writer.write_all("│".as_bytes())?;
+        match &self.style {
+            Some(style) => self.writer.set_color(style)?,
+            None => self.writer.reset()?,
+        }
+        self.writer.write_all(b" ")?;
+        self.column = padding + 2;
+        Ok(())
+    }
+
+    /// Writes the word that is being written, on a new line if it doesn't fit the current one.
+    fn write_word(&mut self) -> io::Result<()> {
+        let (width, indent) = match (self.width, self.indent) {
+            (Some(width), Some(indent)) if !self.word.is_empty() => (width, indent),
+            _ => return Ok(()),
+        };
+
+        let len = visible_len(&self.word);
+        if self.column + self.spaces + len > width && self.column > indent {
+            self.new_line(indent)?;
+        }
+        if self.spaces > 0 {
+            write!(self.writer, "{:1$}", "", self.spaces)?;
+            self.column += self.spaces;
+            self.spaces = 0;
+        }
+        self.writer.write_all(self.word.as_bytes())?;
+        self.column += len;
+        self.word.clear();
+        Ok(())
+    }
+
+    fn write_message(&mut self, text: &str, indent: usize) -> io::Result<()> {
+        if self.width.is_none() {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.continued = true;
+                    self.new_line(indent)?;
+                }
+                self.writer.write_all(line.as_bytes())?;
+                self.column += visible_len(line);
+            }
+            return Ok(());
+        }
+
+        for c in text.chars() {
+            match c {
+                ' ' => {
+                    self.write_word()?;
+                    self.spaces += 1;
+                }
+                '\n' => {
+                    self.write_word()?;
+                    self.continued = true;
+                    self.new_line(indent)?;
+                }
+                _ => self.word.push(c),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for Layout<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        match self.indent {
+            Some(indent) => self.write_message(&text, indent)?,
+            None => {
+                self.writer.write_all(buf)?;
+                self.column = match text.rfind('\n') {
+                    Some(i) => visible_len(&text[i + 1..]),
+                    None => self.column + visible_len(&text),
+                };
+            }
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_word()?;
+        self.writer.flush()
+    }
+}
+
+impl WriteColor for Layout<'_> {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.writer.supports_color()
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.write_word()?;
+        self.style = Some(spec.clone());
+        self.writer.set_color(spec)
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.write_word()?;
+        self.style = None;
+        self.writer.reset()
+    }
+}
+
+/// Returns the number of characters of `text` that take up space, skipping the escape sequences
+/// of [hyperlinks](crate::Link).
+fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.as_str().starts_with(']') {
+            // Skip to the string terminator.
+            while let Some(c) = chars.next() {
+                if c == '\x1b' && chars.as_str().starts_with('\\') {
+                    chars.next();
+                    break;
+                }
+            }
+        } else if !c.is_control() {
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Returns the width of the terminal that stdout, or else stderr, is attached to, or the value of
+/// the `COLUMNS` environment variable.
+pub(crate) fn terminal_width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(sys::terminal_width)
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+mod sys {
+    pub(super) fn terminal_width() -> Option<usize> {
+        [1, 2].iter().find_map(|&fd| {
+            // SAFETY: `winsize` is plain data, for which zeros are valid.
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            // SAFETY: `TIOCGWINSZ` only writes a `winsize` to the given pointer, which is valid.
+            let result = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) };
+            (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+        })
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+    #[repr(C)]
+    struct Coord {
+        x: i16,
+        y: i16,
+    }
+
+    #[repr(C)]
+    struct SmallRect {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    }
+
+    #[repr(C)]
+    struct ConsoleScreenBufferInfo {
+        size: Coord,
+        cursor_position: Coord,
+        attributes: u16,
+        window: SmallRect,
+        maximum_window_size: Coord,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> *mut c_void;
+        fn GetConsoleScreenBufferInfo(
+            console_output: *mut c_void,
+            console_screen_buffer_info: *mut ConsoleScreenBufferInfo,
+        ) -> i32;
+    }
+
+    pub(super) fn terminal_width() -> Option<usize> {
+        [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+            .iter()
+            .find_map(|&std_handle| {
+                // SAFETY: `GetStdHandle` has no preconditions, and `GetConsoleScreenBufferInfo`
+                // only writes to the given pointer, which is valid, and fails for handles that
+                // aren't consoles.
+                let info = unsafe {
+                    let mut info: ConsoleScreenBufferInfo = std::mem::zeroed();
+                    let handle = GetStdHandle(std_handle);
+                    (GetConsoleScreenBufferInfo(handle, &mut info) != 0).then_some(info)
+                }?;
+                let width = i32::from(info.window.right) - i32::from(info.window.left) + 1;
+                usize::try_from(width).ok().filter(|&width| width > 0)
+            })
+    }
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+mod sys {
+    #[inline]
+    pub(super) fn terminal_width() -> Option<usize> {
+        None
+    }
+}
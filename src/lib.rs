@@ -21,178 +21,437 @@
 //!     trace!("{$white+dimmed}A white and dimmed trace message!{/$}");
 //! }
 //! ```
+//!
+//! # Compile-time filters
+//!
+//! Like in the `log` crate, records above a level can be removed at compile time with the
+//! `max_level_*` features, e.g. `max_level_debug`, and in release builds with the
+//! `release_max_level_*` features, e.g. `release_max_level_info`. The macros for disabled levels
+//! still type-check their arguments, but neither format them nor lock the preferences. See
+//! [`STATIC_MAX_LEVEL`].
 
-use std::sync::{Mutex, MutexGuard};
+mod builder;
+mod capture;
+#[cfg(feature = "clap")]
+mod cli;
+mod clock;
+mod color;
+mod config;
+mod context;
+#[cfg(unix)]
+mod control;
+mod dedup;
+mod facade;
+mod field;
+mod filter;
+mod flush;
+mod format;
+mod group;
+mod hexdump;
+mod human;
+mod indent;
+mod instrument;
+#[cfg(feature = "tracing")]
+mod layer;
+mod layout;
+mod limit;
+mod link;
+mod location;
+mod logger;
+mod macros;
+mod mdc;
+mod metrics;
+mod panic;
+mod prefix;
+mod process;
+mod recent;
+mod record;
+mod redact;
+mod result;
+mod sample;
+mod scope;
+#[cfg(unix)]
+mod signal;
+mod sink;
+mod stats;
+mod status;
+mod table;
+mod template;
+mod theme;
+mod thread;
+mod timestamp;
+mod writer;
 
-use log::LevelFilter;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::slice;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::time::Duration;
+
+use bunt::termcolor::NoColor;
 use once_cell::sync::Lazy;
 
-pub use bunt::termcolor::{ColorChoice, StandardStream, WriteColor};
-pub use log::Level;
+use crate::capture::CaptureTarget;
+use crate::color::Stream;
+use crate::config::Config;
+use crate::context::ErrorContext;
+use crate::dedup::Dedup;
+use crate::filter::Directives;
+use crate::layout::Layout;
+use crate::recent::Recent;
+use crate::redact::Redaction;
+use crate::sample::Sampler;
+use crate::sink::SharedSink;
+use crate::template::Template;
+use crate::timestamp::Delta;
+
+pub use bunt::termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+pub use log::{Level, LevelFilter, SetLoggerError};
+#[cfg(feature = "regex")]
+pub use regex::Regex;
 
+pub use crate::builder::{builder, Builder, InitError};
+pub use crate::capture::{capture, TestCapture};
+#[cfg(feature = "clap")]
+pub use crate::cli::Verbosity;
+#[cfg(feature = "chrono")]
+pub use crate::clock::ChronoClock;
+#[cfg(feature = "time")]
+pub use crate::clock::TimeClock;
+pub use crate::clock::{set_clock, Clock, MockClock, SystemClock};
+pub use crate::config::LoadConfigError;
+#[cfg(unix)]
+pub use crate::control::{control_socket, ControlSocket};
+pub use crate::field::{Field, Value};
+#[cfg(feature = "regex")]
+pub use crate::filter::FilterAction;
+pub use crate::filter::ParseFilterError;
+pub use crate::flush::{flush, shutdown, FlushGuard};
+pub use crate::format::Format;
 #[doc(hidden)]
-#[macro_export]
-macro_rules! try_log {
-    ($level:ident, $writer:ident => $b:block) => {{
-        let mut prefs = $crate::with();
-        if prefs.enabled($crate::Level::$level) {
-            let mut $writer = prefs.get_writer();
-            $b
-        }
-    }};
-}
+pub use crate::group::{begin_group, end_group};
+pub use crate::group::{group, GroupGuard};
+#[doc(hidden)]
+pub use crate::hexdump::{hexdump_lines, DEFAULT_MAX as HEXDUMP_DEFAULT_MAX};
+pub use crate::human::{HumanBytes, HumanDuration};
+pub use crate::indent::{indent, IndentGuard};
+#[doc(hidden)]
+pub use crate::instrument::Span;
+#[cfg(feature = "tracing")]
+pub use crate::layer::BuntLoggerLayer;
+#[doc(hidden)]
+pub use crate::limit::CallSite;
+pub use crate::link::Link;
+pub use crate::location::{Location, LocationMode};
+#[doc(hidden)]
+pub use crate::logger::Global;
+pub use crate::logger::Logger;
+pub use crate::mdc::{context, Context, ContextGuard};
+pub use crate::metrics::{metrics, Metrics};
+pub use crate::panic::install_panic_hook;
+pub use crate::prefix::{enter_scope, ScopeGuard};
+pub use crate::recent::recent;
+pub use crate::record::{Kind, Message, Record};
+pub use crate::result::ResultExt;
+pub use crate::scope::{with_scope, PrefsGuard};
+#[cfg(all(feature = "android", target_os = "android"))]
+pub use crate::sink::Logcat;
+#[cfg(feature = "sqlite")]
+pub use crate::sink::Sqlite;
+#[cfg(windows)]
+pub use crate::sink::{DebugOutput, EventLog};
+pub use crate::sink::{
+    Email, EmailBuilder, Fluentd, Gelf, HttpSink, HttpSinkBuilder, Sink, Webhook, WebhookBuilder,
+};
+#[cfg(feature = "kafka")]
+pub use crate::sink::{Kafka, KafkaBuilder};
+#[cfg(feature = "sentry")]
+pub use crate::sink::{Sentry, SentryBuilder};
+pub use crate::stats::{exit_code_from_severity, stats, Stats};
+pub use crate::status::{status, Status};
+pub use crate::table::{table, Table};
+pub use crate::template::ParseTemplateError;
+pub use crate::theme::Theme;
+pub use crate::thread::{thread_level, ThreadLevelGuard};
+pub use crate::timestamp::{TimeZone, TimestampFormat};
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub use crate::writer::ConsoleWriter;
+pub use crate::writer::{
+    reopen_files, Buffering, FileOptions, FileWriter, MultiWriter, NetWriter, NonBlocking,
+    NonBlockingBuilder, Output, Overflow, RotatingFileBuilder, RotatingFileWriter, Rotation,
+    WorkerGuard,
+};
+#[cfg(feature = "instrument")]
+pub use bunt_logger_macros::instrument;
 
-/// Like [`log::error`], but bunt-compatible.
-///
-/// # Example
-/// ```rust
-/// use bunt_logger::error;
+/// The environment variable read by [`from_default_env`].
+pub const DEFAULT_FILTER_ENV: &str = "BUNT_LOG";
+
+/// The most verbose level that can be logged, as configured by the `max_level_*` and
+/// `release_max_level_*` features; [`LevelFilter::Trace`] if none is enabled.
 ///
-/// # fn main() {
-/// let x = -1;
-/// error!("{$red}Not a positive number:{/$} {[bold]}", x);
-/// # }
-/// ```
-#[macro_export]
-macro_rules! error {
-    ($format_str:literal $(, $arg:expr)* $(,)?) => {
-        $crate::error!([$format_str] $(, $arg )*)
-    };
-    ([$($format_str:literal),+ $(,)?] $(, $arg:expr)* $(,)?) => {
-        $crate::try_log!(Error, writer => {
-            let _ = ::bunt::writeln!(writer, [$($format_str),+] $(, $arg )*);
-        })
-    }
+/// Records above this level are discarded at compile time, regardless of the runtime preferences.
+pub const STATIC_MAX_LEVEL: LevelFilter = static_max_level();
+
+#[allow(unreachable_code)]
+const fn static_max_level() -> LevelFilter {
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_off"))]
+    return LevelFilter::Off;
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_error"))]
+    return LevelFilter::Error;
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_warn"))]
+    return LevelFilter::Warn;
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_info"))]
+    return LevelFilter::Info;
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_debug"))]
+    return LevelFilter::Debug;
+    #[cfg(all(not(debug_assertions), feature = "release_max_level_trace"))]
+    return LevelFilter::Trace;
+
+    #[cfg(feature = "max_level_off")]
+    return LevelFilter::Off;
+    #[cfg(feature = "max_level_error")]
+    return LevelFilter::Error;
+    #[cfg(feature = "max_level_warn")]
+    return LevelFilter::Warn;
+    #[cfg(feature = "max_level_info")]
+    return LevelFilter::Info;
+    #[cfg(feature = "max_level_debug")]
+    return LevelFilter::Debug;
+
+    LevelFilter::Trace
 }
 
-/// Like [`log::warn`], but bunt-compatible.
+/// The most verbose level enabled by any directive, or `Off` while quiet. Kept in sync with
+/// [`LOGPREFS`] so that disabled records can be discarded without locking.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LevelFilter::Info as u8);
+
+/// Whether logging is [disabled](LogPrefs::disable). Kept in sync with [`LOGPREFS`] so that the
+/// logging macros can bail out before taking any lock.
+pub(crate) static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// The global preferences. Logging only takes the read lock, and each logging target has a lock of
+/// its own, so that threads only wait for each other while writing to the same target or while
+/// the preferences are modified.
+static LOGPREFS: Lazy<RwLock<LogPrefs>> = Lazy::new(|| {
+    Lazy::force(&timestamp::START);
+    let prefs = LogPrefs::new(true);
+    RwLock::new(prefs)
+});
+
+/// Returns a reference to the global preferences object, used for modifying preferences.
+///
+/// The guard locks out logging on all threads until it is dropped, so it should not be held longer
+/// than needed.
 ///
 /// # Example
 /// ```rust
-/// use bunt_logger::warn;
+/// use bunt_logger::{ColorChoice, Level};
 ///
-/// # fn main() {
-/// warn!("He likes {$yellow}lemons{/$}, like {$blue+italic}a lot{/$}.");
-/// # }
+/// fn main() {
+///     bunt_logger::with()
+///         .level(Level::Debug)
+///         .stdout(ColorChoice::Never);
+/// }
 /// ```
-#[macro_export]
-macro_rules! warn {
-    ($format_str:literal $(, $arg:expr)* $(,)?) => {
-        $crate::warn!([$format_str] $(, $arg )*)
-    };
-    ([$($format_str:literal),+ $(,)?] $(, $arg:expr)* $(,)?) => {
-        $crate::try_log!(Warn, writer => {
-            let _ = ::bunt::writeln!(writer, [$($format_str),+] $(, $arg )*);
-        })
-    }
+#[inline]
+pub fn with() -> RwLockWriteGuard<'static, LogPrefs> {
+    LOGPREFS.write().unwrap_or_else(|err| err.into_inner())
 }
 
-/// Like [`log::info`], but bunt-compatible.
-///
-/// # Example
-/// ```rust
-/// use bunt_logger::info;
-///
-/// # fn main() {
-/// let v = vec![1, 2, 3];
-/// info!("Here is some data: {[green]:?}.", v);
-/// # }
-/// ```
-#[macro_export]
-macro_rules! info {
-    ($format_str:literal $(, $arg:expr)* $(,)?) => {
-        $crate::info!([$format_str] $(, $arg )*)
-    };
-    ([$($format_str:literal),+ $(,)?] $(, $arg:expr)* $(,)?) => {
-        $crate::try_log!(Info, writer => {
-            let _ = ::bunt::writeln!(writer, [$($format_str),+] $(, $arg )*);
-        })
+/// Returns the global preferences for logging.
+#[inline]
+pub(crate) fn read() -> RwLockReadGuard<'static, LogPrefs> {
+    LOGPREFS.read().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Like [`read`], but returns `None` instead of blocking if the preferences are locked.
+#[inline]
+pub(crate) fn try_read() -> Option<RwLockReadGuard<'static, LogPrefs>> {
+    match LOGPREFS.try_read() {
+        Ok(prefs) => Some(prefs),
+        Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
     }
 }
 
-/// Like [`log::debug`], but bunt-compatible.
+/// Returns the most verbose level that may currently be logged.
+///
+/// Records above this level are discarded without locking the global preferences. Module
+/// directives can only make logging more specific, so a record at or below this level may still be
+/// filtered out.
 ///
 /// # Example
 /// ```rust
-/// use bunt_logger::debug;
+/// use bunt_logger::{Level, LevelFilter};
 ///
 /// # fn main() {
-/// let v = vec![1, 2, 3];
-/// debug!("{$bold}Length: {[cyan]}{/$}.", v.len());
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Warn { return; }
+/// bunt_logger::with().level(Level::Warn);
+/// assert_eq!(bunt_logger::max_level(), LevelFilter::Warn);
 /// # }
 /// ```
-#[macro_export]
-macro_rules! debug {
-    ($format_str:literal $(, $arg:expr)* $(,)?) => {
-        $crate::debug!([$format_str] $(, $arg )*)
-    };
-    ([$($format_str:literal),+ $(,)?] $(, $arg:expr)* $(,)?) => {
-        $crate::try_log!(Debug, writer => {
-            let _ = ::bunt::writeln!(writer, [$($format_str),+] $(, $arg )*);
-        })
+#[inline]
+pub fn max_level() -> LevelFilter {
+    match MAX_LEVEL.load(Ordering::Relaxed) {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
     }
 }
 
-/// Like [`log::trace`], but bunt-compatible.
+/// Like [`with`], but first applies the filter directives in the [`DEFAULT_FILTER_ENV`]
+/// environment variable (see [`LogPrefs::parse_env`]).
 ///
 /// # Example
 /// ```rust
-/// use bunt_logger::trace;
+/// use bunt_logger::ColorChoice;
 ///
-/// # fn main() {
-/// let v = vec![1, 2, 3];
-/// trace!("{$italic}Watch the mouse!{/$}.");
+/// # fn main() -> Result<(), bunt_logger::ParseFilterError> {
+/// bunt_logger::from_default_env()?
+///     .stderr(ColorChoice::Auto);
+/// # Ok(())
 /// # }
 /// ```
-#[macro_export]
-macro_rules! trace {
-    ($format_str:literal $(, $arg:expr)* $(,)?) => {
-        $crate::trace!([$format_str] $(, $arg )*)
-    };
-    ([$($format_str:literal),+ $(,)?] $(, $arg:expr)* $(,)?) => {
-        $crate::try_log!(Trace, writer => {
-            let _ = ::bunt::writeln!(writer, [$($format_str),+] $(, $arg )*);
-        })
-    }
+#[inline]
+pub fn from_default_env() -> Result<RwLockWriteGuard<'static, LogPrefs>, ParseFilterError> {
+    let mut prefs = with();
+    prefs.parse_env(DEFAULT_FILTER_ENV)?;
+    Ok(prefs)
 }
 
-static LOGPREFS: Lazy<Mutex<LogPrefs>> = Lazy::new(|| {
-    let prefs = LogPrefs::new();
-    Mutex::new(prefs)
-});
+/// A function that modifies, enriches or drops records; see [`LogPrefs::add_hook`].
+type Hook = dyn Fn(&mut Record) -> bool + Send + Sync;
 
-/// Returns a reference to the global preferences object, used for modifying preferences.
-///
-/// # Example
-/// ```rust
-/// use bunt_logger::{ColorChoice, Level};
-///
-/// fn main() {
-///     bunt_logger::with()
-///         .level(Level::Debug)
-///         .stdout(ColorChoice::Never);
-/// }
-/// ```
-#[inline]
-pub fn with() -> MutexGuard<'static, LogPrefs> {
-    LOGPREFS.lock().unwrap()
+/// A function that wraps writing a record; see [`LogPrefs::suspend_with`].
+type Suspend = dyn Fn(&mut dyn FnMut()) + Send + Sync;
+
+/// The default error handler: prints the first error of the logging targets to stderr.
+fn report_error_once(err: io::Error) {
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    if !REPORTED.swap(true, Ordering::Relaxed) {
+        eprintln!("bunt-logger: failed to write a record: {}", err);
+    }
+}
+
+/// Returns the level `steps` more verbose than `base`, saturating at trace, or `None` if that is
+/// less verbose than error.
+fn step_level(base: Level, steps: i16) -> Option<Level> {
+    let index = base as i16 + steps;
+    if index < 1 {
+        return None;
+    }
+    Level::iter().nth(index.min(5) as usize - 1)
 }
 
 /// Preferences that dictate logging.
+///
+/// Clones share the logging targets of the original.
+#[derive(Clone)]
 pub struct LogPrefs {
+    /// Whether these are the global preferences (or a snapshot of them), rather than those of a
+    /// [`Builder`].
+    global: bool,
     quiet: bool,
-    filter: LevelFilter,
+    disabled: bool,
+    /// The level that [`LogPrefs::verbosity`] steps from, and the current number of steps.
+    base_level: Level,
+    verbosity: i16,
+    color_env: bool,
+    filter: Directives,
+    /// Indexed by `level as usize - 1`; errors are never sampled.
+    samplers: [Option<Arc<Sampler>>; 5],
+    #[cfg(feature = "regex")]
+    message_filters: Vec<(Regex, FilterAction)>,
+    redactions: Vec<Redaction>,
+    max_message_len: Option<usize>,
+    hooks: Vec<Arc<Hook>>,
+    facade: bool,
+    timestamps: TimestampFormat,
+    time_zone: TimeZone,
+    delta: Option<Delta>,
+    show_level: bool,
+    show_location: LocationMode,
+    show_thread: bool,
+    show_pid: bool,
+    show_process_name: bool,
+    show_hostname: bool,
+    wrap: bool,
+    template: Option<Template>,
+    theme: Theme,
+    format: Format,
+    dedup: Option<Arc<Mutex<Dedup>>>,
+    recent: Option<Recent>,
+    context: Option<ErrorContext>,
 
-    writer: Box<dyn WriteColor + Send>,
+    writers: Vec<Output>,
+    /// Whether the first of `writers` is still the default stdout target.
+    default_target: bool,
+    /// Targets that replace `writers` for records of a level, indexed by `level as usize - 1`.
+    routes: [Option<Output>; 5],
+    sinks: Vec<SharedSink>,
+    on_error: fn(io::Error),
+    /// Wraps writing to the logging targets; see [`LogPrefs::suspend_with`].
+    suspend: Option<Arc<Suspend>>,
+    captures: Arc<Mutex<Vec<CaptureTarget>>>,
+    /// The configuration file and filter variable that were loaded last, reloaded on SIGHUP.
+    config_file: Option<PathBuf>,
+    filter_env: Option<String>,
 }
 
 impl LogPrefs {
     #[inline]
-    fn new() -> Self {
+    fn new(global: bool) -> Self {
         Self {
+            global,
             quiet: false,
-            filter: LevelFilter::Info,
-            writer: Box::new(StandardStream::stdout(ColorChoice::Auto)),
+            disabled: false,
+            base_level: Level::Info,
+            verbosity: 0,
+            color_env: true,
+            filter: Directives::new(LevelFilter::Info),
+            samplers: Default::default(),
+            #[cfg(feature = "regex")]
+            message_filters: Vec::new(),
+            redactions: Vec::new(),
+            max_message_len: None,
+            hooks: Vec::new(),
+            facade: false,
+            timestamps: TimestampFormat::Off,
+            time_zone: TimeZone::Utc,
+            delta: None,
+            show_level: false,
+            show_location: LocationMode::Never,
+            show_thread: false,
+            show_pid: false,
+            show_process_name: false,
+            show_hostname: false,
+            wrap: false,
+            template: None,
+            theme: Theme::default(),
+            format: Format::Text,
+            dedup: None,
+            recent: None,
+            context: None,
+            writers: vec![Output::standard(
+                Stream::Stdout,
+                color::resolve(ColorChoice::Auto, Stream::Stdout),
+            )],
+            default_target: true,
+            routes: Default::default(),
+            sinks: Vec::new(),
+            on_error: report_error_once,
+            suspend: None,
+            captures: Default::default(),
+            config_file: None,
+            filter_env: None,
         }
     }
 
@@ -207,11 +466,40 @@ impl LogPrefs {
     #[inline]
     pub fn quiet(&mut self, quiet: bool) -> &mut Self {
         self.quiet = quiet;
+        self.sync_max_level();
+        self
+    }
+
+    /// Sets whether logging is disabled entirely.
+    ///
+    /// Unlike [`quiet`](Self::quiet), which still locks the preferences for records that are kept
+    /// in memory or enabled by a [thread level](crate::thread_level), this discards every record
+    /// before its message is formatted or any lock is taken, so that logging costs no more than an
+    /// atomic load. Nothing is kept for the [recent records](Self::keep_recent) or the
+    /// [error context](Self::context_on_error) either.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Level;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().disable(true);
+    /// assert!(!bunt_logger::log_enabled!(Level::Error));
+    /// # bunt_logger::with().disable(false);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn disable(&mut self, disable: bool) -> &mut Self {
+        self.disabled = disable;
+        self.sync_max_level();
         self
     }
 
     /// Sets the log level.
     ///
+    /// This is the level used for every module without a more specific
+    /// [`module_level`](Self::module_level) directive.
+    ///
     /// # Example
     /// ```rust
     /// use bunt_logger::Level;
@@ -222,71 +510,1651 @@ impl LogPrefs {
     /// ```
     #[inline]
     pub fn level(&mut self, level: Level) -> &mut Self {
-        self.filter = level.to_level_filter();
+        self.filter.set_default(level.to_level_filter());
+        self.sync_max_level();
         self
     }
 
-    /// Sets the logging target.
+    /// Sets the log level and quiet flag from a verbosity, such as the number of `-v` flags minus
+    /// the number of `-q` flags given on the command line.
     ///
-    /// By default, `StandardStream::stdout(ColorChoice::Auto)` is used.
+    /// A verbosity of `0` logs at the [base level](Self::base_level); each step above enables the
+    /// next more verbose level, up to trace, and each step below the next less verbose one, down
+    /// to error. Any verbosity below that makes the logger [quiet](Self::quiet).
+    ///
+    /// With the `clap` feature, `Verbosity` provides the flags.
     ///
     /// # Example
     /// ```rust
-    /// use bunt_logger::{ColorChoice, StandardStream};
+    /// use bunt_logger::LevelFilter;
     ///
     /// # fn main() {
-    /// let stderr_writer = StandardStream::stderr(ColorChoice::Never);
+    /// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Trace { return; }
+    /// let (verbose, quiet) = (2, 0);
+    /// bunt_logger::with().verbosity(verbose - quiet);
+    /// assert_eq!(bunt_logger::max_level(), LevelFilter::Trace);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn verbosity(&mut self, verbosity: i8) -> &mut Self {
+        self.verbosity = i16::from(verbosity);
+        self.apply_verbosity()
+    }
+
+    /// Increases the [verbosity](Self::verbosity) by `steps`, e.g. by the number of `-v` flags.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{Level, LevelFilter};
+    ///
+    /// # fn main() {
+    /// let (verbose, quiet) = (1, 3);
     /// bunt_logger::with()
-    ///     .writer(Box::new(stderr_writer));
+    ///     .base_level(Level::Warn)
+    ///     .more_verbose(verbose)
+    ///     .less_verbose(quiet);
+    /// assert_eq!(bunt_logger::max_level(), LevelFilter::Off);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn more_verbose(&mut self, steps: u8) -> &mut Self {
+        self.verbosity = self.verbosity.saturating_add(i16::from(steps));
+        self.apply_verbosity()
+    }
+
+    /// Decreases the [verbosity](Self::verbosity) by `steps`, e.g. by the number of `-q` flags.
+    #[inline]
+    pub fn less_verbose(&mut self, steps: u8) -> &mut Self {
+        self.verbosity = self.verbosity.saturating_sub(i16::from(steps));
+        self.apply_verbosity()
+    }
+
+    /// Sets the level logged at a [verbosity](Self::verbosity) of `0`, keeping the current
+    /// verbosity. Defaults to [`Level::Info`].
+    #[inline]
+    pub fn base_level(&mut self, level: Level) -> &mut Self {
+        self.base_level = level;
+        self.apply_verbosity()
+    }
+
+    fn apply_verbosity(&mut self) -> &mut Self {
+        match step_level(self.base_level, self.verbosity) {
+            Some(level) => self.quiet(false).level(level),
+            None => self.quiet(true),
+        }
+    }
+
+    /// Sets the log level for records coming from the module `module` and its submodules.
+    ///
+    /// When several directives match a module, the one with the longest module path wins.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Level;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .level(Level::Trace)
+    ///     .module_level("hyper", Level::Warn)
+    ///     .module_level("hyper::client", Level::Info);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn module_level(&mut self, module: &str, level: Level) -> &mut Self {
+        self.module_filter(module, level.to_level_filter())
+    }
+
+    /// Like [`module_level`](Self::module_level), but takes a [`LevelFilter`] so that a module
+    /// can be silenced entirely with [`LevelFilter::Off`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::LevelFilter;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().module_filter("hyper", LevelFilter::Off);
     /// # }
     /// ```
     #[inline]
-    pub fn writer(&mut self, writer: Box<dyn WriteColor + Send + Sync>) -> &mut Self {
-        self.writer = writer;
+    pub fn module_filter(&mut self, module: &str, filter: LevelFilter) -> &mut Self {
+        self.filter.insert(module, filter);
+        self.sync_max_level();
         self
     }
 
-    /// Sets the logging target to stdout with the given [`ColorChoice`].
+    /// Adds a filter on the plain text of messages, so that a known-noisy line can be suppressed
+    /// at runtime without changing the code that logs it.
+    ///
+    /// Filters apply to records that pass the level filters, in the order they were added: the
+    /// first filter whose pattern matches the message decides whether the record is logged.
+    /// Records that match no filter are logged, so [`FilterAction::Allow`] only has an effect on
+    /// records that a [`Deny`](FilterAction::Deny) filter added after it would drop. Add the
+    /// exceptions first and the broader denials after them. Requires the `regex` feature.
     ///
     /// # Example
     /// ```rust
-    /// use bunt_logger::ColorChoice;
+    /// use bunt_logger::{FilterAction, Regex};
+    ///
+    /// # fn main() -> Result<(), regex::Error> {
+    /// bunt_logger::with().filter_message(Regex::new(r"^GET /health")?, FilterAction::Deny);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Dropping health checks unless they failed, since the first matching filter wins:
+    /// ```rust
+    /// use bunt_logger::{FilterAction, Regex};
+    ///
+    /// # fn main() -> Result<(), regex::Error> {
+    /// bunt_logger::with()
+    ///     .filter_message(Regex::new(r"^GET /health 5\d\d$")?, FilterAction::Allow)
+    ///     .filter_message(Regex::new(r"^GET /health")?, FilterAction::Deny);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn filter_message(&mut self, pattern: Regex, action: FilterAction) -> &mut Self {
+        self.message_filters.push((pattern, action));
+        self
+    }
+
+    /// Adds a hook that is called with every record that passes the level filters, before any
+    /// other processing, to modify, enrich or drop it. A hook returns whether the record is
+    /// logged; once a hook returns `false`, later hooks aren't called.
+    ///
+    /// Hooks run in the order they were added.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{Field, Record};
+    ///
+    /// fn enrich(record: &mut Record) -> bool {
+    ///     if record.message().to_string().starts_with("GET /health") {
+    ///         return false;
+    ///     }
+    ///     record.fields_mut().push(Field::new("pid", std::process::id()));
+    ///     true
+    /// }
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().add_hook(enrich);
+    /// # }
+    /// ```
+    ///
+    /// Hooks may also be closures, e.g. to tag records with a value known at startup:
+    /// ```rust
+    /// use bunt_logger::Field;
+    ///
+    /// # fn main() {
+    /// let region = std::env::var("REGION").unwrap_or_default();
+    /// bunt_logger::with().add_hook(move |record| {
+    ///     record.fields_mut().push(Field::new("region", region.clone()));
+    ///     true
+    /// });
+    /// # }
+    /// ```
+    #[inline]
+    pub fn add_hook<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut Record) -> bool + Send + Sync + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Replaces every match of `pattern` in records with `replacement`, before any logging target
+    /// sees them, to keep credentials out of log files.
+    ///
+    /// Redactions apply in the order they were added, to the text of messages and to the string
+    /// values of fields, which are matched as `key=value`. The rest of a redacted message keeps
+    /// its styles, and the replacement takes the style of the text where the match starts.
+    /// Message filters (see [`filter_message`](Self::filter_message)) see the redacted text. The
+    /// replacement is inserted as it is, without expanding `$` references to groups. Requires
+    /// the `regex` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Regex;
+    ///
+    /// # fn main() -> Result<(), regex::Error> {
+    /// bunt_logger::with().redact(Regex::new(r"password=\S+")?, "password=***");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn redact(&mut self, pattern: Regex, replacement: &str) -> &mut Self {
+        self.redactions
+            .push(Redaction::Pattern(pattern, replacement.to_owned()));
+        self
+    }
+
+    /// Scrubs secrets from records with a function that returns the redacted text, before any
+    /// logging target sees them, like `redact` does with regular expressions. It applies to the
+    /// text of messages and to the string values of fields, which are passed as `key=value`. To
+    /// keep their styles, the function is called on each differently styled part of a message
+    /// separately.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// let token = "s3cr3t-t0k3n".to_owned();
+    /// bunt_logger::with().redact_with(move |text| text.replace(&token, "***"));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn redact_with<F>(&mut self, redact: F) -> &mut Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.redactions.push(Redaction::Custom(Arc::new(redact)));
+        self
+    }
+
+    /// Truncates messages that are longer than `max` characters, ending them with a dimmed
+    /// `… (+N chars)` instead, so that accidentally logging a huge value doesn't flood the logging
+    /// targets. Fields aren't truncated. By default, messages have no maximum length.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().max_message_len(500);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn max_message_len(&mut self, max: usize) -> &mut Self {
+        self.max_message_len = Some(max);
+        self
+    }
+
+    /// Keeps only a fraction `rate` of the records of `level` that pass the filters, so that
+    /// debug or trace records in hot loops can stay enabled without drowning the logging targets.
+    ///
+    /// The kept records are spread evenly: a rate of `0.01` keeps every 100th record. A rate of
+    /// `1.0` or more keeps all records again. Errors are always kept, so sampling them has no
+    /// effect.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Level;
     ///
     /// # fn main() {
     /// bunt_logger::with()
-    ///     .stdout(ColorChoice::Always);
+    ///     .level(Level::Debug)
+    ///     .sample(Level::Debug, 0.01);
     /// # }
     /// ```
     #[inline]
-    pub fn stdout(&mut self, color: ColorChoice) -> &mut Self {
-        self.writer(Box::new(StandardStream::stdout(color)))
+    pub fn sample(&mut self, level: Level, rate: f64) -> &mut Self {
+        if level != Level::Error {
+            self.samplers[level as usize - 1] = match rate {
+                rate if rate >= 1.0 => None,
+                rate => Some(Arc::new(Sampler::new(rate.max(0.0)))),
+            };
+        }
+        self
     }
 
-    /// Sets the logging target to stderr with the given [`ColorChoice`].
+    /// Sets the format of the timestamp printed before each record, in the
+    /// [time zone](Self::time_zone) of the preferences.
+    ///
+    /// By default, no timestamp is printed.
     ///
     /// # Example
     /// ```rust
-    /// use bunt_logger::ColorChoice;
+    /// use bunt_logger::TimestampFormat;
     ///
     /// # fn main() {
     /// bunt_logger::with()
-    ///     .stderr(ColorChoice::Always);
+    ///     .timestamps(TimestampFormat::Millis);
+    ///
+    /// bunt_logger::with()
+    ///     .timestamps(TimestampFormat::Custom("%H:%M:%S".to_owned()));
     /// # }
     /// ```
     #[inline]
-    pub fn stderr(&mut self, color: ColorChoice) -> &mut Self {
-        self.writer(Box::new(StandardStream::stderr(color)))
+    pub fn timestamps(&mut self, format: TimestampFormat) -> &mut Self {
+        self.timestamps = format;
+        self
     }
 
-    #[doc(hidden)]
+    /// Sets the time zone of [timestamps](Self::timestamps): UTC, so that the logs of a fleet
+    /// spread over time zones line up, or the local time of the machine, which reads better in
+    /// an interactive terminal. Local timestamps end with their UTC offset, e.g.
+    /// `2021-08-16T11:30:00+02:00`. Defaults to [`TimeZone::Utc`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{TimeZone, TimestampFormat};
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .timestamps(TimestampFormat::Seconds)
+    ///     .time_zone(TimeZone::Local);
+    /// # }
+    /// ```
     #[inline]
-    pub fn enabled(&self, level: Level) -> bool {
-        !self.quiet && self.filter >= level
+    pub fn time_zone(&mut self, zone: TimeZone) -> &mut Self {
+        self.time_zone = zone;
+        self
     }
 
-    #[doc(hidden)]
+    /// Sets whether each record is prefixed with the time since the previous record, such as
+    /// `+3.2ms`, to spot slow steps directly from the log. The first record measures from when the
+    /// global preferences were first used.
+    ///
+    /// Deltas of at least `slow` are printed in the warning style of the [theme](Self::theme),
+    /// others in its metadata style. Pass `None` to stop printing deltas, which is the default.
+    /// Deltas are only part of the default layout, not of [templates](Self::template) or JSON.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().show_delta(Some(Duration::from_millis(500)));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_delta(&mut self, slow: Option<Duration>) -> &mut Self {
+        self.delta = match (self.delta.take(), slow) {
+            (Some(mut delta), Some(slow)) => {
+                delta.slow = slow;
+                Some(delta)
+            }
+            (None, Some(slow)) => Some(Delta::new(slow)),
+            (_, None) => None,
+        };
+        self
+    }
+
+    /// Sets whether each record is prefixed with its colored level label, such as `ERROR` or
+    /// `WARN`.
+    ///
+    /// Records logged with [`success!`], [`notice!`] and [`fatal!`] always carry their label.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().show_level(true);
+    /// # }
+    /// ```
     #[inline]
-    pub fn get_writer<'a>(&'a mut self) -> &'a mut Box<dyn WriteColor + Send> {
-        &mut self.writer
+    pub fn show_level(&mut self, show: bool) -> &mut Self {
+        self.show_level = show;
+        self
+    }
+
+    /// Sets when the source location of records, such as `src/main.rs:12`, is printed. It is
+    /// printed in the metadata style of the [theme](Self::theme) after the message and fields.
+    /// Defaults to [`LocationMode::Never`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::LocationMode;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().show_location(LocationMode::DebugOnly);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_location(&mut self, mode: LocationMode) -> &mut Self {
+        self.show_location = mode;
+        self
+    }
+
+    /// Sets whether each record is prefixed with the name of the thread that logged it, or its id
+    /// if it is unnamed, in the metadata style of the [theme](Self::theme). Defaults to `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().show_thread(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_thread(&mut self, show: bool) -> &mut Self {
+        self.show_thread = show;
+        self
+    }
+
+    /// Sets whether each record is prefixed with the id of the process, in the metadata style of
+    /// the [theme](Self::theme), e.g. `[4242]`, or `my-tool[4242]` together with
+    /// [`show_process_name`](Self::show_process_name). Defaults to `false`.
+    ///
+    /// This tells apart the records of several instances of a program that share a log file or
+    /// collector.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().show_pid(true).show_process_name(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_pid(&mut self, show: bool) -> &mut Self {
+        self.show_pid = show;
+        self
+    }
+
+    /// Sets whether each record is prefixed with the name that the program was started with,
+    /// without its directory and extension, in the metadata style of the [theme](Self::theme).
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().show_process_name(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_process_name(&mut self, show: bool) -> &mut Self {
+        self.show_process_name = show;
+        self
+    }
+
+    /// Sets whether each record is prefixed with the host name of the machine, in the metadata
+    /// style of the [theme](Self::theme), so that records stay attributable once they are
+    /// aggregated. The name is looked up once. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Format;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().format(Format::Json).show_hostname(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn show_hostname(&mut self, show: bool) -> &mut Self {
+        self.show_hostname = show;
+        if show {
+            process::host_name();
+        }
+        self
+    }
+
+    /// Sets whether messages that don't fit the width of the terminal are wrapped at spaces, with
+    /// the following lines indented to where the message starts. Defaults to `false`.
+    ///
+    /// Only logging targets that support color are wrapped. The width is that of the terminal of
+    /// stdout, or else stderr, at the time the record is written, or the value of the `COLUMNS`
+    /// environment variable if set. Wrapping doesn't apply to templates and the JSON format.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().wrap_messages(true);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn wrap_messages(&mut self, wrap: bool) -> &mut Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets the theme that styles the level labels and, optionally, whole messages.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Theme;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .show_level(true)
+    ///     .theme(Theme::monochrome());
+    /// # }
+    /// ```
+    #[inline]
+    pub fn theme(&mut self, theme: Theme) -> &mut Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the template that lays out each record, replacing the default layout of timestamp,
+    /// level label (see [`show_level`](Self::show_level)) and message.
+    ///
+    /// Placeholders are written in braces:
+    ///
+    /// - `{time}`: the timestamp, formatted according to [`timestamps`](Self::timestamps)
+    /// - `{level}`: the colored level label
+    /// - `{target}`: the target of the record
+    /// - `{message}`: the message
+    /// - `{fields}`: the [fields](Field) of the record
+    /// - `{location}`: the source location, regardless of [`show_location`](Self::show_location)
+    /// - `{thread}`: the thread name, regardless of [`show_thread`](Self::show_thread)
+    /// - `{host}`: the host name, regardless of [`show_hostname`](Self::show_hostname)
+    /// - `{pid}`: the process id, regardless of [`show_pid`](Self::show_pid)
+    /// - `{process}`: the process name, regardless of
+    ///   [`show_process_name`](Self::show_process_name)
+    /// - `{scope}`: the names of the [scopes](crate::enter_scope) of the record, joined with `/`
+    ///
+    /// A minimum width can be given after a colon, e.g. `{level:5}`; shorter values are padded
+    /// with spaces. Literal braces are written as `{{` and `}}`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::TimestampFormat;
+    ///
+    /// # fn main() -> Result<(), bunt_logger::ParseTemplateError> {
+    /// bunt_logger::with()
+    ///     .timestamps(TimestampFormat::Seconds)
+    ///     .template("{time} [{level:5}] {target}: {message}")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn template(&mut self, template: &str) -> Result<&mut Self, ParseTemplateError> {
+        self.template = Some(Template::parse(template)?);
+        Ok(self)
+    }
+
+    /// Sets the output format of records. With [`Format::Json`], styles, the theme, and the
+    /// template are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Format;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().format(Format::Json);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets whether consecutive identical records are collapsed. Repetitions are dropped and
+    /// summarized by a record like `last message repeated 3 more times` once a different record is
+    /// logged, when the logger is flushed, or when deduplication is disabled. Records are
+    /// identical if everything but their timestamps matches. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::warn;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().dedup(true);
+    /// for _ in 0..3 {
+    ///     warn!("Connection lost");
+    /// }
+    /// bunt_logger::with().dedup(false);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn dedup(&mut self, dedup: bool) -> &mut Self {
+        if !dedup {
+            self.flush_dedup();
+            self.dedup = None;
+        } else if self.dedup.is_none() {
+            self.dedup = Some(Default::default());
+        }
+        self
+    }
+
+    /// Keeps the last `capacity` records up to `filter` in memory, rendered without styles, so
+    /// that crash handlers and bug-report commands can retrieve them with [`recent`](fn@recent).
+    ///
+    /// Records are kept even if the level filters discard them, so a buffer at
+    /// [`LevelFilter::Debug`] holds debug records while the console only shows warnings. A
+    /// capacity of `0` stops keeping records, which is the default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{Level, LevelFilter};
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .level(Level::Warn)
+    ///     .keep_recent(500, LevelFilter::Debug);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn keep_recent(&mut self, capacity: usize, filter: LevelFilter) -> &mut Self {
+        self.recent = match capacity {
+            0 => None,
+            capacity => Some(Recent::new(capacity, filter)),
+        };
+        self.sync_max_level();
+        self
+    }
+
+    /// Holds back the last `capacity` records up to `filter` that the level filters discard, and
+    /// writes them, dimmed, right before the next error. This gives detailed diagnostics around
+    /// failures without verbose output otherwise. A capacity of `0` stops holding back records,
+    /// which is the default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{debug, error, Level, LevelFilter};
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .level(Level::Info)
+    ///     .context_on_error(50, LevelFilter::Debug);
+    /// debug!("Connecting to 10.0.0.1");
+    /// error!("Connection refused");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn context_on_error(&mut self, capacity: usize, filter: LevelFilter) -> &mut Self {
+        self.context = match capacity {
+            0 => None,
+            capacity => Some(ErrorContext::new(capacity, filter)),
+        };
+        self.sync_max_level();
+        self
+    }
+
+    /// Sets the logging target.
+    ///
+    /// By default, `StandardStream::stdout(ColorChoice::Auto)` is used.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{ColorChoice, StandardStream};
+    ///
+    /// # fn main() {
+    /// let stderr_writer = StandardStream::stderr(ColorChoice::Never);
+    /// bunt_logger::with()
+    ///     .writer(Box::new(stderr_writer));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn writer(&mut self, writer: Box<dyn WriteColor + Send>) -> &mut Self {
+        self.writers = vec![Output::new(writer)];
+        self.default_target = false;
+        self
+    }
+
+    /// Adds a logging target in addition to the current ones, so that every record is written to
+    /// all of them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{ColorChoice, StandardStream};
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .stdout(ColorChoice::Auto)
+    ///     .add_writer(Box::new(StandardStream::stderr(ColorChoice::Never)));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn add_writer(&mut self, writer: Box<dyn WriteColor + Send>) -> &mut Self {
+        self.add_output(Output::new(writer))
+    }
+
+    /// Adds a logging target with its own output settings in addition to the current ones, for
+    /// example to write styled text to the terminal and JSON to a file.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use bunt_logger::{ColorChoice, FileWriter, Format, Output};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let file = FileWriter::open("logs/app.ndjson")?;
+    /// bunt_logger::with()
+    ///     .stdout(ColorChoice::Auto)
+    ///     .add_output(Output::new(Box::new(file)).with_format(Format::Json));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn add_output(&mut self, output: Output) -> &mut Self {
+        self.writers.push(output);
+        self
+    }
+
+    /// Adds a [`Sink`] that receives every record in addition to the writers.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use bunt_logger::{Record, Sink};
+    ///
+    /// struct Discard;
+    ///
+    /// impl Sink for Discard {
+    ///     fn log(&mut self, _record: &Record) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().add_sink(Box::new(Discard));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn add_sink(&mut self, sink: Box<dyn Sink>) -> &mut Self {
+        self.sinks.push(SharedSink::new(sink));
+        self
+    }
+
+    /// Adds a [`Gelf`] sink that sends every record over UDP to the Graylog input at `addr`, e.g.
+    /// `"graylog.internal:12201"`. Fails if the address can't be resolved. Use
+    /// [`add_sink`](Self::add_sink) with [`Gelf::tcp`] to send over TCP instead, or to set the
+    /// host name.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// bunt_logger::with().gelf("graylog.internal:12201")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn gelf<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<&mut Self> {
+        Ok(self.add_sink(Box::new(Gelf::udp(addr)?)))
+    }
+
+    /// Sets the function called with the errors of the logging targets, such as a closed pipe or
+    /// a full disk. By default, the first error is printed to stderr and later ones are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() {
+    /// bunt_logger::with().on_error(|err| eprintln!("logging failed: {}", err));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn on_error(&mut self, on_error: fn(io::Error)) -> &mut Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Sets a function that wraps writing each record to the logging targets, so that terminal
+    /// output such as progress bars can be cleared before and redrawn after it. The function must
+    /// call the closure it's given exactly once. Sinks and captures aren't affected.
+    ///
+    /// For [indicatif](https://docs.rs/indicatif) progress bars, see
+    /// `progress_bars`, behind the `indicatif` feature.
+    ///
+    /// # Example
+    /// A spinner that redraws itself on the last line of stderr is cleared before each record, and
+    /// drawn again on its next tick:
+    /// ```rust
+    /// use std::io::Write;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().suspend_with(|write| {
+    ///     let mut stderr = std::io::stderr().lock();
+    ///     let _ = write!(stderr, "\r\x1b[2K");
+    ///     let _ = stderr.flush();
+    ///     write();
+    /// });
+    /// # }
+    /// ```
+    #[inline]
+    pub fn suspend_with<F>(&mut self, suspend: F) -> &mut Self
+    where
+        F: Fn(&mut dyn FnMut()) + Send + Sync + 'static,
+    {
+        self.suspend = Some(Arc::new(suspend));
+        self
+    }
+
+    /// Prints records above the progress bars of `bars`, which are hidden while a record is
+    /// written and redrawn after it, so that neither garbles the other. The bars may be added
+    /// to `bars` before or after it's passed here.
+    ///
+    /// This replaces a function set with [`suspend_with`](Self::suspend_with).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::info;
+    /// use indicatif::{MultiProgress, ProgressBar};
+    ///
+    /// # fn main() {
+    /// let bars = MultiProgress::new();
+    /// bunt_logger::with().progress_bars(bars.clone());
+    ///
+    /// let bar = bars.add(ProgressBar::new(3));
+    /// for file in ["a.txt", "b.txt", "c.txt"] {
+    ///     info!("Compressing {}", file);
+    ///     bar.inc(1);
+    /// }
+    /// bar.finish();
+    /// # }
+    /// ```
+    #[cfg(feature = "indicatif")]
+    #[inline]
+    pub fn progress_bars(&mut self, bars: indicatif::MultiProgress) -> &mut Self {
+        self.suspend_with(move |write| bars.suspend(write))
+    }
+
+    /// Sets whether [`ColorChoice::Auto`] follows the `NO_COLOR`, `CLICOLOR` and `CLICOLOR_FORCE`
+    /// environment conventions and disables color when the stream isn't a terminal. Enabled by
+    /// default.
+    ///
+    /// When disabled, `Auto` is passed on to termcolor unchanged. This applies to the stdout and
+    /// stderr targets set afterwards, including the default target, which is recreated.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::ColorChoice;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .respect_color_env(false)
+    ///     .stderr(ColorChoice::Auto);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn respect_color_env(&mut self, respect: bool) -> &mut Self {
+        self.color_env = respect;
+        if self.default_target {
+            let color = self.resolve_color(ColorChoice::Auto, Stream::Stdout);
+            self.writers[0] = Output::standard(Stream::Stdout, color);
+        }
+        self
+    }
+
+    /// Sets the logging target to stdout with the given [`ColorChoice`].
+    ///
+    /// [`ColorChoice::Auto`] is resolved according to
+    /// [`respect_color_env`](Self::respect_color_env).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::ColorChoice;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .stdout(ColorChoice::Always);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn stdout(&mut self, color: ColorChoice) -> &mut Self {
+        let color = self.resolve_color(color, Stream::Stdout);
+        self.writers = vec![Output::standard(Stream::Stdout, color)];
+        self.default_target = false;
+        self
+    }
+
+    /// Sets the logging target to stderr with the given [`ColorChoice`].
+    ///
+    /// [`ColorChoice::Auto`] is resolved according to
+    /// [`respect_color_env`](Self::respect_color_env).
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::ColorChoice;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .stderr(ColorChoice::Always);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn stderr(&mut self, color: ColorChoice) -> &mut Self {
+        let color = self.resolve_color(color, Stream::Stderr);
+        self.writers = vec![Output::standard(Stream::Stderr, color)];
+        self.default_target = false;
+        self
+    }
+
+    /// Sets the logging target for records of `level` only, replacing the target set by
+    /// [`writer`](Self::writer) and similar methods for that level.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{ColorChoice, Level, StandardStream};
+    ///
+    /// # fn main() {
+    /// bunt_logger::with()
+    ///     .stdout(ColorChoice::Auto)
+    ///     .route(Level::Error, Box::new(StandardStream::stderr(ColorChoice::Auto)));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn route(&mut self, level: Level, writer: Box<dyn WriteColor + Send>) -> &mut Self {
+        self.routes[level as usize - 1] = Some(Output::new(writer));
+        self
+    }
+
+    /// Sends errors and warnings to stderr and all other records to stdout, both with the given
+    /// [`ColorChoice`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::ColorChoice;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().split_streams(ColorChoice::Auto);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn split_streams(&mut self, color: ColorChoice) -> &mut Self {
+        let stderr = self.resolve_color(color, Stream::Stderr);
+        self.stdout(color);
+        for level in [Level::Error, Level::Warn] {
+            self.routes[level as usize - 1] = Some(Output::standard(Stream::Stderr, stderr));
+        }
+        self
+    }
+
+    /// Sets the logging target to the console of the browser or JavaScript runtime, with each
+    /// level logged through its own console method; see [`ConsoleWriter`]. Requires the `wasm`
+    /// feature, and is only available when targeting `wasm32`.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// #[wasm_bindgen(start)]
+    /// fn start() {
+    ///     bunt_logger::with().console();
+    ///     bunt_logger::warn!("{$yellow}Running in the browser{/$}");
+    /// }
+    /// ```
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    #[inline]
+    pub fn console(&mut self) -> &mut Self {
+        self.writers = vec![Output::new(Box::new(ConsoleWriter::new(Level::Info)))];
+        self.default_target = false;
+        for level in [Level::Error, Level::Warn, Level::Debug, Level::Trace] {
+            self.routes[level as usize - 1] =
+                Some(Output::new(Box::new(ConsoleWriter::new(level))));
+        }
+        self
+    }
+
+    /// Sets the logging target to the file at `path`, without any styling.
+    ///
+    /// The file is opened for appending and is created along with its parent directories if it
+    /// doesn't exist. To keep styles as ANSI escape sequences, pass a [`FileWriter`] to
+    /// [`writer`](Self::writer) instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// bunt_logger::with().file("logs/app.log")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Self> {
+        let writer = FileWriter::open(path)?;
+        Ok(self.writer(Box::new(writer)))
+    }
+
+    /// Sets the logging target to a file at `path` that is rotated once it grows past `max_bytes`,
+    /// keeping at most `max_backups` old files (`app.log.1`, `app.log.2`, ...).
+    ///
+    /// Styles are dropped. For more options, pass a [`RotatingFileWriter`] to
+    /// [`writer`](Self::writer) instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// bunt_logger::with().rotating_file("logs/app.log", 10 * 1024 * 1024, 5)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn rotating_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        max_bytes: u64,
+        max_backups: usize,
+    ) -> io::Result<&mut Self> {
+        let writer = RotatingFileWriter::open(path, max_bytes, max_backups)?;
+        Ok(self.writer(Box::new(writer)))
+    }
+
+    /// Moves writing to each of the current logging targets onto a background thread; see
+    /// [`NonBlocking`]. Targets set with [`route`](Self::route) are not affected.
+    ///
+    /// Records are written until the returned guard is dropped, so it should be kept alive until
+    /// the end of `main`. Targets set after this call are written to directly again.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::ColorChoice;
+    ///
+    /// # fn main() {
+    /// let _guard = bunt_logger::with()
+    ///     .stderr(ColorChoice::Auto)
+    ///     .non_blocking();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn non_blocking(&mut self) -> WorkerGuard {
+        let mut guard = WorkerGuard::empty();
+        for output in &mut self.writers {
+            let (writer, next) = NonBlocking::new(Box::new(output.clone().into_writer()));
+            *output = output.replace_writer(Box::new(writer));
+            guard.merge(next);
+        }
+        self.default_target = false;
+        guard
+    }
+
+    /// Applies level directives written in `RUST_LOG`-style syntax.
+    ///
+    /// The specification is a comma-separated list of directives: a bare level (`info`) sets the
+    /// default level, `module=level` sets the level of a module, and a bare module name enables
+    /// all records from that module. `off` silences output. If any directive is malformed, none of
+    /// them are applied.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() -> Result<(), bunt_logger::ParseFilterError> {
+    /// bunt_logger::with().parse_filters("info,my_crate=trace,hyper=off")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn parse_filters(&mut self, spec: &str) -> Result<&mut Self, ParseFilterError> {
+        self.filter.parse(spec)?;
+        self.sync_max_level();
+        Ok(self)
+    }
+
+    /// Applies the level directives in the environment variable `var`, using the syntax of
+    /// [`parse_filters`](Self::parse_filters). Nothing happens if the variable is not set.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() -> Result<(), bunt_logger::ParseFilterError> {
+    /// std::env::set_var("MY_APP_LOG", "warn,my_app=debug");
+    /// bunt_logger::with().parse_env("MY_APP_LOG")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn parse_env(&mut self, var: &str) -> Result<&mut Self, ParseFilterError> {
+        self.filter_env = Some(var.to_owned());
+        match env::var(var) {
+            Ok(spec) => self.parse_filters(&spec),
+            Err(_) => Ok(self),
+        }
+    }
+
+    /// Applies the settings in the TOML configuration file at `path`, so that logging can be
+    /// changed without recompiling the application; see [`load_config`](Self::load_config) for
+    /// the settings. If the file can't be read or contains an invalid setting, none of them are
+    /// applied.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> Result<(), bunt_logger::LoadConfigError> {
+    /// bunt_logger::with().load_file("logger.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, LoadConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        self.load_config(&text)?;
+        self.config_file = Some(path.to_owned());
+        Ok(self)
+    }
+
+    /// Like [`load_file`](Self::load_file), but also spawns a thread that reloads the global
+    /// preferences whenever the file changes, so that long-running services can change their
+    /// logging without restarting. Requires the `watch` feature.
+    ///
+    /// The file is checked for changes every second, and all of its settings are swapped in at
+    /// once. Failed reloads keep the current settings and are logged as warnings with the target
+    /// `config`. Only one file is watched at a time; watching another one stops watching the
+    /// previous one. Fails with [`LoadConfigError::Io`] if the thread can't be spawned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> Result<(), bunt_logger::LoadConfigError> {
+    /// bunt_logger::with().watch_file("logger.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "watch")]
+    pub fn watch_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, LoadConfigError> {
+        let path = path.as_ref();
+        self.load_file(path)?;
+        config::watch(path.to_owned())?;
+        Ok(self)
+    }
+
+    /// Applies the settings in a TOML configuration. Settings that are left out keep their
+    /// current values. If any setting is invalid, none of them are applied.
+    ///
+    /// The whole TOML syntax is accepted, except dates, and tables nested in the tables below.
+    ///
+    /// The top level takes these keys, which behave like the methods with the same name:
+    ///
+    /// - `level`: the default level, e.g. `"info"`, or `"off"`
+    /// - `filters`: level directives in the syntax of [`parse_filters`](Self::parse_filters)
+    /// - `format`: `"text"` or `"json"`
+    /// - `template`: a [template](Self::template)
+    /// - `timestamps`: `"off"`, `"rfc3339"`, `"seconds"`, `"millis"`, `"elapsed"`, or a
+    ///   [custom format](TimestampFormat::Custom)
+    /// - `time_zone`: `"utc"` or `"local"`
+    /// - `show_level`, `show_thread`, `show_hostname`, `show_pid`, `show_process_name`,
+    ///   `wrap_messages`, `dedup`: booleans
+    /// - `show_location`: `"always"`, `"debug_only"` or `"never"`
+    /// - `max_message_len`: an integer
+    ///
+    /// The `[targets]` table maps module paths to levels, like
+    /// [`module_filter`](Self::module_filter). The `[theme]` table takes a `base` theme,
+    /// `"default"` or `"monochrome"`, and styles for the keys `error`, `warn`, `info`, `debug`,
+    /// `trace`, `success`, `notice`, `fatal`, `fields`, `meta` and `scope`, written as words such
+    /// as `"bold red on white"`: `bold`, `dimmed`, `italic`, `underline` and `intense`, a
+    /// foreground color, and a background color after `on`. Colors are names, numbers of the 256
+    /// ANSI colors, or RGB values such as `"255,127,0"`. It also takes the booleans
+    /// `styled_messages` and `icons`, [labels](Theme::with_label) for the same keys suffixed with
+    /// `_label`, such as `error_label = "EROR"`, and an integer `label_width`.
+    ///
+    /// Each `[[outputs]]` table adds a logging target, replacing the current ones, with a `kind`
+    /// of `"stdout"` or `"stderr"` with an optional `color` of `"auto"`, `"always"` or `"never"`,
+    /// `"file"` with a `path`, or `"rotating_file"` with a `path`, `max_bytes`, `max_backups` and
+    /// an optional `rotation` of `"never"`, `"hourly"` or `"daily"`, whose periods follow the
+    /// `time_zone`. Outputs can override the `level` and `format` for their records; see
+    /// [`Output`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() -> Result<(), bunt_logger::LoadConfigError> {
+    /// bunt_logger::with().load_config(
+    ///     r#"
+    ///     level = "info"
+    ///     show_level = true
+    ///
+    ///     [targets]
+    ///     "my_app::db" = "trace"
+    ///     hyper = "warn"
+    ///
+    ///     [theme]
+    ///     info = "bold blue"
+    ///     icons = true
+    ///
+    ///     [[outputs]]
+    ///     kind = "stderr"
+    ///     color = "auto"
+    ///     "#,
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_config(&mut self, config: &str) -> Result<&mut Self, LoadConfigError> {
+        Config::parse(config, self)?.apply(self);
+        Ok(self)
+    }
+
+    /// Installs a handler for SIGHUP that [reopens](reopen_files) all log files and then reloads
+    /// the global preferences from the configuration file passed to
+    /// [`load_file`](Self::load_file) and the environment variable passed to
+    /// [`parse_env`](Self::parse_env), if any, as `logrotate`-based deployments expect. Only
+    /// available on Unix.
+    ///
+    /// The handler replaces any other handler for SIGHUP, and stays installed. Failed reloads
+    /// keep the current settings and are logged as warnings with the target `config`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # fn main() -> std::io::Result<()> {
+    /// # #[cfg(unix)]
+    /// bunt_logger::with().reload_on_sighup()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    #[inline]
+    pub fn reload_on_sighup(&mut self) -> io::Result<&mut Self> {
+        signal::install()?;
+        Ok(self)
+    }
+
+    /// Registers bunt-logger as the logger for the [`log`](mod@log) crate.
+    ///
+    /// Records emitted through the plain `log` macros (e.g. by dependencies) are then filtered and
+    /// written with the same preferences as the bunt-logger macros. Like [`log::set_logger`], this
+    /// may only succeed once per process.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Level;
+    ///
+    /// # fn main() -> Result<(), bunt_logger::SetLoggerError> {
+    /// bunt_logger::with()
+    ///     .level(Level::Debug)
+    ///     .install_as_log_facade()?;
+    ///
+    /// log::debug!("Written by bunt-logger");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn install_as_log_facade(&mut self) -> Result<&mut Self, SetLoggerError> {
+        log::set_logger(&facade::FACADE)?;
+        self.facade = true;
+        self.sync_max_level();
+        Ok(self)
+    }
+
+    #[inline]
+    fn resolve_color(&self, color: ColorChoice, stream: Stream) -> ColorChoice {
+        if self.color_env {
+            color::resolve(color, stream)
+        } else {
+            color
+        }
+    }
+
+    /// Replaces the preferences with an earlier snapshot.
+    fn restore(&mut self, previous: LogPrefs) {
+        // The facade stays installed regardless of the snapshot.
+        let facade = self.facade;
+        *self = previous;
+        self.facade = facade;
+        self.sync_max_level();
+    }
+
+    /// Keeps [`max_level`] and the `log` crate's global maximum level in line with the
+    /// directives, so that records are discarded early exactly when no directive would enable
+    /// them.
+    #[inline]
+    fn sync_max_level(&self) {
+        if !self.global {
+            return;
+        }
+        DISABLED.store(self.disabled, Ordering::Relaxed);
+        let max = if self.disabled {
+            LevelFilter::Off
+        } else if self.quiet {
+            self.kept_filter()
+        } else {
+            self.filter.max_filter().max(self.kept_filter())
+        };
+        let max = max.min(STATIC_MAX_LEVEL);
+        MAX_LEVEL.store(max as u8, Ordering::Relaxed);
+        if self.facade {
+            log::set_max_level(max);
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        !self.disabled && (self.output_enabled(level, target) || self.kept_filter() >= level)
+    }
+
+    /// Returns the most verbose level of the records that are kept in memory regardless of the
+    /// level filters.
+    #[inline]
+    fn kept_filter(&self) -> LevelFilter {
+        let recent = self.recent.as_ref().map(|recent| recent.filter);
+        let context = self.context.as_ref().map(|context| context.filter);
+        recent.max(context).unwrap_or(LevelFilter::Off)
+    }
+
+    /// Returns whether records of `level` and `target` are written to the logging targets.
+    #[inline]
+    fn output_enabled(&self, level: Level, target: &str) -> bool {
+        let filter = match thread::thread_filter() {
+            Some(filter) if self.global => filter,
+            _ => self.filter.filter_for(target),
+        };
+        !self.quiet && filter >= level
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn log_record(&self, record: &Record) {
+        self.try_log_record(record);
+    }
+
+    /// Logs `record`, returning whether it was written to the logging targets rather than
+    /// filtered out, dropped or only kept in memory.
+    pub(crate) fn try_log_record(&self, record: &Record) -> bool {
+        if self.disabled {
+            return false;
+        }
+        let attached = mdc::attach(record);
+        let record = attached.as_ref().unwrap_or(record);
+        let mut hooked;
+        let record = if self.hooks.is_empty() {
+            record
+        } else {
+            hooked = record.clone();
+            if !self.hooks.iter().all(|hook| hook(&mut hooked)) {
+                return false;
+            }
+            &hooked
+        };
+        let redacted = redact::redact(&self.redactions, record);
+        let record = redacted.as_ref().unwrap_or(record);
+        let truncated = self.max_message_len.and_then(|max| {
+            let message = record.message().truncated(max)?;
+            let mut record = record.clone();
+            record.set_message(message);
+            Some(record)
+        });
+        let record = truncated.as_ref().unwrap_or(record);
+        if self.kept_filter() >= record.level()
+            && !self.output_enabled(record.level(), record.target())
+        {
+            self.keep(record, None);
+            if let Some(context) = self.context.as_ref().filter(|c| c.filter >= record.level()) {
+                context.push(record);
+            }
+            return false;
+        }
+        #[cfg(feature = "regex")]
+        if !self.message_filters.is_empty() {
+            let message = record.message().to_string();
+            let action = self
+                .message_filters
+                .iter()
+                .find(|(pattern, _)| pattern.is_match(&message))
+                .map(|&(_, action)| action);
+            if action == Some(FilterAction::Deny) {
+                return false;
+            }
+        }
+        if let Some(sampler) = &self.samplers[record.level() as usize - 1] {
+            if !sampler.keep() {
+                if self.global {
+                    metrics::dropped();
+                }
+                return false;
+            }
+        }
+        if self.global {
+            stats::count(record.level());
+            metrics::count(record.level(), record.target());
+        }
+        if let Some(dedup) = &self.dedup {
+            let summary = {
+                let mut dedup = dedup.lock().unwrap_or_else(|err| err.into_inner());
+                if dedup.repeats(record) {
+                    if self.global {
+                        metrics::dropped();
+                    }
+                    return false;
+                }
+                let summary = dedup.summary();
+                dedup.set_last(record);
+                summary
+            };
+            if let Some(summary) = summary {
+                self.emit(&summary);
+            }
+        }
+        self.emit(record);
+        true
+    }
+
+    fn emit(&self, record: &Record) {
+        if let Some(context) = self
+            .context
+            .as_ref()
+            .filter(|_| record.level() == Level::Error)
+        {
+            for context in context.take() {
+                self.output(&context, None);
+            }
+        }
+        let delta = self.delta.as_ref().map(|delta| delta.next(record.time()));
+        self.output(record, delta);
+        self.keep(record, delta);
+    }
+
+    /// Writes `record` to the logging targets, sinks and captures.
+    fn output(&self, record: &Record, delta: Option<Duration>) {
+        let mut result = Ok(());
+        match &self.suspend {
+            Some(suspend) => suspend(&mut || result = self.write_record(record, delta)),
+            None => result = self.write_record(record, delta),
+        }
+        if let Err(err) = result {
+            self.write_failed(err);
+        }
+        for sink in &self.sinks {
+            if let Err(err) = sink.lock().log(record) {
+                self.write_failed(err);
+            }
+        }
+        self.capture_record(record, delta);
+    }
+
+    /// Counts a failed write to a logging target or sink and passes `err` to the error handler.
+    #[inline]
+    fn write_failed(&self, err: io::Error) {
+        if self.global {
+            metrics::write_error();
+        }
+        (self.on_error)(err);
+    }
+
+    /// Writes the summary of the repetitions of the last record, if there were any.
+    fn flush_dedup(&self) {
+        let summary = self.dedup.as_ref().and_then(|dedup| {
+            dedup
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .summary()
+        });
+        if let Some(summary) = summary {
+            self.emit(&summary);
+        }
+    }
+
+    fn write_record(&self, record: &Record, delta: Option<Duration>) -> io::Result<()> {
+        let targets = match &self.routes[record.level() as usize - 1] {
+            Some(route) => slice::from_ref(route),
+            None => &self.writers,
+        };
+
+        let timestamp = self.timestamps.format_in(record.time(), self.time_zone);
+        let mut result = Ok(());
+        for target in targets
+            .iter()
+            .filter(|target| target.enabled(record.level()))
+        {
+            let format = target.format().unwrap_or(self.format);
+            let theme = target.theme().unwrap_or(&self.theme);
+            let next = status::around(target.stream(), || {
+                let mut writer = target.lock();
+                if writer.supports_color() {
+                    self.render(
+                        record,
+                        timestamp.as_deref(),
+                        delta,
+                        format,
+                        theme,
+                        &mut color::Downgrade::new(&mut **writer, *color::DEPTH),
+                    )
+                } else {
+                    // Plain text is handed over in a single write, so that processes appending to
+                    // the same file don't interleave their records.
+                    let mut buffer = NoColor::new(Vec::new());
+                    self.render(
+                        record,
+                        timestamp.as_deref(),
+                        delta,
+                        format,
+                        theme,
+                        &mut buffer,
+                    )
+                    .and_then(|()| writer.write_all(buffer.get_ref()))
+                }
+            });
+            if result.is_ok() {
+                result = next;
+            }
+        }
+        result
+    }
+
+    fn render(
+        &self,
+        record: &Record,
+        timestamp: Option<&str>,
+        delta: Option<Duration>,
+        format: Format,
+        theme: &Theme,
+        writer: &mut dyn WriteColor,
+    ) -> io::Result<()> {
+        if format == Format::Json {
+            let keys = format::JsonKeys {
+                location: self.show_location.enabled(),
+                hostname: self.show_hostname,
+                pid: self.show_pid,
+                process_name: self.show_process_name,
+                thread: self.show_thread,
+            };
+            format::write_json(record, timestamp, keys, writer)?;
+            return writeln!(writer);
+        }
+        if let Some(template) = &self.template {
+            template.render(record, timestamp, theme, writer)?;
+            return writeln!(writer);
+        }
+
+        let width = if self.wrap && writer.supports_color() {
+            layout::terminal_width()
+        } else {
+            None
+        };
+        let writer = &mut Layout::new(writer, width);
+        if let Some(timestamp) = timestamp {
+            write!(writer, "{} ", timestamp)?;
+        }
+        if let (Some(delta), Some(slow)) = (delta, self.delta.as_ref().map(|delta| delta.slow)) {
+            let style = if delta >= slow {
+                theme.style(Level::Warn)
+            } else {
+                theme.meta_style()
+            };
+            writer.set_color(style)?;
+            write!(writer, "{:>8}", timestamp::format_delta(delta))?;
+            writer.reset()?;
+            write!(writer, " ")?;
+        }
+        if self.show_hostname {
+            theme.write_meta(&process::host_name(), writer)?;
+            write!(writer, " ")?;
+        }
+        if self.show_pid || self.show_process_name {
+            let process = match (self.show_process_name, self.show_pid) {
+                (true, true) => format!("{}[{}]", process::name(), process::id()),
+                (true, false) => process::name().to_owned(),
+                (false, _) => format!("[{}]", process::id()),
+            };
+            theme.write_meta(&process, writer)?;
+            write!(writer, " ")?;
+        }
+        if self.show_thread {
+            theme.write_meta(&format_args!("[{}]", record.thread_name()), writer)?;
+            write!(writer, " ")?;
+        }
+        if let Some(icon) = theme.icon(record) {
+            writer.set_color(theme.style_for(record))?;
+            write!(writer, "{}", icon)?;
+            writer.reset()?;
+            write!(writer, " ")?;
+        }
+        if self.show_level || record.kind().is_some() {
+            let width = if self.show_level {
+                theme.label_width()
+            } else {
+                0
+            };
+            writer.set_color(theme.style_for(record))?;
+            write!(
+                writer,
+                "{:<width$}",
+                theme.record_label(record),
+                width = width
+            )?;
+            writer.reset()?;
+            write!(writer, " ")?;
+        }
+        if let Some(scope) = record.scope_path() {
+            theme.write_scope(&scope, writer)?;
+            write!(writer, " ")?;
+        }
+        writer.start_message(theme.meta_style());
+        theme.write_message(record, writer)?;
+        if !record.fields().is_empty() {
+            write!(writer, " ")?;
+            theme.write_fields(record, writer)?;
+        }
+        if let Some(location) = record.location().filter(|_| self.show_location.enabled()) {
+            write!(writer, " ")?;
+            theme.write_meta(location, writer)?;
+        }
+        writer.end_message()?;
+        writeln!(writer)
+    }
+
+    fn capture_record(&self, record: &Record, delta: Option<Duration>) {
+        let mut captures = self.captures.lock().unwrap_or_else(|err| err.into_inner());
+        if captures.is_empty() {
+            return;
+        }
+        let line = self.render_line(record, delta);
+        captures.retain(|capture| capture.push(record.level(), &line));
+    }
+
+    /// Keeps `record` in the buffer of recent records, if any.
+    fn keep(&self, record: &Record, delta: Option<Duration>) {
+        if let Some(recent) = self
+            .recent
+            .as_ref()
+            .filter(|recent| recent.filter >= record.level())
+        {
+            recent.push(self.render_line(record, delta));
+        }
+    }
+
+    /// Renders `record` without styles or a trailing newline.
+    fn render_line(&self, record: &Record, delta: Option<Duration>) -> String {
+        let timestamp = self.timestamps.format_in(record.time(), self.time_zone);
+        let mut line = Message::new();
+        let _ = self.render(
+            record,
+            timestamp.as_deref(),
+            delta,
+            self.format,
+            &self.theme,
+            &mut line,
+        );
+
+        let mut line = line.to_string();
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        line
+    }
+
+    /// Flushes all targets, passing the first error to the error handler.
+    fn flush_or_report(&self) {
+        if let Err(err) = self.flush() {
+            (self.on_error)(err);
+        }
+    }
+
+    /// Flushes all logging targets.
+    fn flush(&self) -> io::Result<()> {
+        self.flush_dedup();
+        let mut result = Ok(());
+        for target in self.writers.iter().chain(self.routes.iter().flatten()) {
+            let next = target.lock().flush();
+            if result.is_ok() {
+                result = next;
+            }
+        }
+        for sink in &self.sinks {
+            let next = sink.lock().flush();
+            if result.is_ok() {
+                result = next;
+            }
+        }
+        result
     }
 }
@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The state of a rate-limited call site of [`warn_once!`](crate::warn_once) or
+/// [`warn_every!`](crate::warn_every).
+#[doc(hidden)]
+pub struct CallSite {
+    done: AtomicBool,
+    last: Mutex<Option<Instant>>,
+}
+
+impl CallSite {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            done: AtomicBool::new(false),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Returns `true` the first time it is called.
+    #[inline]
+    pub fn once(&self) -> bool {
+        !self.done.swap(true, Ordering::Relaxed)
+    }
+
+    /// Returns `true` if at least `interval` has passed since it last returned `true`.
+    pub fn every(&self, interval: Duration) -> bool {
+        let now = Instant::now();
+        let mut last = self.last.lock().unwrap_or_else(|err| err.into_inner());
+        match *last {
+            Some(last) if now.duration_since(last) < interval => false,
+            _ => {
+                *last = Some(now);
+                true
+            }
+        }
+    }
+}
+
+impl Default for CallSite {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// Starts a hyperlink; followed by the URL and [`END`].
+const START: &str = "\x1b]8;;";
+/// Ends the URL of a hyperlink.
+const END: &str = "\x1b\\";
+/// Ends a hyperlink.
+const CLOSE: &str = "\x1b]8;;\x1b\\";
+
+/// Displays `text` as a clickable link to the URL, using the OSC 8 escape sequence supported by
+/// most terminal emulators.
+///
+/// In log messages, the link is only kept for logging targets that support color; other targets,
+/// like files and the [JSON format](crate::Format::Json), get `text (url)` instead. The text can be
+/// styled in the logging macros like any other value. Outside of log messages, the escape sequence
+/// is always written.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, Link};
+///
+/// # fn main() {
+/// info!("See the {[blue+underline]}", Link("documentation", "https://docs.rs/bunt-logger"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link<T, U>(pub T, pub U);
+
+impl<T: fmt::Display, U: fmt::Display> fmt::Display for Link<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}{}{}", START, self.1, END, self.0, CLOSE)
+    }
+}
+
+/// Replaces the hyperlinks in `text` with `text (url)`, or just the URL if the text is the URL.
+pub(crate) fn strip(text: &str) -> Cow<'_, str> {
+    if !text.contains(START) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(START) {
+        stripped.push_str(&rest[..start]);
+        let link = &rest[start + START.len()..];
+        let (url, after) = match link.split_once(END) {
+            Some(parts) => parts,
+            None => {
+                rest = link;
+                break;
+            }
+        };
+        let (text, after) = after.split_once(CLOSE).unwrap_or((after, ""));
+        stripped.push_str(text);
+        if !url.is_empty() && text != url {
+            stripped.push_str(" (");
+            stripped.push_str(url);
+            stripped.push(')');
+        }
+        rest = after;
+    }
+    stripped.push_str(rest);
+    Cow::Owned(stripped)
+}
@@ -0,0 +1,72 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// The source location of the call that logged a record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    file: Cow<'static, str>,
+    line: u32,
+}
+
+impl Location {
+    /// Creates a location from a file path and line number, such as those of [`file!`] and
+    /// [`line!`].
+    #[inline]
+    pub fn new<F: Into<Cow<'static, str>>>(file: F, line: u32) -> Self {
+        Self {
+            file: file.into(),
+            line,
+        }
+    }
+
+    /// Returns the path of the source file.
+    #[inline]
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line number.
+    #[inline]
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+}
+
+/// Prints the location as `file:line`.
+impl fmt::Display for Location {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// When the source location of records is printed; see
+/// [`LogPrefs::show_location`](crate::LogPrefs::show_location).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationMode {
+    /// Always print the location.
+    Always,
+    /// Print the location in debug builds only.
+    DebugOnly,
+    /// Never print the location.
+    Never,
+}
+
+impl LocationMode {
+    /// Returns whether locations are printed in this build.
+    #[inline]
+    pub(crate) fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::DebugOnly => cfg!(debug_assertions),
+            Self::Never => false,
+        }
+    }
+}
+
+impl Default for LocationMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Never
+    }
+}
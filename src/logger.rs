@@ -0,0 +1,102 @@
+use std::io;
+use std::sync::atomic::Ordering;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use log::Level;
+
+use crate::{LogPrefs, Record};
+
+/// A logger with its own preferences, independent of the global ones.
+///
+/// Records are logged to it with [`log_to!`](crate::log_to) and the level macros ending in `_to`,
+/// e.g. [`error_to!`](crate::error_to). A new logger starts from the default preferences, and can
+/// be configured through [`with`](Logger::with) or created from a [`Builder`](crate::Builder). Its
+/// level does not affect [`max_level`](crate::max_level) or the `log` crate.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{error_to, info_to, Level, Logger};
+///
+/// # fn main() -> std::io::Result<()> {
+/// # let dir = std::env::temp_dir().join("bunt-logger-doc-logger");
+/// let audit = Logger::new();
+/// audit.with().level(Level::Trace).file(dir.join("audit.log"))?;
+///
+/// info_to!(audit, "{$bold}alice{/$} logged in");
+/// error_to!(audit, target: "auth", "{$bold}mallory{/$} was denied");
+/// # Ok(())
+/// # }
+/// ```
+pub struct Logger {
+    prefs: RwLock<LogPrefs>,
+}
+
+impl Logger {
+    /// Creates a logger with the default preferences.
+    #[inline]
+    pub fn new() -> Self {
+        Self::from_prefs(LogPrefs::new(false))
+    }
+
+    #[inline]
+    pub(crate) fn from_prefs(prefs: LogPrefs) -> Self {
+        Self {
+            prefs: RwLock::new(prefs),
+        }
+    }
+
+    /// Returns the preferences of the logger, used for modifying them.
+    #[inline]
+    pub fn with(&self) -> RwLockWriteGuard<'_, LogPrefs> {
+        self.prefs.write().unwrap_or_else(|err| err.into_inner())
+    }
+
+    #[inline]
+    fn read(&self) -> RwLockReadGuard<'_, LogPrefs> {
+        self.prefs.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns whether a record of `level` and `target` would be logged.
+    #[inline]
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        self.read().enabled(level, target)
+    }
+
+    /// Logs a record, regardless of whether it is [enabled](Self::enabled).
+    #[inline]
+    pub fn log(&self, record: &Record) {
+        self.read().log_record(record);
+    }
+
+    /// Flushes all logging targets of the logger, returning the first error.
+    #[inline]
+    pub fn flush(&self) -> io::Result<()> {
+        self.read().flush()
+    }
+}
+
+impl Default for Logger {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The global preferences as a logger, used by the logging macros.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Global;
+
+impl Global {
+    #[inline]
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        !crate::DISABLED.load(Ordering::Relaxed)
+            && (level <= crate::max_level() || crate::thread::thread_filter().is_some())
+            && crate::read().enabled(level, target)
+    }
+
+    #[inline]
+    pub fn log(&self, record: &Record) {
+        crate::read().log_record(record);
+    }
+}
@@ -0,0 +1,653 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! try_log {
+    (
+        $logger:expr, $level:expr, $target:expr, [$($with:ident = $value:expr),* $(,)?];
+        [$($format_str:literal),+] $(, $arg:expr)*
+    ) => {{
+        let level: $crate::Level = $level;
+        let target: &str = $target;
+        if level <= $crate::STATIC_MAX_LEVEL {
+            let logger = &$logger;
+            if logger.enabled(level, target) {
+                let mut message = $crate::Message::new();
+                let _ = ::bunt::write!(message, [$($format_str),+] $(, $arg )*);
+                let record = $crate::Record::new(level, target, message)
+                    .with_location($crate::Location::new(file!(), line!()))
+                    $(.$with($value))*;
+                logger.log(&record);
+            }
+        }
+    }};
+}
+
+/// Splits the arguments of a logging macro from a trailing block of fields, then passes both on
+/// to [`__log_fields`]. Arguments are collected token by token, each wrapped in parentheses.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_args {
+    // A trailing block of fields ends the arguments.
+    ($head:tt [$($args:tt)*] [] , { $($fields:tt)* } $(,)?) => {
+        $crate::__log_fields!($head [$($args)*] [] $($fields)*)
+    };
+    ($head:tt [$($args:tt)*] [$($cur:tt)+] , { $($fields:tt)* } $(,)?) => {
+        $crate::__log_fields!($head [$($args)* ($($cur)+)] [] $($fields)*)
+    };
+    // No more arguments.
+    ($head:tt [$($args:tt)*] [] $(,)?) => {
+        $crate::__log_fields!($head [$($args)*] [])
+    };
+    ($head:tt [$($args:tt)*] [$($cur:tt)+] $(,)?) => {
+        $crate::__log_fields!($head [$($args)* ($($cur)+)] [])
+    };
+    // A comma ends the current argument.
+    ($head:tt [$($args:tt)*] [$($cur:tt)+] , $($rest:tt)+) => {
+        $crate::__log_args!($head [$($args)* ($($cur)+)] [] , $($rest)+)
+    };
+    ($head:tt [$($args:tt)*] [] , $next:tt $($rest:tt)*) => {
+        $crate::__log_args!($head [$($args)*] [$next] $($rest)*)
+    };
+    ($head:tt [$($args:tt)*] [$($cur:tt)+] $next:tt $($rest:tt)*) => {
+        $crate::__log_args!($head [$($args)*] [$($cur)+ $next] $($rest)*)
+    };
+}
+
+/// Turns `key = value` pairs into `with_field` calls for [`try_log`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_fields {
+    ($head:tt $args:tt [$($out:tt)*] $key:ident = % $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_fields!($head $args [$($out)*
+            with_field = $crate::Field::new(stringify!($key), $crate::Value::display(&$value)),
+        ] $($($rest)*)?)
+    };
+    ($head:tt $args:tt [$($out:tt)*] $key:ident = ? $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_fields!($head $args [$($out)*
+            with_field = $crate::Field::new(stringify!($key), $crate::Value::debug(&$value)),
+        ] $($($rest)*)?)
+    };
+    ($head:tt $args:tt [$($out:tt)*] $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_fields!($head $args [$($out)*
+            with_field = $crate::Field::new(stringify!($key), $value),
+        ] $($($rest)*)?)
+    };
+    (
+        ($logger:expr, $level:expr, $target:expr, [$($with:tt)*], [$($format_str:literal),+])
+        [$(($($arg:tt)*))*] [$($out:tt)*]
+    ) => {
+        $crate::try_log!(
+            $logger, $level, $target, [$($with)* $($out)*];
+            [$($format_str),+] $(, $($arg)*)*
+        )
+    };
+}
+
+/// Like [`log::log`], but bunt-compatible: logs a message at a level given at runtime.
+///
+/// The target of the record defaults to the module path of the call site, and can be set with
+/// `target:` like in the `log` macros. The arguments may be followed by a block of
+/// [fields](crate::Field). All other macros accept `target:` and fields as well.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{log, Level};
+///
+/// # fn main() {
+/// let failures = 2;
+/// let level = if failures > 0 { Level::Warn } else { Level::Info };
+/// log!(level, "{$bold}{}{/$} tests failed", failures);
+/// log!(target: "tests", level, "{$bold}{}{/$} tests failed", failures);
+/// log!(level, "{$bold}{}{/$} tests failed", failures, { suite = "integration" });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::log!(target: $target, $level, [$format_str] $($rest)*)
+    };
+    (target: $target:expr, $level:expr, [$($format_str:literal),+ $(,)?] $($rest:tt)*) => {
+        $crate::__log_args!(($crate::Global, $level, $target, [], [$($format_str),+]) [] [] $($rest)*)
+    };
+    ($level:expr, $($rest:tt)+) => {
+        $crate::log!(target: module_path!(), $level, $($rest)+)
+    };
+}
+
+/// Returns whether a record of the given level, and optionally target, would be logged, so that
+/// expensive data collection can be skipped. Like in the `log` crate, the target defaults to the
+/// module path of the call site.
+///
+/// Levels above [`STATIC_MAX_LEVEL`](crate::STATIC_MAX_LEVEL) or [`max_level`](crate::max_level)
+/// are rejected without locking the preferences; only the remaining levels consult the
+/// per-target filters.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{debug, log_enabled, Level};
+///
+/// # fn collect_stats() -> usize { 0 }
+/// # fn main() {
+/// if log_enabled!(Level::Debug) {
+///     debug!("Cache holds {} entries", collect_stats());
+/// }
+/// if log_enabled!(target: "cache", Level::Trace) {
+///     // ...
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! log_enabled {
+    (target: $target:expr, $level:expr $(,)?) => {{
+        let level: $crate::Level = $level;
+        level <= $crate::STATIC_MAX_LEVEL && $crate::Global.enabled(level, $target)
+    }};
+    ($level:expr $(,)?) => {
+        $crate::log_enabled!(target: module_path!(), $level)
+    };
+}
+
+/// Like [`log::error`], but bunt-compatible.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::error;
+///
+/// # fn main() {
+/// let x = -1;
+/// error!("{$red}Not a positive number:{/$} {[bold]}", x);
+/// error!(target: "input", "{$red}Not a positive number:{/$} {[bold]}", x);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Error, $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::log!($crate::Level::Error, $($rest)+)
+    };
+}
+
+/// Like [`log::warn`], but bunt-compatible.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::warn;
+///
+/// # fn main() {
+/// warn!("He likes {$yellow}lemons{/$}, like {$blue+italic}a lot{/$}.");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Warn, $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::log!($crate::Level::Warn, $($rest)+)
+    };
+}
+
+/// Like [`log::info`], but bunt-compatible.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// let v = vec![1, 2, 3];
+/// info!("Here is some data: {[green]:?}.", v);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Info, $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::log!($crate::Level::Info, $($rest)+)
+    };
+}
+
+/// Like [`log::debug`], but bunt-compatible.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::debug;
+///
+/// # fn main() {
+/// let v = vec![1, 2, 3];
+/// debug!("{$bold}Length: {[cyan]}{/$}.", v.len());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Debug, $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::log!($crate::Level::Debug, $($rest)+)
+    };
+}
+
+/// Like [`log::trace`], but bunt-compatible.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::trace;
+///
+/// # fn main() {
+/// let v = vec![1, 2, 3];
+/// trace!("{$italic}Watch the mouse!{/$}.");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Trace, $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::log!($crate::Level::Trace, $($rest)+)
+    };
+}
+
+/// Like [`log!`](crate::log), but logs to a [`Logger`](crate::Logger) instead of the global
+/// preferences.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{log_to, Level, Logger};
+///
+/// # fn main() {
+/// let logger = Logger::new();
+/// log_to!(logger, Level::Info, "{$green}Installed{/$} {} packages", 3);
+/// log_to!(logger, target: "install", Level::Info, "{$green}Installed{/$} {} packages", 3);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! log_to {
+    ($logger:expr, target: $target:expr, $level:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::log_to!($logger, target: $target, $level, [$format_str] $($rest)*)
+    };
+    (
+        $logger:expr, target: $target:expr, $level:expr, [$($format_str:literal),+ $(,)?]
+        $($rest:tt)*
+    ) => {
+        $crate::__log_args!(($logger, $level, $target, [], [$($format_str),+]) [] [] $($rest)*)
+    };
+    ($logger:expr, $level:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: module_path!(), $level, $($rest)+)
+    };
+}
+
+/// Like [`error!`](crate::error), but logs to a [`Logger`](crate::Logger); see [`log_to!`](crate::log_to).
+#[macro_export]
+macro_rules! error_to {
+    ($logger:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: $target, $crate::Level::Error, $($rest)+)
+    };
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, $crate::Level::Error, $($rest)+)
+    };
+}
+
+/// Like [`warn!`](crate::warn), but logs to a [`Logger`](crate::Logger); see [`log_to!`](crate::log_to).
+#[macro_export]
+macro_rules! warn_to {
+    ($logger:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: $target, $crate::Level::Warn, $($rest)+)
+    };
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, $crate::Level::Warn, $($rest)+)
+    };
+}
+
+/// Like [`info!`](crate::info), but logs to a [`Logger`](crate::Logger); see [`log_to!`](crate::log_to).
+#[macro_export]
+macro_rules! info_to {
+    ($logger:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: $target, $crate::Level::Info, $($rest)+)
+    };
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, $crate::Level::Info, $($rest)+)
+    };
+}
+
+/// Like [`debug!`](crate::debug), but logs to a [`Logger`](crate::Logger); see [`log_to!`](crate::log_to).
+#[macro_export]
+macro_rules! debug_to {
+    ($logger:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: $target, $crate::Level::Debug, $($rest)+)
+    };
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, $crate::Level::Debug, $($rest)+)
+    };
+}
+
+/// Like [`trace!`](crate::trace), but logs to a [`Logger`](crate::Logger); see [`log_to!`](crate::log_to).
+#[macro_export]
+macro_rules! trace_to {
+    ($logger:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, target: $target, $crate::Level::Trace, $($rest)+)
+    };
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log_to!($logger, $crate::Level::Trace, $($rest)+)
+    };
+}
+
+/// Logs a success message at the info level, marked with a green `✓`.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::success;
+///
+/// # fn main() {
+/// success!("Built {[bold]} in 2.1s", "my-crate");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! success {
+    (target: $target:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::success!(target: $target, [$format_str] $($rest)*)
+    };
+    (target: $target:expr, [$($format_str:literal),+ $(,)?] $($rest:tt)*) => {
+        $crate::__log_args!(
+            ($crate::Global, $crate::Level::Info, $target, [with_kind = $crate::Kind::Success,], [$($format_str),+])
+            [] [] $($rest)*
+        )
+    };
+    ($($rest:tt)+) => {
+        $crate::success!(target: module_path!(), $($rest)+)
+    };
+}
+
+/// Logs a notice at the info level: something noteworthy that isn't a problem, marked with a bold
+/// blue `NOTE` label.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::notice;
+///
+/// # fn main() {
+/// notice!("A new version is available: {[bold]}", "1.2.0");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! notice {
+    (target: $target:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::notice!(target: $target, [$format_str] $($rest)*)
+    };
+    (target: $target:expr, [$($format_str:literal),+ $(,)?] $($rest:tt)*) => {
+        $crate::__log_args!(
+            ($crate::Global, $crate::Level::Info, $target, [with_kind = $crate::Kind::Notice,], [$($format_str),+])
+            [] [] $($rest)*
+        )
+    };
+    ($($rest:tt)+) => {
+        $crate::notice!(target: module_path!(), $($rest)+)
+    };
+}
+
+/// Logs an unrecoverable error at the error level, marked with a `FATAL` label in white on red.
+///
+/// This only logs the message; it doesn't terminate the process.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::fatal;
+///
+/// # fn main() {
+/// fatal!("Could not open {[bold]}", "config.toml");
+/// # std::process::exit(0);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fatal {
+    (target: $target:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::fatal!(target: $target, [$format_str] $($rest)*)
+    };
+    (target: $target:expr, [$($format_str:literal),+ $(,)?] $($rest:tt)*) => {
+        $crate::__log_args!(
+            ($crate::Global, $crate::Level::Error, $target, [with_kind = $crate::Kind::Fatal,], [$($format_str),+])
+            [] [] $($rest)*
+        )
+    };
+    ($($rest:tt)+) => {
+        $crate::fatal!(target: module_path!(), $($rest)+)
+    };
+}
+
+/// Starts a collapsible group of log output with a title formatted like [`format!`], until the
+/// next [`end_group!`](crate::end_group!); see [`group`](crate::group()).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{end_group, group, info};
+///
+/// # fn main() {
+/// group!("Running {} tests", 12);
+/// info!("test parse ... ok");
+/// end_group!();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! group {
+    ($($arg:tt)+) => {
+        $crate::begin_group(::std::format_args!($($arg)+))
+    };
+}
+
+/// Ends the group started last by [`group!`](crate::group!).
+#[macro_export]
+macro_rules! end_group {
+    () => {
+        $crate::end_group()
+    };
+}
+
+/// Logs a step of a build-tool style process at the info level, with the verb in bold green and
+/// right-aligned in a 12 character column like in cargo's output.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::step;
+///
+/// # fn main() {
+/// step!("Compiling", "my-crate v0.1.0");
+/// step!("Finished", "release profile in {[bold]}", "4.2s");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! step {
+    (target: $target:expr, $verb:expr, $format_str:literal $($rest:tt)*) => {
+        $crate::step!(target: $target, $verb, [$format_str] $($rest)*)
+    };
+    (target: $target:expr, $verb:expr, [$($format_str:literal),+ $(,)?] $($rest:tt)*) => {
+        $crate::__log_args!(
+            ($crate::Global, $crate::Level::Info, $target, [], ["{$green+bold}{:>12}{/$} ", $($format_str),+])
+            [] [] , $verb $($rest)*
+        )
+    };
+    ($($rest:tt)+) => {
+        $crate::step!(target: module_path!(), $($rest)+)
+    };
+}
+
+/// Like [`warn!`](crate::warn), but logs only the first time the call site is reached.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::warn_once;
+///
+/// # fn main() {
+/// for _ in 0..100 {
+///     warn_once!("{$yellow}Falling back{/$} to the default config");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! warn_once {
+    ($($rest:tt)+) => {{
+        static CALL_SITE: $crate::CallSite = $crate::CallSite::new();
+        if CALL_SITE.once() {
+            $crate::warn!($($rest)+);
+        }
+    }};
+}
+
+/// Like [`warn!`](crate::warn), but logs at most once per `interval` from the call site. Calls in
+/// between are dropped.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// use bunt_logger::warn_every;
+///
+/// # fn main() {
+/// for i in 0..100 {
+///     warn_every!(Duration::from_secs(5), "Queue is {$bold}full{/$} ({} dropped)", i);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! warn_every {
+    ($interval:expr, $($rest:tt)+) => {{
+        static CALL_SITE: $crate::CallSite = $crate::CallSite::new();
+        if CALL_SITE.every($interval) {
+            $crate::warn!($($rest)+);
+        }
+    }};
+}
+
+/// Runs a block, then logs how long it took at the given level, like `indexing finished in 1.25s`.
+/// Evaluates to the value of the block.
+///
+/// The label may be any [`Display`](std::fmt::Display) value, and the target can be set with
+/// `target:` like in [`log!`](crate::log).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{timed, Level};
+///
+/// # fn main() {
+/// let words = timed!(Level::Debug, "indexing", {
+///     "a few words to index".split(' ').count()
+/// });
+/// # let _ = words;
+/// # }
+/// ```
+#[macro_export]
+macro_rules! timed {
+    (target: $target:expr, $level:expr, $label:expr, $body:block $(,)?) => {{
+        let start = ::std::time::Instant::now();
+        let value = $body;
+        $crate::log!(
+            target: $target,
+            $level,
+            "{$bold}{}{/$} finished in {}",
+            $label,
+            $crate::HumanDuration(start.elapsed()),
+        );
+        value
+    }};
+    ($level:expr, $label:expr, $body:block $(,)?) => {
+        $crate::timed!(target: module_path!(), $level, $label, $body)
+    };
+}
+
+/// Logs a byte slice as a hex dump at the given level, one record per 16 bytes with the offset,
+/// the bytes in hex and the bytes as ASCII. Bytes that aren't printable ASCII are highlighted.
+///
+/// At most 1024 bytes are shown unless `max` is given; the number of omitted bytes is logged after
+/// the dump. The slice may be anything that implements `AsRef<[u8]>`, and the target can be set
+/// with `target:` like in [`log!`](crate::log).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{hexdump, Level};
+///
+/// # fn main() {
+/// let packet = b"\x01\x00\x00\x2aGET /index.html HTTP/1.1\r\n";
+/// hexdump!(Level::Debug, packet);
+/// hexdump!(target: "net", Level::Trace, packet, max = 16);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! hexdump {
+    (target: $target:expr, $level:expr, $bytes:expr, max = $max:expr $(,)?) => {{
+        let level: $crate::Level = $level;
+        let target: &str = $target;
+        if level <= $crate::STATIC_MAX_LEVEL && $crate::Global.enabled(level, target) {
+            let bytes: &[u8] = ::std::convert::AsRef::as_ref(&$bytes);
+            for message in $crate::hexdump_lines(bytes, $max) {
+                let record = $crate::Record::new(level, target, message)
+                    .with_location($crate::Location::new(file!(), line!()));
+                $crate::Global.log(&record);
+            }
+        }
+    }};
+    (target: $target:expr, $level:expr, $bytes:expr $(,)?) => {
+        $crate::hexdump!(target: $target, $level, $bytes, max = $crate::HEXDUMP_DEFAULT_MAX)
+    };
+    ($level:expr, $bytes:expr $(, max = $max:expr)? $(,)?) => {
+        $crate::hexdump!(target: module_path!(), $level, $bytes $(, max = $max)?)
+    };
+}
+
+/// Runs a block with the indentation increased by one level; see [`indent`](fn@crate::indent).
+/// Evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{indented, info};
+///
+/// # fn main() {
+/// info!("Building");
+/// let count = indented!({
+///     info!("Compiling {$bold}bunt-logger{/$}");
+///     1
+/// });
+/// info!("{$green}Built {} crate(s){/$}", count);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! indented {
+    ($($body:tt)*) => {{
+        let _indent = $crate::indent();
+        $($body)*
+    }};
+}
+
+/// Runs a block inside a named scope, which prefixes all records created inside it on the current
+/// thread; see [`enter_scope`](crate::enter_scope). Evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, scope};
+///
+/// # fn main() {
+/// let handles: Vec<_> = (0..2)
+///     .map(|i| {
+///         std::thread::spawn(move || {
+///             scope!(format!("worker-{}", i), {
+///                 info!("Fetching"); // [worker-0] Fetching
+///                 scope!("parse", {
+///                     info!("Parsing"); // [worker-0/parse] Parsing
+///                 });
+///             })
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! scope {
+    ($name:expr, $body:block $(,)?) => {{
+        let _scope = $crate::enter_scope($name);
+        $body
+    }};
+}
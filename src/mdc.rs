@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::RwLock;
+
+use crate::{Field, Record, Value};
+
+/// Fields attached to all records.
+static GLOBAL: RwLock<Vec<Field>> = RwLock::new(Vec::new());
+
+thread_local! {
+    /// Fields attached to the records of the thread, innermost scope last.
+    static SCOPED: RefCell<Vec<Field>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the context: [fields](Field) that are attached to every record, such as the id of the
+/// request being handled, so that records can be correlated.
+///
+/// Fields can be inserted for all threads until they are removed, or for the current thread until
+/// a guard is dropped. Fields given to a logging macro take precedence over context fields with
+/// the same key, and scoped fields over ones for all threads. Like other fields, they are printed
+/// after the message, or included in the [JSON format](crate::Format::Json).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// bunt_logger::context().insert("service", "billing");
+/// {
+///     let _request = bunt_logger::context().scoped("request_id", 8172);
+///     info!("Handling request"); // service=billing request_id=8172
+/// }
+/// info!("Idle"); // service=billing
+/// # }
+/// ```
+#[inline]
+pub fn context() -> Context {
+    Context { _private: () }
+}
+
+/// A handle to the fields attached to every record; see [`context`].
+#[derive(Debug)]
+pub struct Context {
+    _private: (),
+}
+
+impl Context {
+    /// Attaches a field to the records of all threads, replacing the field with the same key.
+    pub fn insert<K, V>(&self, key: K, value: V) -> &Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<Value>,
+    {
+        let field = Field::new(key, value);
+        let mut fields = GLOBAL.write().unwrap_or_else(|err| err.into_inner());
+        match fields.iter_mut().find(|f| f.key() == field.key()) {
+            Some(existing) => *existing = field,
+            None => fields.push(field),
+        }
+        self
+    }
+
+    /// Removes the field with the given key from the records of all threads.
+    pub fn remove(&self, key: &str) -> &Self {
+        GLOBAL
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|field| field.key() != key);
+        self
+    }
+
+    /// Removes all fields attached to the records of all threads. Scoped fields stay attached.
+    pub fn clear(&self) {
+        GLOBAL
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    /// Attaches a field to the records of the current thread, returning a guard that removes it
+    /// again when dropped.
+    pub fn scoped<K, V>(&self, key: K, value: V) -> ContextGuard
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<Value>,
+    {
+        let len = SCOPED.with(|fields| {
+            let mut fields = fields.borrow_mut();
+            fields.push(Field::new(key, value));
+            fields.len() - 1
+        });
+        ContextGuard {
+            len,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// A guard that removes a scoped field of the context when dropped; see [`Context::scoped`].
+#[must_use = "the field is removed as soon as the guard is dropped"]
+pub struct ContextGuard {
+    /// The number of scoped fields before the field was attached.
+    len: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    #[inline]
+    fn drop(&mut self) {
+        SCOPED.with(|fields| fields.borrow_mut().truncate(self.len));
+    }
+}
+
+/// Returns `record` with the context fields attached, or `None` if there are none.
+pub(crate) fn attach(record: &Record) -> Option<Record> {
+    let global = GLOBAL.read().unwrap_or_else(|err| err.into_inner());
+    SCOPED.with(|scoped| {
+        let scoped = scoped.borrow();
+        if global.is_empty() && scoped.is_empty() {
+            return None;
+        }
+
+        let mut record = record.clone();
+        for field in scoped.iter().rev().chain(global.iter()) {
+            if record.fields().iter().all(|f| f.key() != field.key()) {
+                record.fields_mut().push(field.clone());
+            }
+        }
+        Some(record)
+    })
+}
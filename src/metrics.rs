@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use log::Level;
+use once_cell::sync::Lazy;
+
+/// The number of records logged through the global preferences, by target and then
+/// `level as usize - 1`.
+static RECORDS: Lazy<Mutex<BTreeMap<String, [u64; 5]>>> = Lazy::new(Default::default);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static WRITE_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+/// Counts a record of `level` for `target`.
+pub(crate) fn count(level: Level, target: &str) {
+    let mut records = RECORDS.lock().unwrap_or_else(|err| err.into_inner());
+    // Avoids allocating the target for every record once it has been seen.
+    if let Some(counts) = records.get_mut(target) {
+        counts[level as usize - 1] += 1;
+        return;
+    }
+    let mut counts = [0; 5];
+    counts[level as usize - 1] = 1;
+    records.insert(target.to_owned(), counts);
+}
+
+/// Counts a record that passed the filters but was dropped.
+#[inline]
+pub(crate) fn dropped() {
+    DROPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts a failed write to a logging target or sink.
+#[inline]
+pub(crate) fn write_error() {
+    WRITE_ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counters of the records logged through the global preferences, returned by [`metrics`].
+///
+/// The [`Display`](fmt::Display) implementation writes the counters in the Prometheus text
+/// exposition format, so that they can be served from a `/metrics` endpoint:
+///
+/// ```text
+/// # HELP bunt_logger_records_total Records logged, by level and target.
+/// # TYPE bunt_logger_records_total counter
+/// bunt_logger_records_total{level="error",target="app::db"} 2
+/// # HELP bunt_logger_dropped_records_total Records dropped by sampling or deduplication.
+/// # TYPE bunt_logger_dropped_records_total counter
+/// bunt_logger_dropped_records_total 0
+/// # HELP bunt_logger_write_errors_total Failed writes to logging targets and sinks.
+/// # TYPE bunt_logger_write_errors_total counter
+/// bunt_logger_write_errors_total 0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metrics {
+    records: BTreeMap<String, [u64; 5]>,
+    dropped: u64,
+    write_errors: u64,
+}
+
+impl Metrics {
+    /// Returns the number of records logged at `level` for `target`. Records count like those of
+    /// [`Stats`](crate::Stats).
+    #[inline]
+    pub fn records(&self, level: Level, target: &str) -> u64 {
+        self.records
+            .get(target)
+            .map_or(0, |counts| counts[level as usize - 1])
+    }
+
+    /// Returns the levels and targets that records were logged for, with their counts, ordered by
+    /// target and then level.
+    pub fn iter(&self) -> impl Iterator<Item = (Level, &str, u64)> + '_ {
+        self.records.iter().flat_map(|(target, counts)| {
+            Level::iter()
+                .zip(counts)
+                .filter(|&(_, &count)| count > 0)
+                .map(move |(level, &count)| (level, target.as_str(), count))
+        })
+    }
+
+    /// Returns the number of records that passed the filters but were dropped by
+    /// [sampling](crate::LogPrefs::sample) or collapsed by [`dedup`](crate::LogPrefs::dedup).
+    #[inline]
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Returns the number of failed writes to logging targets and sinks, which were passed to the
+    /// [error handler](crate::LogPrefs::on_error).
+    #[inline]
+    pub fn write_errors(&self) -> u64 {
+        self.write_errors
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# HELP bunt_logger_records_total Records logged, by level and target."
+        )?;
+        writeln!(f, "# TYPE bunt_logger_records_total counter")?;
+        for (level, target, count) in self.iter() {
+            write!(
+                f,
+                "bunt_logger_records_total{{level=\"{}\",target=\"",
+                level.as_str().to_lowercase()
+            )?;
+            for c in target.chars() {
+                match c {
+                    '\\' => f.write_str("\\\\")?,
+                    '"' => f.write_str("\\\"")?,
+                    '\n' => f.write_str("\\n")?,
+                    c => write!(f, "{}", c)?,
+                }
+            }
+            writeln!(f, "\"}} {}", count)?;
+        }
+        writeln!(
+            f,
+            "# HELP bunt_logger_dropped_records_total Records dropped by sampling or deduplication."
+        )?;
+        writeln!(f, "# TYPE bunt_logger_dropped_records_total counter")?;
+        writeln!(f, "bunt_logger_dropped_records_total {}", self.dropped)?;
+        writeln!(
+            f,
+            "# HELP bunt_logger_write_errors_total Failed writes to logging targets and sinks."
+        )?;
+        writeln!(f, "# TYPE bunt_logger_write_errors_total counter")?;
+        writeln!(f, "bunt_logger_write_errors_total {}", self.write_errors)
+    }
+}
+
+/// Returns the counters of the records logged so far through the global preferences, for
+/// dashboards and alerts on spikes in the error rate.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{error, Level};
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Error { return; }
+/// error!(target: "db", "Connection lost");
+///
+/// let metrics = bunt_logger::metrics();
+/// assert_eq!(metrics.records(Level::Error, "db"), 1);
+/// assert!(metrics
+///     .to_string()
+///     .contains("bunt_logger_records_total{level=\"error\",target=\"db\"} 1"));
+/// # }
+/// ```
+pub fn metrics() -> Metrics {
+    Metrics {
+        records: RECORDS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone(),
+        dropped: DROPPED.load(Ordering::Relaxed),
+        write_errors: WRITE_ERRORS.load(Ordering::Relaxed),
+    }
+}
@@ -0,0 +1,78 @@
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic;
+use std::thread;
+
+use crate::{Level, Message, Record};
+
+/// Replaces the panic hook with one that logs panics as errors, with the configured theme and
+/// logging targets.
+///
+/// The record has the target `panic` and contains the panic message and location, followed by a
+/// backtrace if one is enabled through `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`. The previous hook
+/// is called instead if the record isn't written, e.g. because the logger is
+/// [quiet](crate::LogPrefs::quiet) or errors are filtered out, or if the global preferences are
+/// locked while panicking, e.g. because a logging target panicked, so that panics are never
+/// swallowed.
+///
+/// # Example
+/// ```rust
+/// # fn main() {
+/// bunt_logger::install_panic_hook();
+/// # }
+/// ```
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let prefs = match crate::try_read() {
+            Some(prefs) => prefs,
+            None => return previous(info),
+        };
+        if Level::Error > crate::STATIC_MAX_LEVEL || !prefs.enabled(Level::Error, "panic") {
+            drop(prefs);
+            return previous(info);
+        }
+
+        let thread = thread::current();
+        let name = thread.name().unwrap_or("<unnamed>");
+        let mut message = Message::new();
+        let _ = match info.location() {
+            Some(location) => bunt::write!(
+                message,
+                "{$bold}thread '{}' panicked at {}{/$}: {}",
+                name,
+                location,
+                payload_str(info.payload()),
+            ),
+            None => bunt::write!(
+                message,
+                "{$bold}thread '{}' panicked{/$}: {}",
+                name,
+                payload_str(info.payload()),
+            ),
+        };
+
+        let backtrace = Backtrace::capture();
+        if backtrace.status() == BacktraceStatus::Captured {
+            let backtrace = backtrace.to_string();
+            let _ = bunt::write!(message, "\nstack backtrace:\n{}", backtrace.trim_end());
+        }
+
+        if !prefs.try_log_record(&Record::new(Level::Error, "panic", message)) {
+            drop(prefs);
+            previous(info);
+        }
+    }));
+}
+
+/// Returns the message of a panic payload, which is a string unless the panic was started with
+/// [`panic::panic_any`].
+fn payload_str(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
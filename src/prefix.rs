@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+thread_local! {
+    /// The names of the scopes entered on the thread, innermost last.
+    static SCOPES: RefCell<Vec<Arc<str>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Enters a named scope on the current thread, returning a guard that leaves it again when
+/// dropped.
+///
+/// All records created on the thread inside the scope, by any macro or through the `log` facade,
+/// are prefixed with its name in the [scope style](crate::Theme::with_scope_style), so that the
+/// output of concurrent workers can be told apart. The names of nested scopes are joined with `/`.
+/// See also [`scope!`](macro@crate::scope).
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// let _scope = bunt_logger::enter_scope("worker-3");
+/// info!("Fetching"); // [worker-3] Fetching
+/// # }
+/// ```
+pub fn enter_scope<T: Into<Arc<str>>>(name: T) -> ScopeGuard {
+    let len = SCOPES.with(|scopes| {
+        let mut scopes = scopes.borrow_mut();
+        scopes.push(name.into());
+        scopes.len() - 1
+    });
+    ScopeGuard {
+        len,
+        _not_send: PhantomData,
+    }
+}
+
+/// Returns the names of the scopes entered on the current thread.
+#[inline]
+pub(crate) fn current() -> Vec<Arc<str>> {
+    SCOPES.with(|scopes| scopes.borrow().clone())
+}
+
+/// A guard that leaves a scope when dropped; see [`enter_scope`].
+#[must_use = "the scope is left as soon as the guard is dropped"]
+pub struct ScopeGuard {
+    /// The number of scopes before the scope was entered.
+    len: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ScopeGuard {
+    #[inline]
+    fn drop(&mut self) {
+        SCOPES.with(|scopes| scopes.borrow_mut().truncate(self.len));
+    }
+}
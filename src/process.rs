@@ -0,0 +1,70 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+
+/// The name that the program was started with, without its directory and extension.
+static NAME: Lazy<String> = Lazy::new(|| {
+    env::args_os()
+        .next()
+        .map(Into::into)
+        .or_else(|| env::current_exe().ok())
+        .and_then(|path| {
+            let name = Path::new(&path).file_stem()?;
+            Some(name.to_string_lossy().into_owned())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_owned())
+});
+
+/// The host name of the machine.
+static HOST_NAME: Lazy<String> = Lazy::new(|| {
+    system_host_name()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .or_else(|| {
+            let name = fs::read_to_string("/etc/hostname").ok()?;
+            Some(name.trim().to_owned())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_owned())
+});
+
+#[cfg(unix)]
+fn system_host_name() -> Option<String> {
+    let mut name = [0u8; 256];
+    // SAFETY: The buffer is valid for its length; one byte is left for the null terminator,
+    // which isn't written if the name is truncated.
+    if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len() - 1) } != 0 {
+        return None;
+    }
+    let len = name.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&name[..len]).into_owned())
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn system_host_name() -> Option<String> {
+    None
+}
+
+/// Returns the host name of the machine, from `gethostname` on Unix, the `HOSTNAME` or
+/// `COMPUTERNAME` environment variable, or `/etc/hostname`, or `localhost` if it's unknown. It is
+/// looked up once.
+#[inline]
+pub(crate) fn host_name() -> &'static str {
+    &HOST_NAME
+}
+
+/// Returns the name of the process, such as `my-tool` for `/usr/bin/my-tool`.
+#[inline]
+pub(crate) fn name() -> &'static str {
+    &NAME
+}
+
+/// Returns the id of the process.
+#[inline]
+pub(crate) fn id() -> u32 {
+    std::process::id()
+}
@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::LevelFilter;
+
+/// A bounded buffer of the most recent records; see
+/// [`LogPrefs::keep_recent`](crate::LogPrefs::keep_recent).
+#[derive(Debug, Clone)]
+pub(crate) struct Recent {
+    /// The most verbose level kept, regardless of the level filters.
+    pub(crate) filter: LevelFilter,
+    capacity: usize,
+    /// Shared between clones.
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl Recent {
+    #[inline]
+    pub(crate) fn new(capacity: usize, filter: LevelFilter) -> Self {
+        Self {
+            filter,
+            capacity,
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Keeps `line`, dropping the oldest line if the buffer is full.
+    pub(crate) fn push(&self, line: String) {
+        let mut lines = self.lock();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Returns the kept lines, oldest first.
+    #[inline]
+    pub(crate) fn lines(&self) -> Vec<String> {
+        self.lock().iter().cloned().collect()
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, VecDeque<String>> {
+        self.lines.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Returns the most recent records kept by [`LogPrefs::keep_recent`](crate::LogPrefs::keep_recent)
+/// of the global preferences, rendered without styles and oldest first, or nothing if no records
+/// are kept.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{debug, Level, LevelFilter};
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Debug { return; }
+/// bunt_logger::with()
+///     .level(Level::Warn)
+///     .keep_recent(100, LevelFilter::Debug);
+/// debug!("Loaded 3 plugins");
+///
+/// assert_eq!(bunt_logger::recent(), ["Loaded 3 plugins"]);
+/// # }
+/// ```
+#[inline]
+pub fn recent() -> Vec<String> {
+    crate::read()
+        .recent
+        .as_ref()
+        .map(Recent::lines)
+        .unwrap_or_default()
+}
@@ -0,0 +1,476 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::SystemTime;
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+use log::Level;
+
+use crate::{link, Field, Location};
+
+/// A single log record: the rendered message together with its metadata.
+#[derive(Debug, Clone)]
+pub struct Record {
+    level: Level,
+    target: String,
+    time: SystemTime,
+    kind: Option<Kind>,
+    message: Message,
+    fields: Vec<Field>,
+    indent: usize,
+    location: Option<Location>,
+    thread: Thread,
+    scopes: Vec<Arc<str>>,
+}
+
+/// The semantic kind of a record logged with [`success!`](crate::success),
+/// [`notice!`](crate::notice) or [`fatal!`](crate::fatal).
+///
+/// Records of a kind carry their own label and style from the [`Theme`](crate::Theme) instead of
+/// those of their level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// A completed operation, logged at the info level.
+    Success,
+    /// A noteworthy event, logged at the info level.
+    Notice,
+    /// An unrecoverable error, logged at the error level.
+    Fatal,
+}
+
+impl Record {
+    /// Creates a record for `message` on the current thread, timestamped with the current time of
+    /// the [clock](crate::set_clock), indented by the current [indentation](fn@crate::indent) and in
+    /// the current [scopes](crate::enter_scope) of the thread.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{Level, Message, Record};
+    ///
+    /// # fn main() {
+    /// let record = Record::new(Level::Info, "my_app", Message::from("Hello!"));
+    /// assert_eq!(record.message().to_string(), "Hello!");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn new<T: Into<String>>(level: Level, target: T, message: Message) -> Self {
+        Self {
+            level,
+            target: target.into(),
+            time: crate::clock::now(),
+            kind: None,
+            message,
+            fields: Vec::new(),
+            indent: crate::indent::current(),
+            location: None,
+            thread: thread::current(),
+            scopes: crate::prefix::current(),
+        }
+    }
+
+    /// Sets the semantic kind of the record.
+    #[inline]
+    pub fn with_kind(mut self, kind: Kind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Sets the source location of the call that logged the record.
+    #[inline]
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Attaches a structured field to the record.
+    #[inline]
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Replaces the level of the record.
+    #[inline]
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    /// Replaces the target of the record.
+    #[inline]
+    pub fn set_target<T: Into<String>>(&mut self, target: T) {
+        self.target = target.into();
+    }
+
+    /// Replaces the message of the record.
+    #[inline]
+    pub fn set_message(&mut self, message: Message) {
+        self.message = message;
+    }
+
+    /// Returns the structured fields of the record for modification, e.g. to attach a field with
+    /// [`push`](Vec::push).
+    #[inline]
+    pub fn fields_mut(&mut self) -> &mut Vec<Field> {
+        &mut self.fields
+    }
+
+    /// Returns the level of the record.
+    #[inline]
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the target of the record; the module path of the call site for records emitted by
+    /// the bunt-logger macros.
+    #[inline]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the time at which the record was created.
+    #[inline]
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Returns the semantic kind of the record, if any.
+    #[inline]
+    pub fn kind(&self) -> Option<Kind> {
+        self.kind
+    }
+
+    /// Returns the rendered message.
+    #[inline]
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// Returns the source location of the call that logged the record, if known.
+    #[inline]
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    /// Returns the thread on which the record was created.
+    #[inline]
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Returns the name of the thread on which the record was created, or its id if it is
+    /// unnamed.
+    #[inline]
+    pub(crate) fn thread_name(&self) -> Cow<'_, str> {
+        match self.thread.name() {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(format!("{:?}", self.thread.id())),
+        }
+    }
+
+    /// Returns the names of the [scopes](crate::enter_scope) the record was created in, outermost
+    /// first.
+    #[inline]
+    pub fn scopes(&self) -> &[Arc<str>] {
+        &self.scopes
+    }
+
+    /// Adds a scope inside the ones the record was created in, e.g. of a `tracing` span.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub(crate) fn push_scope(&mut self, name: Arc<str>) {
+        self.scopes.push(name);
+    }
+
+    /// Returns the names of the scopes of the record joined with `/`, or `None` if there are
+    /// none.
+    pub(crate) fn scope_path(&self) -> Option<String> {
+        if self.scopes.is_empty() {
+            return None;
+        }
+        Some(self.scopes.join("/"))
+    }
+
+    /// Returns the indentation level of the record; see [`indent`](fn@crate::indent).
+    #[inline]
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    /// Returns the structured fields of the record, in the order they were attached.
+    #[inline]
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+}
+
+/// A message rendered by bunt.
+///
+/// The message keeps its styling as a sequence of styled segments, so that it can be written to any
+/// [`WriteColor`] later on; writers that don't support color simply drop the styles. The
+/// [`Display`](fmt::Display) implementation prints the plain text.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::Message;
+///
+/// # fn main() {
+/// let mut message = Message::new();
+/// let _ = bunt::write!(message, "{$red}Oh no!{/$} Something went wrong.");
+/// assert_eq!(message.to_string(), "Oh no! Something went wrong.");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    /// The style set before the text, or `None` if the style was reset.
+    spec: Option<ColorSpec>,
+    text: String,
+}
+
+impl Span {
+    /// Returns the text to write to `writer`, with hyperlinks replaced if it doesn't support
+    /// color.
+    #[inline]
+    fn text_for(&self, writer: &dyn WriteColor) -> Cow<'_, str> {
+        if writer.supports_color() {
+            Cow::Borrowed(&self.text)
+        } else {
+            link::strip(&self.text)
+        }
+    }
+}
+
+impl Message {
+    /// Creates an empty message.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the message contains no text.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|span| span.text.is_empty())
+    }
+
+    /// Writes the message, including its styles, to `writer`.
+    pub fn write_to(&self, writer: &mut dyn WriteColor) -> io::Result<()> {
+        let mut styled = false;
+        for span in &self.spans {
+            match &span.spec {
+                Some(spec) => {
+                    writer.set_color(spec)?;
+                    styled = true;
+                }
+                None if styled => {
+                    writer.reset()?;
+                    styled = false;
+                }
+                None => {}
+            }
+            writer.write_all(span.text_for(writer).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Like [`write_to`](Self::write_to), but unstyled parts of the message are written in
+    /// the `base` style.
+    pub(crate) fn write_with_base(
+        &self,
+        writer: &mut dyn WriteColor,
+        base: &ColorSpec,
+    ) -> io::Result<()> {
+        for span in &self.spans {
+            match &span.spec {
+                Some(spec) if !spec.is_none() => writer.set_color(spec)?,
+                _ => writer.set_color(base)?,
+            }
+            writer.write_all(span.text_for(writer).as_bytes())?;
+        }
+        writer.reset()
+    }
+
+    /// Returns the first `max` characters of the message followed by a dimmed `… (+N chars)`, or
+    /// `None` if the message isn't longer than that.
+    pub(crate) fn truncated(&self, max: usize) -> Option<Message> {
+        let len: usize = self
+            .spans
+            .iter()
+            .map(|span| span.text.chars().count())
+            .sum();
+        if len <= max {
+            return None;
+        }
+
+        let mut truncated = Message::new();
+        let mut remaining = max;
+        for span in &self.spans {
+            if remaining == 0 {
+                break;
+            }
+            let end = span
+                .text
+                .char_indices()
+                .nth(remaining)
+                .map_or(span.text.len(), |(i, _)| i);
+            remaining -= span.text[..end].chars().count();
+            truncated.spans.push(Span {
+                spec: span.spec.clone(),
+                text: span.text[..end].to_owned(),
+            });
+        }
+        let _ = bunt::write!(truncated, "{$dimmed}… (+{} chars){/$}", len - max);
+        Some(truncated)
+    }
+
+    #[cfg(feature = "regex")]
+    /// Returns the text of the message, including hyperlinks.
+    pub(crate) fn raw_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[cfg(feature = "regex")]
+    /// Returns the message with the byte `ranges` of its [raw text](Self::raw_text), which are
+    /// sorted and don't overlap, replaced with `replacement` in the style of the text where each
+    /// range starts. The rest of the message keeps its styles.
+    pub(crate) fn replace_ranges(&self, ranges: &[(usize, usize)], replacement: &str) -> Message {
+        let mut spans = Vec::with_capacity(self.spans.len());
+        let mut ranges = ranges.iter().peekable();
+        let mut offset = 0;
+        for (i, span) in self.spans.iter().enumerate() {
+            let end = offset + span.text.len();
+            let last = i + 1 == self.spans.len();
+            let mut text = String::new();
+            let mut pos = offset;
+            while let Some(&&(start, stop)) = ranges.peek() {
+                if start >= offset && (start < end || last && start == end) {
+                    text.push_str(&span.text[pos - offset..start - offset]);
+                    text.push_str(replacement);
+                    pos = stop.min(end);
+                } else if start < offset && stop > offset {
+                    // The range started in an earlier span, which took the replacement.
+                    pos = stop.min(end);
+                } else {
+                    break;
+                }
+                if stop > end {
+                    break;
+                }
+                ranges.next();
+            }
+            text.push_str(&span.text[pos - offset..]);
+            spans.push(Span {
+                spec: span.spec.clone(),
+                text,
+            });
+            offset = end;
+        }
+        Message { spans }
+    }
+
+    /// Returns the message with `f` applied to the text of each span, or `None` if `f` returned
+    /// `None` for all of them.
+    pub(crate) fn map_spans(&self, mut f: impl FnMut(&str) -> Option<String>) -> Option<Message> {
+        let mut changed = false;
+        let spans = self
+            .spans
+            .iter()
+            .map(|span| Span {
+                spec: span.spec.clone(),
+                text: match f(&span.text) {
+                    Some(text) => {
+                        changed = true;
+                        text
+                    }
+                    None => span.text.clone(),
+                },
+            })
+            .collect();
+        changed.then_some(Message { spans })
+    }
+
+    /// Returns the style that is active at the end of the message.
+    #[inline]
+    pub(crate) fn current_style(&self) -> Option<&ColorSpec> {
+        self.spans.last().and_then(|span| span.spec.as_ref())
+    }
+
+    #[inline]
+    fn push_style(&mut self, spec: Option<ColorSpec>) {
+        match self.spans.last_mut() {
+            Some(last) if last.text.is_empty() => last.spec = spec,
+            _ => self.spans.push(Span {
+                spec,
+                text: String::new(),
+            }),
+        }
+    }
+}
+
+impl From<String> for Message {
+    #[inline]
+    fn from(text: String) -> Self {
+        Self {
+            spans: vec![Span { spec: None, text }],
+        }
+    }
+}
+
+impl From<&str> for Message {
+    #[inline]
+    fn from(text: &str) -> Self {
+        Self::from(text.to_owned())
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.spans
+            .iter()
+            .try_for_each(|span| f.write_str(&link::strip(&span.text)))
+    }
+}
+
+impl Write for Message {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        match self.spans.last_mut() {
+            Some(last) => last.text.push_str(&text),
+            None => self.spans.push(Span {
+                spec: None,
+                text: text.into_owned(),
+            }),
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteColor for Message {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.push_style(Some(spec.clone()));
+        Ok(())
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.push_style(None);
+        Ok(())
+    }
+}
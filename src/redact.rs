@@ -0,0 +1,270 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+#[cfg(feature = "regex")]
+use regex::{NoExpand, Regex};
+
+use crate::{Field, Message, Record, Value};
+
+/// A transformation that scrubs secrets from the text of records; see
+/// [`LogPrefs::redact_with`](crate::LogPrefs::redact_with).
+#[derive(Clone)]
+pub(crate) enum Redaction {
+    #[cfg(feature = "regex")]
+    Pattern(Regex, String),
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl Redaction {
+    #[inline]
+    fn apply<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self {
+            #[cfg(feature = "regex")]
+            Self::Pattern(pattern, replacement) => pattern.replace_all(text, NoExpand(replacement)),
+            Self::Custom(redact) => Cow::Owned(redact(text)),
+        }
+    }
+}
+
+/// Applies `redactions` in order to `message`, keeping its styles, or returns `None` if none of
+/// them changed it.
+fn redact_message(redactions: &[Redaction], message: &Message) -> Option<Message> {
+    let mut current = Cow::Borrowed(message);
+    for redaction in redactions {
+        let next = match redaction {
+            #[cfg(feature = "regex")]
+            Redaction::Pattern(pattern, replacement) => {
+                let ranges: Vec<_> = pattern
+                    .find_iter(&current.raw_text())
+                    .map(|found| (found.start(), found.end()))
+                    .collect();
+                if ranges.is_empty() {
+                    continue;
+                }
+                current.replace_ranges(&ranges, replacement)
+            }
+            // The text that a function changed can't be mapped back onto the styles, so it
+            // redacts each span on its own.
+            Redaction::Custom(redact) => match current.map_spans(|text| {
+                let redacted = redact(text);
+                Some(redacted).filter(|redacted| redacted != text)
+            }) {
+                Some(next) => next,
+                None => continue,
+            },
+        };
+        current = Cow::Owned(next);
+    }
+    match current {
+        Cow::Owned(redacted) if redacted != *message => Some(redacted),
+        _ => None,
+    }
+}
+
+/// Applies `redactions` in order, returning `None` if none of them changed `text`.
+fn apply_all(redactions: &[Redaction], text: &str) -> Option<String> {
+    let mut current = Cow::Borrowed(text);
+    for redaction in redactions {
+        if let Cow::Owned(next) = redaction.apply(&current) {
+            current = Cow::Owned(next);
+        }
+    }
+    match current {
+        Cow::Owned(redacted) if redacted != text => Some(redacted),
+        _ => None,
+    }
+}
+
+/// Returns a copy of `record` with `redactions` applied to its message and the string values of
+/// its fields, or `None` if nothing had to be redacted.
+///
+/// Patterns are matched against the whole message, and a replacement takes the style of the text
+/// where its match starts, so that a secret that spans several styled parts is still caught while
+/// the rest of the message keeps its styles. Functions see each styled part on its own. Field
+/// values are redacted as `key=value`, so that a pattern like `password=\S+` covers both messages
+/// and a `password` field.
+pub(crate) fn redact(redactions: &[Redaction], record: &Record) -> Option<Record> {
+    if redactions.is_empty() {
+        return None;
+    }
+    let mut redacted = None;
+
+    if let Some(message) = redact_message(redactions, record.message()) {
+        redacted
+            .get_or_insert_with(|| record.clone())
+            .set_message(message);
+    }
+    for (i, field) in record.fields().iter().enumerate() {
+        let value = match field.value() {
+            Value::Str(value) | Value::Debug(value) => value,
+            _ => continue,
+        };
+        let pair = format!("{}={}", field.key(), value);
+        if let Some(pair) = apply_all(redactions, &pair) {
+            // If a redaction changed the key itself, fall back to redacting the value alone.
+            let value = match pair.strip_prefix(field.key()) {
+                Some(value) if value.starts_with('=') => value[1..].to_owned(),
+                _ => match apply_all(redactions, value) {
+                    Some(value) => value,
+                    None => continue,
+                },
+            };
+            let key = field.key().to_owned();
+            redacted.get_or_insert_with(|| record.clone()).fields_mut()[i] =
+                Field::new(key, Value::Str(value));
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use bunt::termcolor::Ansi;
+    use log::Level;
+
+    use super::*;
+
+    fn custom(redact: fn(&str) -> String) -> Redaction {
+        Redaction::Custom(Arc::new(redact))
+    }
+
+    #[cfg(feature = "regex")]
+    fn pattern(pattern: &str, replacement: &str) -> Redaction {
+        Redaction::Pattern(Regex::new(pattern).unwrap(), replacement.to_owned())
+    }
+
+    /// Returns `message` with its styles as ANSI escape sequences.
+    fn ansi(message: &Message) -> String {
+        let mut out = Ansi::new(Vec::new());
+        message.write_to(&mut out).unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    fn styled(text: &str) -> Message {
+        let mut message = Message::new();
+        let _ = bunt::write!(message, "{$red}{}{/$} done", text);
+        message
+    }
+
+    fn info(message: &str) -> Record {
+        Record::new(Level::Info, "my_app", Message::from(message))
+    }
+
+    fn field<'r>(record: &'r Record, key: &str) -> &'r Value {
+        let field = record.fields().iter().find(|field| field.key() == key);
+        field.unwrap().value()
+    }
+
+    #[test]
+    fn redact_with_functions() {
+        let redactions = [custom(|text| text.replace("hunter2", "***"))];
+        let record = info("Logging in with hunter2")
+            .with_field(Field::new("password", "hunter2"))
+            .with_field(Field::new("user", "alice"));
+
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(redacted.message().to_string(), "Logging in with ***");
+        assert_eq!(field(&redacted, "password"), &Value::Str("***".to_owned()));
+        assert_eq!(field(&redacted, "user"), &Value::Str("alice".to_owned()));
+        // The original is left alone.
+        assert_eq!(record.message().to_string(), "Logging in with hunter2");
+    }
+
+    #[test]
+    fn keep_records_without_secrets() {
+        let redactions = [custom(|text| text.replace("hunter2", "***"))];
+        let record = info("Logged in")
+            .with_field(Field::new("user", "alice"))
+            .with_field(Field::new("attempts", 2));
+        assert!(redact(&redactions, &record).is_none());
+        assert!(redact(&[], &record).is_none());
+
+        // A function that returns its input unchanged doesn't count as a change either.
+        let redactions = [custom(str::to_owned)];
+        assert!(redact(&redactions, &record).is_none());
+    }
+
+    #[test]
+    fn redact_styled_parts_with_functions() {
+        let redactions = [custom(|text| text.replace("hunter2", "***"))];
+        let record = Record::new(Level::Info, "my_app", styled("hunter2"));
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(ansi(redacted.message()), ansi(&styled("***")));
+
+        // Functions see each styled part on its own, so a secret split across styles stays.
+        let mut message = Message::new();
+        let _ = bunt::write!(message, "hun{$red}ter2{/$}");
+        let record = Record::new(Level::Info, "my_app", message);
+        assert!(redact(&redactions, &record).is_none());
+    }
+
+    #[test]
+    fn apply_redactions_in_order() {
+        let redactions = [
+            custom(|text| text.replace("secret", "hunter2")),
+            custom(|text| text.replace("hunter2", "***")),
+        ];
+        let redacted = redact(&redactions, &info("a secret")).unwrap();
+        assert_eq!(redacted.message().to_string(), "a ***");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redact_with_patterns() {
+        let redactions = [pattern(r"password=\S+", "password=***")];
+        let record = info("Connecting with password=hunter2 to db")
+            .with_field(Field::new("password", "hunter2"))
+            .with_field(Field::new("user", "alice"));
+
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(
+            redacted.message().to_string(),
+            "Connecting with password=*** to db"
+        );
+        assert_eq!(field(&redacted, "password"), &Value::Str("***".to_owned()));
+        assert_eq!(field(&redacted, "user"), &Value::Str("alice".to_owned()));
+
+        let record = info("Connecting to db").with_field(Field::new("user", "alice"));
+        assert!(redact(&redactions, &record).is_none());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn insert_replacements_literally() {
+        let redactions = [pattern(r"token=(\w+)", "token=$1")];
+        let redacted = redact(&redactions, &info("token=abc")).unwrap();
+        assert_eq!(redacted.message().to_string(), "token=$1");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redact_across_styles_with_patterns() {
+        let redactions = [pattern(r"hunter\d", "***")];
+        let record = Record::new(Level::Info, "my_app", styled("hunter2"));
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(ansi(redacted.message()), ansi(&styled("***")));
+
+        // A match that spans several styles takes the style where it starts.
+        let mut message = Message::new();
+        let _ = bunt::write!(message, "key hun{$red}ter2{/$} done");
+        let record = Record::new(Level::Info, "my_app", message);
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(redacted.message().to_string(), "key *** done");
+        assert!(ansi(redacted.message()).starts_with("key ***\u{1b}"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redact_values_of_changed_keys() {
+        // The pattern also matches the key, so only the value is redacted.
+        let redactions = [pattern(r"(?i)secret\S*", "***")];
+        let record = info("Loaded").with_field(Field::new("secret", "secret-value"));
+        let redacted = redact(&redactions, &record).unwrap();
+        assert_eq!(redacted.fields()[0].key(), "secret");
+        assert_eq!(field(&redacted, "secret"), &Value::Str("***".to_owned()));
+
+        // Other values than strings are left alone.
+        let record = info("Loaded").with_field(Field::new("secret", 42));
+        assert!(redact(&redactions, &record).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::panic;
+
+use crate::{Global, Level, Location, Message, Record};
+
+/// Combinators that log the error of a [`Result`] through the global preferences.
+///
+/// The records have the target `result`, the source location of the call, and a message of the
+/// form `context: error`.
+///
+/// # Example
+/// ```rust
+/// use std::fs;
+///
+/// use bunt_logger::ResultExt;
+///
+/// # fn main() {
+/// let config = fs::read_to_string("config.toml").log_warn_default("reading the config");
+/// let port: u16 = "80a".parse().log_err("parsing the port").unwrap_or(8080);
+/// # let _ = (config, port);
+/// # }
+/// ```
+pub trait ResultExt<T, E> {
+    /// Logs the error, if any, as an error, and returns the result unchanged.
+    fn log_err(self, context: &str) -> Self;
+
+    /// Logs the error, if any, as a warning, and returns the result unchanged.
+    fn log_warn(self, context: &str) -> Self;
+
+    /// Logs the error, if any, as an error, and returns the value or its default.
+    fn log_err_default(self, context: &str) -> T
+    where
+        T: Default;
+
+    /// Logs the error, if any, as a warning, and returns the value or its default.
+    fn log_warn_default(self, context: &str) -> T
+    where
+        T: Default;
+}
+
+impl<T, E: fmt::Display> ResultExt<T, E> for Result<T, E> {
+    #[inline]
+    #[track_caller]
+    fn log_err(self, context: &str) -> Self {
+        if let Err(err) = &self {
+            log_error(Level::Error, context, err);
+        }
+        self
+    }
+
+    #[inline]
+    #[track_caller]
+    fn log_warn(self, context: &str) -> Self {
+        if let Err(err) = &self {
+            log_error(Level::Warn, context, err);
+        }
+        self
+    }
+
+    #[inline]
+    #[track_caller]
+    fn log_err_default(self, context: &str) -> T
+    where
+        T: Default,
+    {
+        self.log_err(context).unwrap_or_default()
+    }
+
+    #[inline]
+    #[track_caller]
+    fn log_warn_default(self, context: &str) -> T
+    where
+        T: Default,
+    {
+        self.log_warn(context).unwrap_or_default()
+    }
+}
+
+#[track_caller]
+fn log_error(level: Level, context: &str, err: &dyn fmt::Display) {
+    if level > crate::STATIC_MAX_LEVEL || !Global.enabled(level, "result") {
+        return;
+    }
+    let caller = panic::Location::caller();
+    let message = Message::from(format!("{}: {}", context, err));
+    let record = Record::new(level, "result", message)
+        .with_location(Location::new(caller.file(), caller.line()));
+    Global.log(&record);
+}
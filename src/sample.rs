@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Keeps a fraction of the records of a level; see
+/// [`LogPrefs::sample`](crate::LogPrefs::sample).
+#[derive(Debug)]
+pub(crate) struct Sampler {
+    rate: f64,
+    seen: AtomicU64,
+}
+
+impl Sampler {
+    #[inline]
+    pub(crate) fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts a record and returns whether it is kept.
+    ///
+    /// The records are kept evenly spread, so that a rate of `0.01` keeps every 100th record.
+    #[inline]
+    pub(crate) fn keep(&self) -> bool {
+        let seen = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((seen + 1.0) * self.rate).floor() > (seen * self.rate).floor()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::LogPrefs;
+
+/// Snapshots the global preferences, returning a guard that restores them when dropped.
+///
+/// Changes made through [`with`](crate::with) while the guard is alive, including changes to the
+/// logging targets, are undone when it goes out of scope. Guards may be nested; they should be
+/// dropped in reverse order of creation. Dropping a guard locks the preferences, so it must not
+/// be dropped while the result of [`with`](crate::with) is still held.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{debug, Level};
+///
+/// # fn main() {
+/// bunt_logger::with().level(Level::Info);
+/// {
+///     let _scope = bunt_logger::with_scope();
+///     bunt_logger::with().level(Level::Debug);
+///     debug!("Only printed inside the scope");
+/// }
+/// debug!("Not printed anymore");
+/// # }
+/// ```
+#[inline]
+pub fn with_scope() -> PrefsGuard {
+    PrefsGuard {
+        previous: Some(crate::with().clone()),
+    }
+}
+
+/// A guard that restores the global preferences to an earlier snapshot when dropped; see
+/// [`with_scope`].
+#[must_use = "the preferences are restored as soon as the guard is dropped"]
+pub struct PrefsGuard {
+    previous: Option<LogPrefs>,
+}
+
+impl Drop for PrefsGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            crate::with().restore(previous);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::raw::c_int;
+use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The end of the pipe that the signal handler writes to, to wake the reload thread up.
+static WAKE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_hangup(_: c_int) {
+    let fd = WAKE.load(Ordering::Relaxed);
+    // SAFETY: `write` is async-signal-safe. If the pipe is full, a reload is pending anyway.
+    unsafe { libc::write(fd, [0u8].as_ptr().cast(), 1) };
+}
+
+/// Installs the SIGHUP handler and starts the thread that reloads the global preferences, unless
+/// that happened before.
+pub(crate) fn install() -> io::Result<()> {
+    static INSTALLED: Mutex<bool> = Mutex::new(false);
+    let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+    if *installed {
+        return Ok(());
+    }
+
+    let (reader, writer) = pipe()?;
+    thread::Builder::new()
+        .name("bunt-logger-sighup".to_owned())
+        .spawn(move || wait(reader))?;
+    // The write end stays open for the handler from now on.
+    WAKE.store(writer.into_raw_fd(), Ordering::Relaxed);
+    let handler = on_hangup as extern "C" fn(c_int) as libc::sighandler_t;
+    // SAFETY: The handler only writes to a pipe, which is async-signal-safe.
+    if unsafe { libc::signal(libc::SIGHUP, handler) } == libc::SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+    *installed = true;
+    Ok(())
+}
+
+/// Opens a pipe whose ends are closed on exec, and whose write end doesn't block.
+fn pipe() -> io::Result<(File, File)> {
+    let mut fds: [RawFd; 2] = [-1; 2];
+    // SAFETY: `fds` has room for both ends.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: The ends were just opened, and nothing else owns them.
+    let (reader, writer) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    // SAFETY: The ends are open, and these calls only change their flags.
+    let failed = unsafe {
+        libc::fcntl(fds[0], libc::F_SETFD, libc::FD_CLOEXEC) == -1
+            || libc::fcntl(fds[1], libc::F_SETFD, libc::FD_CLOEXEC) == -1
+            || libc::fcntl(fds[1], libc::F_SETFL, libc::O_NONBLOCK) == -1
+    };
+    if failed {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((reader, writer))
+}
+
+/// Reloads whenever the signal handler writes to `reader`, once for SIGHUPs that arrive together.
+fn wait(mut reader: File) {
+    let mut buf = [0; 64];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return,
+            Ok(_) => reload(),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return,
+        }
+    }
+}
+
+/// Reopens the log files, then reapplies the configuration file and the filter environment
+/// variable that the global preferences were last loaded from.
+fn reload() {
+    crate::reopen_files();
+
+    let (config_file, filter_env) = {
+        let prefs = crate::read();
+        (prefs.config_file.clone(), prefs.filter_env.clone())
+    };
+    if let Some(path) = config_file {
+        crate::config::reload(&path);
+    }
+    if let Some(var) = filter_env {
+        let result = crate::with().parse_env(&var).map(|_| ());
+        if let Err(err) = result {
+            crate::warn!(target: "config", "Failed to reload the filters from {}: {}", var, err);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use std::io;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::WorkerGuard;
+
+/// The number of records that may be queued before new ones are dropped.
+pub(crate) const CAPACITY: usize = 64_000;
+/// How long connecting, sending and waiting for the response may take.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(10);
+/// The delay before the first retry, doubled after every failed one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+enum Command<T> {
+    Record(T),
+    Flush,
+    Shutdown,
+}
+
+/// The queue of a thread that sends records in batches, once `size` of them are queued or
+/// `interval` after the first of them was.
+pub(crate) struct Batcher<T> {
+    sender: SyncSender<Command<T>>,
+    /// The name of the destination, as used in errors.
+    name: &'static str,
+}
+
+impl<T: Send + 'static> Batcher<T> {
+    /// Spawns the thread, which passes every batch to `send` and stops once the returned guard is
+    /// dropped, after sending everything that was queued.
+    pub(crate) fn spawn<F>(
+        name: &'static str,
+        size: usize,
+        interval: Duration,
+        mut send: F,
+    ) -> io::Result<(Self, WorkerGuard)>
+    where
+        F: FnMut(&mut Vec<T>) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let handle = thread::Builder::new()
+            .name(format!("bunt-logger-{}", name.to_lowercase()))
+            .spawn(move || {
+                let mut batch = Vec::new();
+                let mut deadline: Option<Instant> = None;
+                loop {
+                    let command = match deadline {
+                        Some(deadline) => {
+                            let timeout = deadline.saturating_duration_since(Instant::now());
+                            receiver.recv_timeout(timeout)
+                        }
+                        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    let stop = match command {
+                        Ok(Command::Record(record)) => {
+                            deadline.get_or_insert_with(|| Instant::now() + interval);
+                            batch.push(record);
+                            if batch.len() < size {
+                                continue;
+                            }
+                            false
+                        }
+                        Ok(Command::Flush) | Err(RecvTimeoutError::Timeout) => false,
+                        Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => true,
+                    };
+
+                    if !batch.is_empty() {
+                        send(&mut batch);
+                        batch.clear();
+                    }
+                    deadline = None;
+                    if stop {
+                        break;
+                    }
+                }
+            })?;
+
+        let shutdown = sender.clone();
+        let guard = WorkerGuard::new(
+            Box::new(move || {
+                let _ = shutdown.send(Command::Shutdown);
+            }),
+            handle,
+        );
+        Ok((Self { sender, name }, guard))
+    }
+
+    /// Queues `record`, failing with [`io::ErrorKind::WouldBlock`] if the queue is full.
+    pub(crate) fn push(&self, record: T) -> io::Result<()> {
+        match self.sender.try_send(Command::Record(record)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("the {} sink fell behind and dropped a record", self.name),
+            )),
+            Err(TrySendError::Disconnected(_)) => Err(self.disconnected()),
+        }
+    }
+
+    /// Sends the queued records, without waiting for them to be delivered.
+    #[inline]
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        self.sender
+            .send(Command::Flush)
+            .map_err(|_| self.disconnected())
+    }
+
+    #[inline]
+    fn disconnected(&self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            format!("the bunt-logger {} thread has stopped", self.name),
+        )
+    }
+}
+
+impl<T> fmt::Debug for Batcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Batcher")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The outcome of an attempt to deliver a batch that failed.
+pub(crate) enum Failure {
+    /// The batch may be delivered if it is retried.
+    Retry(io::Error),
+    /// The destination rejected the batch.
+    Reject(io::Error),
+}
+
+/// Calls `attempt` until it succeeds, retrying up to `max_retries` times with growing delays
+/// unless it fails with [`Failure::Reject`].
+#[inline]
+pub(crate) fn retry<F>(max_retries: u32, attempt: F) -> io::Result<()>
+where
+    F: FnMut() -> Result<(), Failure>,
+{
+    retry_with(max_retries, attempt, thread::sleep)
+}
+
+/// Like [`retry`], but waits with `sleep`, so that tests don't have to.
+fn retry_with<F, S>(max_retries: u32, mut attempt: F, mut sleep: S) -> io::Result<()>
+where
+    F: FnMut() -> Result<(), Failure>,
+    S: FnMut(Duration),
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut retries = 0;
+    loop {
+        match attempt() {
+            Ok(()) => return Ok(()),
+            Err(Failure::Retry(_)) if retries < max_retries => {
+                sleep(backoff);
+                backoff *= 2;
+                retries += 1;
+            }
+            Err(Failure::Retry(err)) | Err(Failure::Reject(err)) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::Receiver;
+
+    use super::*;
+
+    /// Spawns a batcher that sends its batches to the returned receiver.
+    fn spawn(size: usize, interval: Duration) -> (Batcher<u32>, WorkerGuard, Receiver<Vec<u32>>) {
+        let (sender, receiver) = mpsc::channel();
+        let (batcher, guard) = Batcher::spawn("Test", size, interval, move |batch| {
+            let _ = sender.send(batch.clone());
+        })
+        .unwrap();
+        (batcher, guard, receiver)
+    }
+
+    fn failure(kind: io::ErrorKind) -> io::Error {
+        io::Error::new(kind, "failed")
+    }
+
+    #[test]
+    fn batch_by_size() {
+        let (batcher, guard, receiver) = spawn(2, Duration::from_secs(3600));
+        for record in 1..=5 {
+            batcher.push(record).unwrap();
+        }
+        drop(guard);
+        let batches: Vec<_> = receiver.iter().collect();
+        assert_eq!(batches, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn batch_on_flush() {
+        let (batcher, guard, receiver) = spawn(100, Duration::from_secs(3600));
+        batcher.push(1).unwrap();
+        batcher.push(2).unwrap();
+        batcher.flush().unwrap();
+        assert_eq!(receiver.recv().unwrap(), vec![1, 2]);
+        // Flushing without queued records sends nothing.
+        batcher.flush().unwrap();
+        batcher.push(3).unwrap();
+        drop(guard);
+        let batches: Vec<_> = receiver.iter().collect();
+        assert_eq!(batches, vec![vec![3]]);
+    }
+
+    #[test]
+    fn batch_by_interval() {
+        let (batcher, _guard, receiver) = spawn(100, Duration::from_millis(10));
+        batcher.push(1).unwrap();
+        batcher.push(2).unwrap();
+        let batch = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(batch, vec![1, 2]);
+    }
+
+    #[test]
+    fn stopped_batcher() {
+        let (batcher, guard, _receiver) = spawn(100, Duration::from_secs(3600));
+        drop(guard);
+        assert_eq!(
+            batcher.push(1).unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            batcher.flush().unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn retry_with_backoff() {
+        let mut sleeps = Vec::new();
+        let mut attempts = 0;
+        let result = retry_with(
+            3,
+            || {
+                attempts += 1;
+                match attempts {
+                    1 | 2 => Err(Failure::Retry(failure(io::ErrorKind::TimedOut))),
+                    _ => Ok(()),
+                }
+            },
+            |delay| sleeps.push(delay),
+        );
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            sleeps,
+            vec![Duration::from_millis(500), Duration::from_secs(1)]
+        );
+    }
+
+    #[test]
+    fn retry_up_to_max_retries() {
+        let mut sleeps = Vec::new();
+        let mut attempts = 0;
+        let result = retry_with(
+            2,
+            || {
+                attempts += 1;
+                Err(Failure::Retry(failure(io::ErrorKind::TimedOut)))
+            },
+            |delay| sleeps.push(delay),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            sleeps,
+            vec![Duration::from_millis(500), Duration::from_secs(1)]
+        );
+    }
+
+    #[test]
+    fn reject_without_retrying() {
+        let mut sleeps = Vec::new();
+        let mut attempts = 0;
+        let result = retry_with(
+            5,
+            || {
+                attempts += 1;
+                match attempts {
+                    1 => Err(Failure::Retry(failure(io::ErrorKind::TimedOut))),
+                    _ => Err(Failure::Reject(failure(io::ErrorKind::InvalidData))),
+                }
+            },
+            |delay| sleeps.push(delay),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(attempts, 2);
+        assert_eq!(sleeps, vec![Duration::from_millis(500)]);
+    }
+}
@@ -0,0 +1,68 @@
+use std::io;
+
+use log::LevelFilter;
+
+use crate::{Record, Sink};
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn OutputDebugStringW(output_string: *const u16);
+}
+
+/// A sink that writes records to the debugger with `OutputDebugStringW`, where they show up in
+/// the output window of Visual Studio or in DebugView, for GUI applications without a console.
+///
+/// Each record is written as one line with its level, target, plain message and fields, e.g.
+/// `WARN app::ui: Slow frame ms=42`. Nothing is written if no debugger is listening, so the sink
+/// is cheap to keep in release builds.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::DebugOutput;
+///
+/// # fn main() {
+/// bunt_logger::with().add_sink(Box::new(DebugOutput::new()));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DebugOutput {
+    level: Option<LevelFilter>,
+}
+
+impl DebugOutput {
+    /// Returns a sink that writes all records that pass the filters of the preferences.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the most verbose level that is written.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = Some(level);
+        self
+    }
+}
+
+impl Sink for DebugOutput {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if matches!(self.level, Some(level) if level < record.level()) {
+            return Ok(());
+        }
+
+        let mut text = format!(
+            "{} {}: {}",
+            record.level(),
+            record.target(),
+            record.message()
+        );
+        for field in record.fields() {
+            text.push_str(&format!(" {}={}", field.key(), field.value()));
+        }
+        text.push('\n');
+        let text: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+        // SAFETY: `text` is a null-terminated UTF-16 string that outlives the call.
+        unsafe { OutputDebugStringW(text.as_ptr()) };
+        Ok(())
+    }
+}
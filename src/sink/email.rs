@@ -0,0 +1,363 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::LevelFilter;
+
+use super::http::report;
+use crate::{Record, Sink, TimestampFormat, WorkerGuard};
+
+/// The number of records that may be queued before new ones are dropped.
+const CAPACITY: usize = 1024;
+/// The number of records listed in one digest; later ones are only counted.
+const MAX_LISTED: usize = 50;
+/// How long connecting and each step of the SMTP dialog may take.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+enum Command {
+    Record(String),
+    Shutdown,
+}
+
+/// A sink that emails a digest of error records through an SMTP relay, so that unattended tools
+/// such as cron jobs can report their failures.
+///
+/// The first record starts a digest, which collects the records that follow for the
+/// [`interval`](EmailBuilder::interval) and is then sent as one plain text email listing the time,
+/// level, target and message of the first 50 of them and counting the rest. Digests with fewer
+/// records than the [`threshold`](EmailBuilder::threshold) are dropped, so that an occasional
+/// error doesn't wake anyone up. Emails are sent from a dedicated thread, which stops once the
+/// [`WorkerGuard`] returned alongside the sink is dropped, after sending the pending digest if it
+/// reached the threshold. Failures are passed to the [error handler](crate::LogPrefs::on_error).
+///
+/// Only plain SMTP without TLS or authentication is supported, as offered by a local relay such as
+/// Postfix or an internal mail server.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use bunt_logger::Email;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (email, _guard) = Email::builder("localhost:25", "backup@example.com")
+///     .to("ops@example.com")
+///     .subject("nightly backup")
+///     .threshold(3)
+///     .interval(Duration::from_secs(600))
+///     .spawn()?;
+/// bunt_logger::with().add_sink(Box::new(email));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Email {
+    sender: SyncSender<Command>,
+    level: LevelFilter,
+}
+
+impl Email {
+    /// Returns a builder for a sink that sends emails from the address `from` through the SMTP
+    /// server at `server`, e.g. `"localhost:25"`.
+    #[inline]
+    pub fn builder<S: Into<String>, F: Into<String>>(server: S, from: F) -> EmailBuilder {
+        EmailBuilder {
+            server: server.into(),
+            from: from.into(),
+            to: Vec::new(),
+            subject: None,
+            level: LevelFilter::Error,
+            threshold: 1,
+            interval: Duration::from_secs(600),
+        }
+    }
+}
+
+impl Sink for Email {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let line = format!(
+            "{} {} {}: {}",
+            TimestampFormat::Seconds
+                .format(record.time())
+                .unwrap_or_default(),
+            record.level(),
+            record.target(),
+            record.message()
+        );
+        match self.sender.try_send(Command::Record(line)) {
+            // Like webhooks, digests are best-effort.
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the bunt-logger email thread has stopped",
+            )),
+        }
+    }
+}
+
+/// A builder for an [`Email`] sink.
+#[derive(Debug, Clone)]
+pub struct EmailBuilder {
+    server: String,
+    from: String,
+    to: Vec<String>,
+    subject: Option<String>,
+    level: LevelFilter,
+    threshold: usize,
+    interval: Duration,
+}
+
+impl EmailBuilder {
+    /// Adds a recipient of the digests. At least one is required.
+    #[inline]
+    pub fn to<T: Into<String>>(mut self, recipient: T) -> Self {
+        self.to.push(recipient.into());
+        self
+    }
+
+    /// Sets the start of the subject of the digests, which is followed by the number of records.
+    /// Defaults to the host name of the machine.
+    #[inline]
+    pub fn subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the most verbose level that is collected. Defaults to [`LevelFilter::Error`].
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the number of records that a digest needs to be sent. Defaults to 1.
+    #[inline]
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    /// Sets the time for which a digest collects records after the first one. Defaults to 10
+    /// minutes.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Spawns the thread that sends the digests. Fails if there is no recipient, or the address
+    /// of the server is invalid or can't be resolved.
+    pub fn spawn(self) -> io::Result<(Email, WorkerGuard)> {
+        if self.to.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the email sink needs a recipient",
+            ));
+        }
+        let invalid = |addr: &str| addr.contains(['\r', '\n', '<', '>']);
+        if invalid(&self.from) || self.to.iter().any(|to| invalid(to)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid email address",
+            ));
+        }
+        self.server.to_socket_addrs()?;
+
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let level = self.level;
+        let handle = thread::Builder::new()
+            .name("bunt-logger-email".to_owned())
+            .spawn(move || work(self, receiver))?;
+
+        let sink = Email {
+            sender: sender.clone(),
+            level,
+        };
+        let guard = WorkerGuard::new(
+            Box::new(move || {
+                let _ = sender.send(Command::Shutdown);
+            }),
+            handle,
+        );
+        Ok((sink, guard))
+    }
+
+    /// Returns the message of the digest of `lines` and `more` records that aren't listed, with
+    /// its headers.
+    fn message(&self, lines: &[String], more: usize) -> String {
+        let count = lines.len() + more;
+        let subject = match &self.subject {
+            Some(subject) => subject.clone(),
+            None => crate::process::host_name().to_owned(),
+        };
+        let date = TimestampFormat::Custom("%a, %d %b %Y %H:%M:%S +0000".to_owned())
+            .format(SystemTime::now())
+            .unwrap_or_default();
+
+        let mut message = format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: {}: {} log record{}\r\nDate: {}\r\n\
+             MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 8bit\r\n\r\n",
+            self.from,
+            self.to
+                .iter()
+                .map(|to| format!("<{}>", to))
+                .collect::<Vec<_>>()
+                .join(", "),
+            subject.replace(['\r', '\n'], " "),
+            count,
+            if count == 1 { "" } else { "s" },
+            date,
+        );
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        if more > 0 {
+            text.push_str(&format!("... and {} more\n", more));
+        }
+        for line in text.lines() {
+            // A line starting with a dot is escaped, so that it doesn't end the data.
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message
+    }
+
+    /// Sends `message` to the recipients.
+    fn send(&self, message: &str) -> io::Result<()> {
+        let addr = self.server.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the address resolved to nothing",
+            )
+        })?;
+        let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut smtp = Smtp {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+
+        smtp.expect(220)?;
+        smtp.command(&format!("EHLO {}", crate::process::host_name()), 250)?;
+        smtp.command(&format!("MAIL FROM:<{}>", self.from), 250)?;
+        for to in &self.to {
+            smtp.command(&format!("RCPT TO:<{}>", to), 250)?;
+        }
+        smtp.command("DATA", 354)?;
+        smtp.writer.write_all(message.as_bytes())?;
+        smtp.command(".", 250)?;
+        // The message is accepted; the server may close the connection without answering.
+        let _ = smtp.command("QUIT", 221);
+        Ok(())
+    }
+}
+
+/// A connection to an SMTP server.
+struct Smtp {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Smtp {
+    /// Sends `command` and checks that the server answers with `code`.
+    fn command(&mut self, command: &str, code: u16) -> io::Result<()> {
+        self.writer.write_all(command.as_bytes())?;
+        self.writer.write_all(b"\r\n")?;
+        self.expect(code).map_err(|err| {
+            let verb = command.split([' ', ':']).next();
+            io::Error::new(
+                err.kind(),
+                format!("{} failed: {}", verb.unwrap_or_default(), err),
+            )
+        })
+    }
+
+    /// Reads a reply, which may span several lines, and checks that its code is `code`, or
+    /// another one of the same class.
+    fn expect(&mut self, code: u16) -> io::Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the SMTP server closed the connection",
+                ));
+            }
+            // The lines of a reply look like `250-first`, ..., `250 last`.
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+        let reply = line.trim_end();
+        match reply.get(..3).and_then(|reply| reply.parse::<u16>().ok()) {
+            // E.g. `251 User not local; will forward` is as good as `250`.
+            Some(reply) if reply / 100 == code / 100 => Ok(()),
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("the SMTP server replied `{}`", reply),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid SMTP reply `{}`", reply),
+            )),
+        }
+    }
+}
+
+fn work(builder: EmailBuilder, receiver: Receiver<Command>) {
+    let mut pending = Vec::new();
+    let mut more = 0;
+    // The time at which the pending digest is due.
+    let mut deadline: Option<Instant> = None;
+    loop {
+        let command = match deadline {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let stop = match command {
+            Ok(Command::Record(line)) => {
+                deadline.get_or_insert_with(|| Instant::now() + builder.interval);
+                if pending.len() < MAX_LISTED {
+                    pending.push(line);
+                } else {
+                    more += 1;
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        if pending.len() + more >= builder.threshold {
+            let message = builder.message(&pending, more);
+            if let Err(err) = builder.send(&message) {
+                report(io::Error::new(
+                    err.kind(),
+                    format!("failed to email a digest via {}: {}", builder.server, err),
+                ));
+            }
+        }
+        pending.clear();
+        more = 0;
+        deadline = None;
+        if stop {
+            break;
+        }
+    }
+}
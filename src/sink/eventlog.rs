@@ -0,0 +1,135 @@
+use std::ffi::{c_void, OsStr};
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+
+use log::{Level, LevelFilter};
+
+use crate::{Record, Sink};
+
+type Handle = *mut c_void;
+
+const EVENTLOG_ERROR_TYPE: u16 = 0x0001;
+const EVENTLOG_WARNING_TYPE: u16 = 0x0002;
+const EVENTLOG_INFORMATION_TYPE: u16 = 0x0004;
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn RegisterEventSourceW(server_name: *const u16, source_name: *const u16) -> Handle;
+    fn DeregisterEventSource(event_log: Handle) -> i32;
+    fn ReportEventW(
+        event_log: Handle,
+        event_type: u16,
+        category: u16,
+        event_id: u32,
+        user_sid: *mut c_void,
+        num_strings: u16,
+        data_size: u32,
+        strings: *const *const u16,
+        raw_data: *mut c_void,
+    ) -> i32;
+}
+
+/// A sink that reports records to the Windows Event Log, under an event source of the
+/// application log.
+///
+/// Errors are reported as error events, warnings as warning events, and, if the level allows it,
+/// all other records as information events. The event text is the plain message followed by the
+/// fields of the record. Without a registered message file for the source, the Event Viewer
+/// prefixes the text with a note that the event description could not be found; registering the
+/// source, e.g. with `New-EventLog -LogName Application -Source my-service`, requires
+/// administrator rights and is best done by the installer.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::EventLog;
+///
+/// # fn main() -> std::io::Result<()> {
+/// bunt_logger::with().add_sink(Box::new(EventLog::open("my-service")?));
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventLog {
+    handle: Handle,
+    level: LevelFilter,
+}
+
+// SAFETY: event log handles may be used from any thread.
+unsafe impl Send for EventLog {}
+
+impl EventLog {
+    /// Opens the event source `source`, reporting warnings and errors.
+    pub fn open(source: &str) -> io::Result<Self> {
+        let source = wide(source);
+        // SAFETY: `source` is a null-terminated UTF-16 string.
+        let handle = unsafe { RegisterEventSourceW(ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            handle,
+            level: LevelFilter::Warn,
+        })
+    }
+
+    /// Sets the most verbose level that is reported. Defaults to [`LevelFilter::Warn`].
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl Sink for EventLog {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if record.level() > self.level {
+            return Ok(());
+        }
+        let event_type = match record.level() {
+            Level::Error => EVENTLOG_ERROR_TYPE,
+            Level::Warn => EVENTLOG_WARNING_TYPE,
+            _ => EVENTLOG_INFORMATION_TYPE,
+        };
+
+        let mut text = record.message().to_string();
+        for field in record.fields() {
+            text.push_str(&format!(" {}={}", field.key(), field.value()));
+        }
+        let text = wide(&text);
+        let strings = [text.as_ptr()];
+
+        // SAFETY: `self.handle` is an open event source, and `strings` holds one null-terminated
+        // UTF-16 string that outlives the call.
+        let reported = unsafe {
+            ReportEventW(
+                self.handle,
+                event_type,
+                0,
+                0,
+                ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                ptr::null_mut(),
+            )
+        };
+        if reported == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        // SAFETY: `self.handle` is an open event source that is not used afterwards.
+        unsafe {
+            DeregisterEventSource(self.handle);
+        }
+    }
+}
+
+/// Encodes `s` as a null-terminated UTF-16 string.
+fn wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+}
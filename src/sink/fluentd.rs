@@ -0,0 +1,214 @@
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::net::ToSocketAddrs;
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::LevelFilter;
+
+use crate::writer::{Client, Connection};
+use crate::{Record, Sink, Value};
+
+/// A sink that sends records to Fluentd or Fluent Bit over TCP, in [Forward protocol] message
+/// mode.
+///
+/// Each record becomes a `[tag, time, record]` entry, with the time as a nanosecond-precision
+/// `EventTime` and the record as a map with the keys `level`, `target`, `message` and `thread`,
+/// then `kind`, `scope`, `file` and `line` if known, and `fields`, a map of the
+/// [fields](crate::Field) of the record, if any. Connections are remade after errors like those
+/// of [`NetWriter`](crate::NetWriter); records are dropped while waiting to reconnect.
+///
+/// [Forward protocol]: https://github.com/fluent/fluentd/wiki/Forward-Protocol-Specification-v1
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::Fluentd;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let fluentd = Fluentd::new("127.0.0.1:24224", "app.web")?;
+/// bunt_logger::with().add_sink(Box::new(fluentd));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Fluentd {
+    client: Client,
+    tag: String,
+    level: LevelFilter,
+}
+
+impl Fluentd {
+    /// Creates a sink that sends records tagged with `tag` to the forward input at `addr`. Fails
+    /// if the address can't be resolved.
+    #[inline]
+    pub fn new<A: ToSocketAddrs, T: Into<String>>(addr: A, tag: T) -> io::Result<Self> {
+        Ok(Self {
+            client: Client::new(addr, false)?,
+            tag: tag.into(),
+            level: LevelFilter::Trace,
+        })
+    }
+
+    /// Sets the most verbose level that is sent. Defaults to [`LevelFilter::Trace`], so that only
+    /// the filters of the preferences apply.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Writes `record` as a forward protocol entry.
+    fn encode(&self, record: &Record, out: &mut Vec<u8>) -> io::Result<()> {
+        // A fixed-size array of 3 elements.
+        out.write_all(&[0x93])?;
+        write_str(&self.tag, out)?;
+
+        // EventTime, extension type 0 with a fixed size of 8 bytes.
+        let time = record
+            .time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        out.write_all(&[0xd7, 0x00])?;
+        out.write_all(&(time.as_secs() as u32).to_be_bytes())?;
+        out.write_all(&time.subsec_nanos().to_be_bytes())?;
+
+        let kind = record
+            .kind()
+            .map(|kind| format!("{:?}", kind).to_lowercase());
+        let scope = record.scope_path();
+        let len = 4
+            + kind.is_some() as usize
+            + scope.is_some() as usize
+            + 2 * record.location().is_some() as usize
+            + !record.fields().is_empty() as usize;
+        write_map(len, out)?;
+        write_str("level", out)?;
+        write_str(record.level().as_str(), out)?;
+        write_str("target", out)?;
+        write_str(record.target(), out)?;
+        write_str("message", out)?;
+        write_str(&record.message().to_string(), out)?;
+        write_str("thread", out)?;
+        write_str(&record.thread_name(), out)?;
+        if let Some(kind) = kind {
+            write_str("kind", out)?;
+            write_str(&kind, out)?;
+        }
+        if let Some(scope) = scope {
+            write_str("scope", out)?;
+            write_str(&scope, out)?;
+        }
+        if let Some(location) = record.location() {
+            write_str("file", out)?;
+            write_str(location.file(), out)?;
+            write_str("line", out)?;
+            write_uint(location.line().into(), out)?;
+        }
+        if !record.fields().is_empty() {
+            write_str("fields", out)?;
+            write_map(record.fields().len(), out)?;
+            for field in record.fields() {
+                write_str(field.key(), out)?;
+                write_value(field.value(), out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sink for Fluentd {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let mut entry = Vec::new();
+        self.encode(record, &mut entry)?;
+        self.client
+            .send(|connection| match connection {
+                Connection::Tcp(stream) => stream.write_all(&entry),
+                Connection::Udp(socket) => socket.send(&entry).map(drop),
+            })
+            .map(drop)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.client.flush()
+    }
+}
+
+/// Writes the header of a MessagePack map of `len` pairs.
+fn write_map(len: usize, out: &mut Vec<u8>) -> io::Result<()> {
+    match u16::try_from(len) {
+        Ok(len) if len <= 15 => out.write_all(&[0x80 | len as u8]),
+        Ok(len) => {
+            out.write_all(&[0xde])?;
+            out.write_all(&len.to_be_bytes())
+        }
+        Err(_) => {
+            out.write_all(&[0xdf])?;
+            out.write_all(&(len as u32).to_be_bytes())
+        }
+    }
+}
+
+/// Writes `s` as a MessagePack string.
+fn write_str(s: &str, out: &mut Vec<u8>) -> io::Result<()> {
+    let len = s.len();
+    if len <= 31 {
+        out.write_all(&[0xa0 | len as u8])?;
+    } else if let Ok(len) = u8::try_from(len) {
+        out.write_all(&[0xd9, len])?;
+    } else if let Ok(len) = u16::try_from(len) {
+        out.write_all(&[0xda])?;
+        out.write_all(&len.to_be_bytes())?;
+    } else {
+        out.write_all(&[0xdb])?;
+        out.write_all(&(len as u32).to_be_bytes())?;
+    }
+    out.write_all(s.as_bytes())
+}
+
+/// Writes `v` as a MessagePack integer in its shortest form.
+fn write_uint(v: u64, out: &mut Vec<u8>) -> io::Result<()> {
+    if v <= 0x7f {
+        out.write_all(&[v as u8])
+    } else if let Ok(v) = u8::try_from(v) {
+        out.write_all(&[0xcc, v])
+    } else if let Ok(v) = u16::try_from(v) {
+        out.write_all(&[0xcd])?;
+        out.write_all(&v.to_be_bytes())
+    } else if let Ok(v) = u32::try_from(v) {
+        out.write_all(&[0xce])?;
+        out.write_all(&v.to_be_bytes())
+    } else {
+        out.write_all(&[0xcf])?;
+        out.write_all(&v.to_be_bytes())
+    }
+}
+
+/// Writes `v` as a MessagePack integer, in its shortest form if it is small.
+fn write_int(v: i64, out: &mut Vec<u8>) -> io::Result<()> {
+    match v {
+        0.. => write_uint(v as u64, out),
+        -32..=-1 => out.write_all(&[v as u8]),
+        _ => {
+            out.write_all(&[0xd3])?;
+            out.write_all(&v.to_be_bytes())
+        }
+    }
+}
+
+/// Writes `value` as a MessagePack value.
+fn write_value(value: &Value, out: &mut Vec<u8>) -> io::Result<()> {
+    match value {
+        Value::Str(s) | Value::Debug(s) => write_str(s, out),
+        Value::I64(v) => write_int(*v, out),
+        Value::U64(v) => write_uint(*v, out),
+        Value::F64(v) => {
+            out.write_all(&[0xcb])?;
+            out.write_all(&v.to_be_bytes())
+        }
+        Value::Bool(v) => out.write_all(&[if *v { 0xc3 } else { 0xc2 }]),
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::net::ToSocketAddrs;
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::{Level, LevelFilter};
+
+use crate::format::{write_json_str, write_json_value};
+use crate::writer::{Client, Connection};
+use crate::{Kind, Record, Sink};
+
+/// The largest payload of a UDP chunk, which keeps datagrams below typical LAN limits.
+const DEFAULT_CHUNK_SIZE: usize = 8154;
+/// The most chunks that a GELF message may be split into.
+const MAX_CHUNKS: usize = 128;
+/// The magic bytes that start a chunk.
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+
+/// A sink that sends records to Graylog or another GELF collector, see [`LogPrefs::gelf`].
+///
+/// Each record becomes a GELF 1.1 message whose `short_message` is the first line of the plain
+/// message and whose `full_message`, for multi-line messages, the whole message. Levels are mapped
+/// to syslog severities: errors to 3, warnings to 4, info to 6, and debug and trace records to 7;
+/// [fatal](Kind::Fatal) records to 2. The target, source location, thread name,
+/// [scope](crate::enter_scope) and [fields](crate::Field) of records are sent as additional
+/// fields, with invalid characters in keys replaced by `_`.
+///
+/// Over UDP, messages larger than the [chunk size](Self::chunk_size) are split into GELF chunks.
+/// Over TCP, messages are terminated by a null byte. Connections are remade after errors like
+/// those of [`NetWriter`](crate::NetWriter); records are dropped while waiting to reconnect.
+///
+/// [`LogPrefs::gelf`]: crate::LogPrefs::gelf
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::Gelf;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let gelf = Gelf::tcp("graylog.internal:12201")?.host("web-1");
+/// bunt_logger::with().add_sink(Box::new(gelf));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Gelf {
+    client: Client,
+    udp: bool,
+    host: String,
+    level: LevelFilter,
+    chunk_size: usize,
+    /// Makes the ids of chunked messages unpredictable across processes.
+    ids: RandomState,
+    count: u64,
+}
+
+impl Gelf {
+    /// Creates a sink that sends messages over UDP to `addr`, chunking large ones. Fails if the
+    /// address can't be resolved.
+    #[inline]
+    pub fn udp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(addr, true)
+    }
+
+    /// Creates a sink that sends messages over TCP to `addr`. Fails if the address can't be
+    /// resolved.
+    #[inline]
+    pub fn tcp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(addr, false)
+    }
+
+    fn new<A: ToSocketAddrs>(addr: A, udp: bool) -> io::Result<Self> {
+        Ok(Self {
+            client: Client::new(addr, udp)?,
+            udp,
+            host: crate::process::host_name().to_owned(),
+            level: LevelFilter::Trace,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            ids: RandomState::new(),
+            count: 0,
+        })
+    }
+
+    /// Sets the `host` of messages. Defaults to the host name from the `HOSTNAME` or
+    /// `COMPUTERNAME` environment variable, or `/etc/hostname`, and else to `localhost`.
+    #[inline]
+    pub fn host<T: Into<String>>(mut self, host: T) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    /// Sets the most verbose level that is sent. Defaults to [`LevelFilter::Trace`], so that only
+    /// the filters of the preferences apply.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the largest payload of a UDP chunk, in bytes. Defaults to 8154; 1420 is safer across
+    /// the internet.
+    #[inline]
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Returns a new id for the chunks of a message.
+    fn next_id(&mut self) -> [u8; 8] {
+        self.count += 1;
+        let mut hasher = self.ids.build_hasher();
+        hasher.write_u64(self.count);
+        hasher.finish().to_be_bytes()
+    }
+
+    /// Writes `record` as a GELF message.
+    fn encode(&self, record: &Record, out: &mut Vec<u8>) -> io::Result<()> {
+        let message = record.message().to_string();
+        let short = message.lines().next().unwrap_or_default();
+        write!(out, "{{\"version\":\"1.1\",\"host\":")?;
+        write_json_str(&self.host, out)?;
+        write!(out, ",\"short_message\":")?;
+        write_json_str(short, out)?;
+        if short.len() < message.trim_end().len() {
+            write!(out, ",\"full_message\":")?;
+            write_json_str(&message, out)?;
+        }
+        let time = record
+            .time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        write!(
+            out,
+            ",\"timestamp\":{}.{:03},\"level\":{}",
+            time.as_secs(),
+            time.subsec_millis(),
+            severity(record)
+        )?;
+
+        write!(out, ",\"_target\":")?;
+        write_json_str(record.target(), out)?;
+        if let Some(location) = record.location() {
+            write!(out, ",\"_file\":")?;
+            write_json_str(location.file(), out)?;
+            write!(out, ",\"_line\":{}", location.line())?;
+        }
+        write!(out, ",\"_thread\":")?;
+        write_json_str(&record.thread_name(), out)?;
+        if let Some(scope) = record.scope_path() {
+            write!(out, ",\"_scope\":")?;
+            write_json_str(&scope, out)?;
+        }
+        for field in record.fields() {
+            write!(out, ",")?;
+            write_json_str(&field_key(field.key()), out)?;
+            write!(out, ":")?;
+            write_json_value(field.value(), out)?;
+        }
+        write!(out, "}}")
+    }
+}
+
+impl Sink for Gelf {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let mut message = Vec::new();
+        self.encode(record, &mut message)?;
+        if !self.udp {
+            message.push(0);
+        }
+        if !self.udp || message.len() <= self.chunk_size {
+            return self
+                .client
+                .send(|connection| send(connection, &message))
+                .map(drop);
+        }
+
+        let count = message.chunks(self.chunk_size).len();
+        if count > MAX_CHUNKS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the record is too large for GELF over UDP",
+            ));
+        }
+        let id = self.next_id();
+        let chunk_size = self.chunk_size;
+        self.client
+            .send(|connection| {
+                let mut datagram = Vec::with_capacity(12 + chunk_size);
+                for (i, chunk) in message.chunks(chunk_size).enumerate() {
+                    datagram.clear();
+                    datagram.extend_from_slice(&CHUNK_MAGIC);
+                    datagram.extend_from_slice(&id);
+                    datagram.extend_from_slice(&[i as u8, count as u8]);
+                    datagram.extend_from_slice(chunk);
+                    send(connection, &datagram)?;
+                }
+                Ok(())
+            })
+            .map(drop)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.client.flush()
+    }
+}
+
+/// Sends `bytes` as one datagram or as part of the stream.
+#[inline]
+fn send(connection: &mut Connection, bytes: &[u8]) -> io::Result<()> {
+    match connection {
+        Connection::Tcp(stream) => stream.write_all(bytes),
+        Connection::Udp(socket) => socket.send(bytes).map(drop),
+    }
+}
+
+/// Returns the syslog severity of `record`.
+#[inline]
+fn severity(record: &Record) -> u8 {
+    match (record.kind(), record.level()) {
+        (Some(Kind::Fatal), _) => 2,
+        (_, Level::Error) => 3,
+        (_, Level::Warn) => 4,
+        (_, Level::Info) => 6,
+        (_, Level::Debug) | (_, Level::Trace) => 7,
+    }
+}
+
+/// Returns the name of the additional field for the field `key`: `key` prefixed with `_`, with
+/// characters other than letters, digits, `_`, `.` and `-` replaced by `_`. `_id` is reserved, so
+/// `id` becomes `_id_`.
+fn field_key(key: &str) -> String {
+    let mut name = String::with_capacity(key.len() + 1);
+    name.push('_');
+    name.extend(key.chars().map(|c| {
+        if c.is_ascii_alphanumeric() || "_.-".contains(c) {
+            c
+        } else {
+            '_'
+        }
+    }));
+    if name == "_id" {
+        name.push('_');
+    }
+    name
+}
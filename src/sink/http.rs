@@ -0,0 +1,705 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::LevelFilter;
+
+use super::batch::{self, Batcher, Failure, TIMEOUT};
+use crate::format::{write_json, write_json_str, JsonKeys};
+use crate::{Record, Sink, TimestampFormat, WorkerGuard};
+
+/// A record encoded as a JSON object, with its time in nanoseconds since the Unix epoch.
+struct Encoded {
+    nanos: u128,
+    json: String,
+}
+
+/// The shape of the body of the requests.
+#[derive(Debug, Clone)]
+enum Body {
+    /// A JSON array of the records.
+    Array,
+    /// A Loki push with a single stream with these labels.
+    Loki(Vec<(String, String)>),
+    /// An OTLP logs export with these resource attributes.
+    #[cfg(feature = "otel")]
+    Otlp(Vec<(String, String)>),
+}
+
+/// A sink that buffers records and posts them in batches to an HTTP endpoint, such as a custom
+/// collector or the Loki push API.
+///
+/// Records are encoded as JSON objects like those of [`Format::Json`](crate::Format::Json), and
+/// posted as a JSON array once [`batch_size`](HttpSinkBuilder::batch_size) of them are queued, or
+/// [`batch_interval`](HttpSinkBuilder::batch_interval) after the first of them was. With
+/// [`loki`](HttpSinkBuilder::loki), the body follows the Loki push API instead, and with `otlp`,
+/// behind the `otel` feature, OpenTelemetry's OTLP/HTTP. Batches are posted
+/// from a dedicated thread, so logging doesn't wait for the endpoint. Failed requests are retried
+/// with growing delays, unless the endpoint rejects the batch with a 4xx status other than 408 or
+/// 429; batches that fail for good are passed to the
+/// [error handler](crate::LogPrefs::on_error). If the endpoint falls far behind, new records are
+/// dropped.
+///
+/// The thread stops once the [`WorkerGuard`] returned alongside the sink is dropped, after posting
+/// everything that was queued. `https://` URLs need the `tls` feature, which verifies servers
+/// against the Mozilla root certificates; otherwise, only plain `http://` URLs are supported.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use bunt_logger::HttpSink;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (sink, _guard) = HttpSink::builder("http://127.0.0.1:3100/loki/api/v1/push")
+///     .loki(vec![("app", "web")])
+///     .batch_interval(Duration::from_secs(5))
+///     .spawn()?;
+/// bunt_logger::with().add_sink(Box::new(sink));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HttpSink {
+    batcher: Batcher<Encoded>,
+    level: LevelFilter,
+    /// Encodes a record as a JSON object.
+    encode: fn(&Record) -> io::Result<String>,
+}
+
+impl HttpSink {
+    /// Returns a builder for a sink that posts to `url`, e.g. `"http://collector:8080/logs"`.
+    #[inline]
+    pub fn builder<T: Into<String>>(url: T) -> HttpSinkBuilder {
+        HttpSinkBuilder {
+            url: url.into(),
+            batch_size: 100,
+            batch_interval: Duration::from_secs(1),
+            max_retries: 3,
+            headers: Vec::new(),
+            body: Body::Array,
+            level: LevelFilter::Trace,
+        }
+    }
+}
+
+impl Sink for HttpSink {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let encoded = Encoded {
+            nanos: record
+                .time()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos()),
+            json: (self.encode)(record)?,
+        };
+        self.batcher.push(encoded)
+    }
+
+    /// Posts the queued records, without waiting for the request.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.batcher.flush()
+    }
+}
+
+/// A builder for an [`HttpSink`].
+#[derive(Debug, Clone)]
+pub struct HttpSinkBuilder {
+    url: String,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: u32,
+    headers: Vec<(String, String)>,
+    body: Body,
+    level: LevelFilter,
+}
+
+impl HttpSinkBuilder {
+    /// Sets the number of records after which a batch is posted. Defaults to 100.
+    #[inline]
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Sets the longest time that a record waits before its batch is posted. Defaults to 1 second.
+    #[inline]
+    pub fn batch_interval(mut self, interval: Duration) -> Self {
+        self.batch_interval = interval;
+        self
+    }
+
+    /// Sets the number of times that a failed request is retried before the batch is dropped.
+    /// Defaults to 3.
+    #[inline]
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Adds a header to the requests, e.g. `("Authorization", "Bearer ...")`. The sink fails to
+    /// [spawn](Self::spawn) if the name is empty or contains a colon, or either contains a line
+    /// break.
+    #[inline]
+    pub fn header<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Posts batches in the format of the Loki push API, as a single stream with `labels`, whose
+    /// lines are the JSON objects of the records.
+    pub fn loki<I, K, V>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.body = Body::Loki(collect_pairs(labels));
+        self
+    }
+
+    /// Posts batches to an OpenTelemetry collector in the JSON encoding of OTLP/HTTP, with the
+    /// resource attributes `resource`, e.g. `("service.name", "web")`. The URL is that of the logs
+    /// endpoint, usually `http://<collector>:4318/v1/logs`. Requires the `otel` feature.
+    ///
+    /// Records become log records with their time, severity, message as the body, and target,
+    /// thread, source location and [fields](crate::Field) as attributes. Fields named `trace_id`
+    /// and `span_id`, holding the ids in hexadecimal, are sent as the trace context of the record
+    /// instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use bunt_logger::HttpSink;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let (sink, _guard) = HttpSink::builder("http://127.0.0.1:4318/v1/logs")
+    ///     .otlp(vec![("service.name", "web")])
+    ///     .spawn()?;
+    /// bunt_logger::with().add_sink(Box::new(sink));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "otel")]
+    pub fn otlp<I, K, V>(mut self, resource: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.body = Body::Otlp(collect_pairs(resource));
+        self
+    }
+
+    /// Sets the most verbose level that is sent. Defaults to [`LevelFilter::Trace`], so that only
+    /// the filters of the preferences apply.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Spawns the thread that posts the batches. Fails if the URL or a header is invalid, or the
+    /// host can't be resolved.
+    pub fn spawn(self) -> io::Result<(HttpSink, WorkerGuard)> {
+        let invalid = |name: &str, value: &str| {
+            name.is_empty() || name.contains([':', '\r', '\n']) || value.contains(['\r', '\n'])
+        };
+        if self
+            .headers
+            .iter()
+            .any(|(name, value)| invalid(name, value))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid HTTP header",
+            ));
+        }
+        let endpoint = Endpoint::parse(&self.url)?;
+        let level = self.level;
+        let encode = match self.body {
+            Body::Array | Body::Loki(_) => encode_json,
+            #[cfg(feature = "otel")]
+            Body::Otlp(_) => super::otel::encode,
+        };
+        let (batcher, guard) = Batcher::spawn(
+            "HTTP",
+            self.batch_size,
+            self.batch_interval,
+            move |batch: &mut Vec<Encoded>| {
+                let body = self.body(batch);
+                if let Err(err) = endpoint.post(&self.headers, &body, self.max_retries) {
+                    report(err);
+                }
+            },
+        )?;
+
+        let sink = HttpSink {
+            batcher,
+            level,
+            encode,
+        };
+        Ok((sink, guard))
+    }
+
+    /// Returns the body of the request for `batch`.
+    fn body(&self, batch: &[Encoded]) -> String {
+        let mut body = String::new();
+        match &self.body {
+            Body::Array => {
+                body.push('[');
+                for (i, record) in batch.iter().enumerate() {
+                    if i > 0 {
+                        body.push(',');
+                    }
+                    body.push_str(&record.json);
+                }
+                body.push(']');
+            }
+            Body::Loki(labels) => {
+                body.push_str("{\"streams\":[{\"stream\":{");
+                for (i, (key, value)) in labels.iter().enumerate() {
+                    if i > 0 {
+                        body.push(',');
+                    }
+                    push_json_str(key, &mut body);
+                    body.push(':');
+                    push_json_str(value, &mut body);
+                }
+                body.push_str("},\"values\":[");
+                for (i, record) in batch.iter().enumerate() {
+                    if i > 0 {
+                        body.push(',');
+                    }
+                    let _ = write!(body, "[\"{}\",", record.nanos);
+                    push_json_str(&record.json, &mut body);
+                    body.push(']');
+                }
+                body.push_str("]}]}");
+            }
+            #[cfg(feature = "otel")]
+            Body::Otlp(resource) => {
+                let records = batch.iter().map(|record| record.json.as_str());
+                super::otel::write_body(resource, records, &mut body);
+            }
+        }
+        body
+    }
+}
+
+/// Collects `pairs` into owned strings.
+fn collect_pairs<I, K, V>(pairs: I) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    pairs
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+/// Encodes `record` like [`Format::Json`](crate::Format::Json), with an RFC 3339 timestamp.
+pub(crate) fn encode_json(record: &Record) -> io::Result<String> {
+    let timestamp = TimestampFormat::Rfc3339.format(record.time());
+    let mut json = Vec::new();
+    let keys = JsonKeys {
+        location: true,
+        thread: true,
+        ..JsonKeys::default()
+    };
+    write_json(record, timestamp.as_deref(), keys, &mut json)?;
+    Ok(String::from_utf8(json).expect("JSON output is UTF-8"))
+}
+
+/// The parts of an `http://` or `https://` URL.
+#[derive(Debug)]
+pub(crate) struct Endpoint {
+    /// Whether the URL is `https://`.
+    tls: bool,
+    /// The host and port, as sent in the `Host` header.
+    authority: String,
+    path: String,
+    content_type: &'static str,
+}
+
+impl Endpoint {
+    /// Parses `url`, failing if it isn't an `http://` URL, or an `https://` URL with the `tls`
+    /// feature, or its host can't be resolved.
+    pub(crate) fn parse(url: &str) -> io::Result<Self> {
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: `{}`", message, url),
+            )
+        };
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            if !cfg!(feature = "tls") {
+                return Err(invalid("HTTPS needs the `tls` feature"));
+            }
+            (true, rest)
+        } else {
+            return Err(invalid("the URL must start with `http://` or `https://`"));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if authority.is_empty() {
+            return Err(invalid("the URL has no host"));
+        }
+        let endpoint = Self {
+            tls,
+            authority: authority.to_owned(),
+            path: path.to_owned(),
+            content_type: "application/json",
+        };
+        endpoint.addr()?;
+        Ok(endpoint)
+    }
+
+    /// Sets the `Content-Type` of the requests, `application/json` by default.
+    #[cfg(feature = "sentry")]
+    #[inline]
+    pub(crate) fn content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Returns the host of the URL, without the port or the brackets of an IPv6 address.
+    fn host(&self) -> &str {
+        let authority = &self.authority;
+        // An IPv6 address in brackets contains colons itself.
+        let host = match authority.rfind(']') {
+            Some(i) => &authority[..=i],
+            None => authority.split(':').next().unwrap_or_default(),
+        };
+        host.trim_start_matches('[').trim_end_matches(']')
+    }
+
+    /// Returns the address to connect to; port 80, or 443 for HTTPS, unless the URL names
+    /// another.
+    fn addr(&self) -> io::Result<SocketAddr> {
+        let authority = &self.authority;
+        let has_port = match authority.rfind(']') {
+            Some(i) => authority[i..].contains(':'),
+            None => authority.contains(':'),
+        };
+        let mut addrs = if has_port {
+            authority.to_socket_addrs()?
+        } else {
+            let port = if self.tls { 443 } else { 80 };
+            (self.host(), port).to_socket_addrs()?
+        };
+        addrs.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the address resolved to nothing",
+            )
+        })
+    }
+
+    /// Posts `body` with the extra `headers`, retrying up to `max_retries` times with growing
+    /// delays unless the endpoint rejects it.
+    pub(crate) fn post(
+        &self,
+        headers: &[(String, String)],
+        body: &str,
+        max_retries: u32,
+    ) -> io::Result<()> {
+        batch::retry(max_retries, || self.request(headers, body)).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("failed to post to {}{}: {}", self.authority, self.path, err),
+            )
+        })
+    }
+
+    /// Sends one request with `body` and checks the status of the response.
+    fn request(&self, headers: &[(String, String)], body: &str) -> Result<(), Failure> {
+        let send = || -> io::Result<u16> {
+            let stream = TcpStream::connect_timeout(&self.addr()?, TIMEOUT)?;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            #[cfg(feature = "tls")]
+            if self.tls {
+                let stream = super::tls::connect(self.host(), stream)?;
+                return self.exchange(stream, headers, body);
+            }
+            self.exchange(stream, headers, body)
+        };
+
+        let status = send().map_err(Failure::Retry)?;
+        // `io::Error::other` needs Rust 1.74.
+        #[allow(clippy::io_other_error)]
+        let err = || io::Error::new(io::ErrorKind::Other, format!("status {}", status));
+        match status {
+            200..=299 => Ok(()),
+            408 | 429 | 500..=599 => Err(Failure::Retry(err())),
+            _ => Err(Failure::Reject(err())),
+        }
+    }
+
+    /// Writes the request over `stream` and returns the status of the response.
+    fn exchange<S: Read + Write>(
+        &self,
+        mut stream: S,
+        headers: &[(String, String)],
+        body: &str,
+    ) -> io::Result<u16> {
+        let mut head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\n\
+             Content-Length: {}\r\nConnection: close\r\n",
+            self.path,
+            self.authority,
+            self.content_type,
+            body.len()
+        );
+        for (name, value) in headers {
+            let _ = write!(head, "{}: {}\r\n", name, value);
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()?;
+
+        read_status(&mut BufReader::new(stream))
+    }
+}
+
+/// Reads the status of the final response from `reader`, skipping interim 1xx responses such as
+/// `100 Continue` along with their headers.
+fn read_status<R: BufRead>(reader: &mut R) -> io::Result<u16> {
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        // The status line looks like `HTTP/1.1 204 No Content`.
+        let status: u16 = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .filter(|_| line.starts_with("HTTP/"))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid status line `{}`", line.trim_end()),
+                )
+            })?;
+        if !(100..200).contains(&status) {
+            return Ok(status);
+        }
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if line.trim_end().is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+/// Passes `err` to the error handler of the global preferences.
+#[inline]
+pub(crate) fn report(err: io::Error) {
+    let on_error = crate::read().on_error;
+    on_error(err);
+}
+
+/// Appends `s` to `out` as a quoted and escaped JSON string.
+#[inline]
+pub(crate) fn push_json_str(s: &str, out: &mut String) {
+    let mut bytes = Vec::new();
+    let _ = write_json_str(s, &mut bytes);
+    out.push_str(&String::from_utf8(bytes).expect("JSON output is UTF-8"));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    /// A stream that reads a canned response and records the request.
+    struct Exchange {
+        response: Cursor<&'static [u8]>,
+        request: Vec<u8>,
+    }
+
+    impl Read for Exchange {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.response.read(buf)
+        }
+    }
+
+    impl Write for Exchange {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.request.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn status(response: &'static str) -> io::Result<u16> {
+        read_status(&mut Cursor::new(response.as_bytes()))
+    }
+
+    #[test]
+    fn parse_urls() {
+        let endpoint = Endpoint::parse("http://127.0.0.1:8080/loki/api/v1/push").unwrap();
+        assert!(!endpoint.tls);
+        assert_eq!(endpoint.authority, "127.0.0.1:8080");
+        assert_eq!(endpoint.path, "/loki/api/v1/push");
+        assert_eq!(endpoint.addr().unwrap(), "127.0.0.1:8080".parse().unwrap());
+
+        let endpoint = Endpoint::parse("http://127.0.0.1").unwrap();
+        assert_eq!(endpoint.path, "/");
+        assert_eq!(endpoint.addr().unwrap(), "127.0.0.1:80".parse().unwrap());
+
+        let endpoint = Endpoint::parse("http://[::1]:9000/logs?tenant=a").unwrap();
+        assert_eq!(endpoint.host(), "::1");
+        assert_eq!(endpoint.path, "/logs?tenant=a");
+        assert_eq!(endpoint.addr().unwrap(), "[::1]:9000".parse().unwrap());
+        let endpoint = Endpoint::parse("http://[::1]/").unwrap();
+        assert_eq!(endpoint.addr().unwrap(), "[::1]:80".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_urls() {
+        for url in [
+            "",
+            "127.0.0.1",
+            "ftp://127.0.0.1/",
+            "http://",
+            "http:///logs",
+        ] {
+            let err = Endpoint::parse(url).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", url);
+        }
+        let https = Endpoint::parse("https://127.0.0.1/");
+        assert_eq!(https.is_ok(), cfg!(feature = "tls"));
+        if let Ok(endpoint) = https {
+            assert_eq!(endpoint.addr().unwrap(), "127.0.0.1:443".parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn read_status_lines() {
+        assert_eq!(status("HTTP/1.1 204 No Content\r\n\r\n").unwrap(), 204);
+        assert_eq!(status("HTTP/1.0 500\r\n").unwrap(), 500);
+        assert_eq!(status("HTTP/2 429 Too Many Requests\n").unwrap(), 429);
+
+        for response in [
+            "",
+            "\r\n",
+            "HTTP/1.1\r\n",
+            "HTTP/1.1 OK 200\r\n",
+            "SMTP 200\r\n",
+        ] {
+            let err = status(response).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn skip_interim_responses() {
+        let response = "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\r\n";
+        assert_eq!(status(response).unwrap(), 200);
+        let response = "HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+                        HTTP/1.1 100 Continue\r\n\r\n\
+                        HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(status(response).unwrap(), 202);
+
+        let err = status("HTTP/1.1 100 Continue\r\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn exchange_requests() {
+        let endpoint = Endpoint::parse("http://127.0.0.1:3100/push").unwrap();
+        let responses: [&'static [u8]; 2] = [
+            b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\n{\"ok\":true}\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+        ];
+        for response in responses {
+            let mut exchange = Exchange {
+                response: Cursor::new(response),
+                request: Vec::new(),
+            };
+            let headers = [("Authorization".to_owned(), "Bearer token".to_owned())];
+            let status = endpoint.exchange(&mut exchange, &headers, "[]").unwrap();
+            assert_eq!(status, 200);
+            assert_eq!(
+                String::from_utf8(exchange.request).unwrap(),
+                "POST /push HTTP/1.1\r\nHost: 127.0.0.1:3100\r\n\
+                 Content-Type: application/json\r\nContent-Length: 2\r\n\
+                 Connection: close\r\nAuthorization: Bearer token\r\n\r\n[]"
+            );
+        }
+    }
+
+    #[test]
+    fn classify_statuses() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let responses = [
+            "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n",
+            "HTTP/1.1 400 Bad Request\r\nContent-Length: 3\r\n\r\nbad",
+            "HTTP/1.1 503 Service Unavailable\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+        ];
+        let server = thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                // Reads the head of the request, which ends with an empty line, then its body.
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while line != "\r\n" {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                }
+                let mut body = [0; 2];
+                reader.read_exact(&mut body).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let endpoint = Endpoint::parse(&url).unwrap();
+        assert!(endpoint.request(&[], "[]").is_ok());
+        assert!(matches!(
+            endpoint.request(&[], "[]"),
+            Err(Failure::Reject(_))
+        ));
+        assert!(matches!(
+            endpoint.request(&[], "[]"),
+            Err(Failure::Retry(_))
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn reject_invalid_headers() {
+        let headers = [
+            ("", "value"),
+            ("X-Name:", "value"),
+            ("X-Name", "value\r\nX-Injected: yes"),
+            ("X-Name\n", "value"),
+        ];
+        for (name, value) in headers {
+            let builder = HttpSink::builder("http://127.0.0.1/").header(name, value);
+            let err = builder.spawn().map(drop).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{:?}", name);
+        }
+    }
+}
@@ -0,0 +1,715 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::LevelFilter;
+
+use super::batch::{self, Batcher, Failure, TIMEOUT};
+use super::http::{encode_json, report};
+use crate::{Record, Sink, WorkerGuard};
+
+/// The largest response that is read, well above those of the requests sent here.
+const MAX_RESPONSE: usize = 16 << 20;
+
+/// The API key and version of the requests.
+const PRODUCE: (i16, i16) = (0, 3);
+const METADATA: (i16, i16) = (3, 4);
+
+/// The CRC-32C table of record batches.
+const CRC32C: [u32; 256] = crc32c_table();
+
+/// A function that is handed the records of a batch that couldn't be delivered.
+type OnFailure = dyn Fn(io::Error, Vec<String>) + Send + Sync;
+
+/// A record encoded as a JSON object, with its time in milliseconds since the Unix epoch.
+struct Encoded {
+    millis: i64,
+    json: String,
+}
+
+/// A sink that publishes records to a Kafka topic. Requires the `kafka` feature.
+///
+/// Records are encoded as JSON objects like those of [`Format::Json`](crate::Format::Json), and
+/// produced to one partition of the topic, as a batch once
+/// [`batch_size`](KafkaBuilder::batch_size) of them are queued, or
+/// [`batch_interval`](KafkaBuilder::batch_interval) after the first of them was. Batches are sent
+/// from a dedicated thread, so logging doesn't wait for the brokers. The leader of the partition
+/// is looked up through the bootstrap brokers, and again whenever producing fails; failed batches
+/// are retried with growing delays, and those that fail for good are handed to the
+/// [failure callback](KafkaBuilder::on_failure). If the brokers fall far behind, new records are
+/// dropped.
+///
+/// The thread stops once the [`WorkerGuard`] returned alongside the sink is dropped, after sending
+/// everything that was queued. Only plaintext listeners are supported, without TLS or SASL, and
+/// batches are sent uncompressed.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::Kafka;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (kafka, _guard) = Kafka::builder("kafka1:9092,kafka2:9092", "logs")
+///     .on_failure(|err, records| eprintln!("lost {} records: {}", records.len(), err))
+///     .spawn()?;
+/// bunt_logger::with().add_sink(Box::new(kafka));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Kafka {
+    batcher: Batcher<Encoded>,
+    level: LevelFilter,
+}
+
+impl Kafka {
+    /// Returns a builder for a sink that publishes to `topic`, through the comma-separated
+    /// `host:port` pairs of the bootstrap brokers, e.g. `"kafka1:9092,kafka2:9092"`.
+    #[inline]
+    pub fn builder<B: AsRef<str>, T: Into<String>>(brokers: B, topic: T) -> KafkaBuilder {
+        KafkaBuilder {
+            brokers: brokers
+                .as_ref()
+                .split(',')
+                .map(str::trim)
+                .filter(|broker| !broker.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            topic: topic.into(),
+            partition: 0,
+            client_id: "bunt-logger".to_owned(),
+            batch_size: 100,
+            batch_interval: Duration::from_secs(1),
+            max_retries: 3,
+            level: LevelFilter::Trace,
+            on_failure: None,
+        }
+    }
+}
+
+impl Sink for Kafka {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let encoded = Encoded {
+            millis: record
+                .time()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as i64),
+            json: encode_json(record)?,
+        };
+        self.batcher.push(encoded)
+    }
+
+    /// Sends the queued records, without waiting for the brokers.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.batcher.flush()
+    }
+}
+
+/// A builder for a [`Kafka`] sink.
+#[derive(Clone)]
+pub struct KafkaBuilder {
+    brokers: Vec<String>,
+    topic: String,
+    partition: i32,
+    client_id: String,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: u32,
+    level: LevelFilter,
+    on_failure: Option<Arc<OnFailure>>,
+}
+
+impl KafkaBuilder {
+    /// Sets the partition that records are produced to. Defaults to 0.
+    #[inline]
+    pub fn partition(mut self, partition: i32) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    /// Sets the client id sent to the brokers. Defaults to `bunt-logger`.
+    #[inline]
+    pub fn client_id<T: Into<String>>(mut self, id: T) -> Self {
+        self.client_id = id.into();
+        self
+    }
+
+    /// Sets the number of records after which a batch is sent. Defaults to 100.
+    #[inline]
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Sets the longest time that a record waits before its batch is sent. Defaults to 1 second.
+    #[inline]
+    pub fn batch_interval(mut self, interval: Duration) -> Self {
+        self.batch_interval = interval;
+        self
+    }
+
+    /// Sets the number of times that a failed batch is retried before it is given up. Defaults
+    /// to 3.
+    #[inline]
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Sets the most verbose level that is sent. Defaults to [`LevelFilter::Trace`], so that only
+    /// the filters of the preferences apply.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets a function that is called on the thread of the sink with the error and the JSON
+    /// records of every batch that couldn't be delivered, e.g. to spool them to a file. By
+    /// default, the error is passed to the [error handler](crate::LogPrefs::on_error).
+    #[inline]
+    pub fn on_failure<F>(mut self, on_failure: F) -> Self
+    where
+        F: Fn(io::Error, Vec<String>) + Send + Sync + 'static,
+    {
+        self.on_failure = Some(Arc::new(on_failure));
+        self
+    }
+
+    /// Spawns the thread that sends the batches. Fails if no broker is given or the topic is
+    /// empty; the brokers themselves are only contacted once there is something to send.
+    pub fn spawn(self) -> io::Result<(Kafka, WorkerGuard)> {
+        if self.brokers.is_empty() || self.topic.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the Kafka sink needs a broker and a topic",
+            ));
+        }
+        let level = self.level;
+        let (size, interval) = (self.batch_size, self.batch_interval);
+        let mut producer = Producer {
+            options: self,
+            leader: None,
+            correlation_id: 0,
+        };
+        let (batcher, guard) =
+            Batcher::spawn("Kafka", size, interval, move |batch: &mut Vec<Encoded>| {
+                if let Err(err) = producer.send(batch) {
+                    match &producer.options.on_failure {
+                        Some(on_failure) => {
+                            on_failure(err, batch.drain(..).map(|record| record.json).collect())
+                        }
+                        None => report(err),
+                    }
+                }
+            })?;
+
+        Ok((Kafka { batcher, level }, guard))
+    }
+}
+
+impl fmt::Debug for KafkaBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KafkaBuilder")
+            .field("brokers", &self.brokers)
+            .field("topic", &self.topic)
+            .field("partition", &self.partition)
+            .field("client_id", &self.client_id)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The connection to the leader of the partition, owned by the thread of the sink.
+struct Producer {
+    options: KafkaBuilder,
+    /// The leader, if it has been looked up and the last request to it succeeded.
+    leader: Option<TcpStream>,
+    correlation_id: i32,
+}
+
+impl Producer {
+    /// Produces `batch`, retrying up to `max_retries` times with growing delays unless the broker
+    /// rejects it.
+    fn send(&mut self, batch: &[Encoded]) -> io::Result<()> {
+        let records = record_batch(batch);
+        let result = batch::retry(self.options.max_retries, || {
+            let result = self.produce(&records);
+            if result.is_err() {
+                // The leader may have moved, or the connection broke.
+                self.leader = None;
+            }
+            result
+        });
+        result.map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "failed to produce {} records to {}/{}: {}",
+                    batch.len(),
+                    self.options.topic,
+                    self.options.partition,
+                    err
+                ),
+            )
+        })
+    }
+
+    /// Sends one produce request with `records` to the leader, connecting to it first if needed.
+    fn produce(&mut self, records: &[u8]) -> Result<(), Failure> {
+        let mut stream = match self.leader.take() {
+            Some(stream) => stream,
+            None => self.connect_leader().map_err(Failure::Retry)?,
+        };
+        let mut body = Vec::new();
+        // There is no transactional id, and only the leader has to acknowledge the batch.
+        put_i16(&mut body, -1);
+        put_i16(&mut body, 1);
+        put_i32(&mut body, TIMEOUT.as_millis() as i32);
+        put_i32(&mut body, 1);
+        put_str(&mut body, &self.options.topic);
+        put_i32(&mut body, 1);
+        put_i32(&mut body, self.options.partition);
+        put_i32(&mut body, records.len() as i32);
+        body.extend_from_slice(records);
+
+        let response = self
+            .call(&mut stream, PRODUCE, &body)
+            .map_err(Failure::Retry)?;
+        let code = produce_error(&response, self.options.partition).map_err(Failure::Retry)?;
+        self.leader = Some(stream);
+        match code {
+            0 => Ok(()),
+            code if is_retriable(code) => Err(Failure::Retry(kafka_error(code))),
+            code => Err(Failure::Reject(kafka_error(code))),
+        }
+    }
+
+    /// Looks up the leader of the partition through the first bootstrap broker that answers, and
+    /// connects to it.
+    fn connect_leader(&mut self) -> io::Result<TcpStream> {
+        let mut last_err = None;
+        for i in 0..self.options.brokers.len() {
+            let broker = self.options.brokers[i].clone();
+            let leader = connect(&broker).and_then(|mut stream| self.find_leader(&mut stream));
+            match leader.and_then(|leader| connect(&leader)) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.expect("the builder checks that there is a broker"))
+    }
+
+    /// Asks the broker of `stream` for the `host:port` of the leader of the partition.
+    fn find_leader(&mut self, stream: &mut TcpStream) -> io::Result<String> {
+        let mut body = Vec::new();
+        put_i32(&mut body, 1);
+        put_str(&mut body, &self.options.topic);
+        // Let the broker create the topic, if it is configured to.
+        body.push(1);
+        let response = self.call(stream, METADATA, &body)?;
+
+        let mut reader = Reader(&response);
+        reader.take(4)?; // Throttle time
+        let mut brokers = Vec::new();
+        for _ in 0..reader.len()? {
+            let id = reader.i32()?;
+            let host = reader.str()?.unwrap_or_default();
+            let port = reader.i32()?;
+            reader.str()?; // Rack
+            brokers.push((id, format!("{}:{}", host, port)));
+        }
+        reader.str()?; // Cluster id
+        reader.take(4)?; // Controller id
+        for _ in 0..reader.len()? {
+            let topic_code = reader.i16()?;
+            let name = reader.str()?;
+            reader.take(1)?; // Whether the topic is internal
+            let mut leader = None;
+            for _ in 0..reader.len()? {
+                let code = reader.i16()?;
+                let partition = reader.i32()?;
+                let id = reader.i32()?;
+                if partition == self.options.partition {
+                    leader = Some((code, id));
+                }
+                for _ in 0..2 {
+                    // The replicas and in-sync replicas.
+                    let len = reader.len()?;
+                    reader.take(4 * len)?;
+                }
+            }
+            if name != Some(self.options.topic.as_str()) {
+                continue;
+            }
+            return match leader {
+                _ if topic_code != 0 => Err(kafka_error(topic_code)),
+                Some((0, id)) => brokers
+                    .into_iter()
+                    .find(|&(broker, _)| broker == id)
+                    .map(|(_, addr)| addr)
+                    .ok_or_else(|| kafka_error(5)),
+                Some((code, _)) => Err(kafka_error(code)),
+                None => Err(kafka_error(3)),
+            };
+        }
+        Err(kafka_error(3))
+    }
+
+    /// Sends a request with `body` and returns the body of the response, after its correlation
+    /// id.
+    fn call(
+        &mut self,
+        stream: &mut TcpStream,
+        api: (i16, i16),
+        body: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        self.correlation_id = self.correlation_id.wrapping_add(1);
+        let mut request = Vec::with_capacity(body.len() + 64);
+        put_i32(&mut request, 0);
+        put_i16(&mut request, api.0);
+        put_i16(&mut request, api.1);
+        put_i32(&mut request, self.correlation_id);
+        put_str(&mut request, &self.options.client_id);
+        request.extend_from_slice(body);
+        let len = (request.len() - 4) as i32;
+        request[..4].copy_from_slice(&len.to_be_bytes());
+        stream.write_all(&request)?;
+
+        let mut len = [0; 4];
+        stream.read_exact(&mut len)?;
+        let len = usize::try_from(i32::from_be_bytes(len)).unwrap_or(0);
+        if !(4..=MAX_RESPONSE).contains(&len) {
+            return Err(invalid_response());
+        }
+        let mut response = vec![0; len];
+        stream.read_exact(&mut response)?;
+        if response[..4] != self.correlation_id.to_be_bytes() {
+            return Err(invalid_response());
+        }
+        response.drain(..4);
+        Ok(response)
+    }
+}
+
+/// Connects to the `host:port` of a broker.
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, TIMEOUT) {
+            Ok(stream) => {
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                // The broker may wait up to the timeout of the request before answering.
+                stream.set_read_timeout(Some(TIMEOUT * 2))?;
+                stream.set_nodelay(true)?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} resolved to nothing", addr),
+        )
+    }))
+}
+
+/// Encodes `batch` as an uncompressed record batch, in the format of Kafka 0.11 and later.
+fn record_batch(batch: &[Encoded]) -> Vec<u8> {
+    let first = batch.first().map_or(0, |record| record.millis);
+    let last = batch.iter().map(|record| record.millis).max().unwrap_or(0);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    for (i, encoded) in batch.iter().enumerate() {
+        record.clear();
+        record.push(0); // Attributes
+        put_varint(&mut record, encoded.millis - first);
+        put_varint(&mut record, i as i64);
+        put_varint(&mut record, -1); // No key
+        put_varint(&mut record, encoded.json.len() as i64);
+        record.extend_from_slice(encoded.json.as_bytes());
+        put_varint(&mut record, 0); // No headers
+        put_varint(&mut records, record.len() as i64);
+        records.extend_from_slice(&record);
+    }
+
+    // The part of the batch that the checksum covers.
+    let mut checked = Vec::with_capacity(records.len() + 40);
+    put_i16(&mut checked, 0); // Attributes: uncompressed, with the times of creation
+    put_i32(&mut checked, batch.len() as i32 - 1);
+    put_i64(&mut checked, first);
+    put_i64(&mut checked, last);
+    // Not idempotent, so no producer id, epoch or sequence.
+    put_i64(&mut checked, -1);
+    put_i16(&mut checked, -1);
+    put_i32(&mut checked, -1);
+    put_i32(&mut checked, batch.len() as i32);
+    checked.extend_from_slice(&records);
+
+    let mut out = Vec::with_capacity(checked.len() + 21);
+    put_i64(&mut out, 0); // Base offset
+    put_i32(&mut out, checked.len() as i32 + 9); // Length, from the leader epoch on
+    put_i32(&mut out, -1); // Leader epoch
+    out.push(2); // Magic byte
+    out.extend_from_slice(&crc32c(&checked).to_be_bytes());
+    out.extend_from_slice(&checked);
+    out
+}
+
+/// Returns the error code of `partition` in the body of a produce response.
+fn produce_error(response: &[u8], partition: i32) -> io::Result<i16> {
+    let mut reader = Reader(response);
+    for _ in 0..reader.len()? {
+        reader.str()?; // Topic
+        for _ in 0..reader.len()? {
+            let index = reader.i32()?;
+            let code = reader.i16()?;
+            reader.take(16)?; // Base offset and log append time
+            if index == partition {
+                return Ok(code);
+            }
+        }
+    }
+    Err(invalid_response())
+}
+
+/// Whether the error `code` may go away with a retry, such as when the leader moved.
+#[inline]
+fn is_retriable(code: i16) -> bool {
+    matches!(code, 3 | 5 | 6 | 7 | 13 | 14 | 15 | 19 | 20)
+}
+
+/// Returns an error for the Kafka error `code`.
+fn kafka_error(code: i16) -> io::Error {
+    let (kind, message) = match code {
+        3 => (io::ErrorKind::NotFound, "unknown topic or partition"),
+        5 => (io::ErrorKind::NotConnected, "leader not available"),
+        6 => (
+            io::ErrorKind::NotConnected,
+            "not the leader of the partition",
+        ),
+        7 => (io::ErrorKind::TimedOut, "request timed out"),
+        10 => (io::ErrorKind::InvalidInput, "message too large"),
+        19 | 20 => (io::ErrorKind::Other, "not enough replicas"),
+        29 => (
+            io::ErrorKind::PermissionDenied,
+            "topic authorization failed",
+        ),
+        _ => (io::ErrorKind::Other, "broker error"),
+    };
+    io::Error::new(kind, format!("Kafka: {} (error code {})", message, code))
+}
+
+#[inline]
+fn invalid_response() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid response from Kafka")
+}
+
+/// Reads the fields of a response.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid_response());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    #[inline]
+    fn i16(&mut self) -> io::Result<i16> {
+        let bytes = self.take(2)?;
+        Ok(i16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    #[inline]
+    fn i32(&mut self) -> io::Result<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads the length of an array, where a null array is empty.
+    #[inline]
+    fn len(&mut self) -> io::Result<usize> {
+        Ok(usize::try_from(self.i32()?).unwrap_or(0))
+    }
+
+    /// Reads a string, which may be null.
+    fn str(&mut self) -> io::Result<Option<&'a str>> {
+        let len = match usize::try_from(self.i16()?) {
+            Ok(len) => len,
+            Err(_) => return Ok(None),
+        };
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| invalid_response())
+    }
+}
+
+#[inline]
+fn put_i16(out: &mut Vec<u8>, n: i16) {
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+#[inline]
+fn put_i32(out: &mut Vec<u8>, n: i32) {
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+#[inline]
+fn put_i64(out: &mut Vec<u8>, n: i64) {
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+#[inline]
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    put_i16(out, s.len() as i16);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Writes `n` as a zigzag-encoded variable-length integer.
+fn put_varint(out: &mut Vec<u8>, n: i64) {
+    let mut n = ((n << 1) ^ (n >> 63)) as u64;
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC32C[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(n: i64) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_varint(&mut out, n);
+        out
+    }
+
+    #[test]
+    fn zigzag_varints() {
+        assert_eq!(varint(0), [0x00]);
+        assert_eq!(varint(-1), [0x01]);
+        assert_eq!(varint(1), [0x02]);
+        assert_eq!(varint(-2), [0x03]);
+        assert_eq!(varint(63), [0x7e]);
+        assert_eq!(varint(-64), [0x7f]);
+        assert_eq!(varint(64), [0x80, 0x01]);
+        assert_eq!(varint(-65), [0x81, 0x01]);
+        assert_eq!(varint(150), [0xac, 0x02]);
+        assert_eq!(varint(-150), [0xab, 0x02]);
+        assert_eq!(
+            varint(i64::MAX),
+            [&[0xfe][..], &[0xff; 8], &[0x01]].concat()
+        );
+        assert_eq!(varint(i64::MIN), [&[0xff; 9][..], &[0x01]].concat());
+    }
+
+    #[test]
+    fn crc32c_vectors() {
+        // The test vectors of RFC 3720, appendix B.4.
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(&[0; 32]), 0x8a91_36aa);
+        assert_eq!(crc32c(&[0xff; 32]), 0x62a8_ab43);
+        let ascending: Vec<u8> = (0..32).collect();
+        assert_eq!(crc32c(&ascending), 0x46dd_794e);
+        let descending: Vec<u8> = (0..32).rev().collect();
+        assert_eq!(crc32c(&descending), 0x113f_db5c);
+    }
+
+    #[test]
+    fn encode_record_batch() {
+        let batch = [
+            Encoded {
+                millis: 1000,
+                json: "{}".to_owned(),
+            },
+            Encoded {
+                millis: 1005,
+                json: "ab".to_owned(),
+            },
+        ];
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0, 0, 0, 0, 0, 0, 0, 0, // Base offset
+            0, 0, 0, 67, // Length
+            0xff, 0xff, 0xff, 0xff, // Leader epoch
+            2, // Magic byte
+            0x29, 0x0f, 0x49, 0xea, // CRC-32C
+            0, 0, // Attributes
+            0, 0, 0, 1, // Last offset delta
+            0, 0, 0, 0, 0, 0, 0x03, 0xe8, // First timestamp
+            0, 0, 0, 0, 0, 0, 0x03, 0xed, // Max timestamp
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Producer id
+            0xff, 0xff, // Producer epoch
+            0xff, 0xff, 0xff, 0xff, // Base sequence
+            0, 0, 0, 2, // Records
+            // Length, attributes, timestamp and offset deltas, null key, value and no headers.
+            0x10, 0, 0x00, 0x00, 0x01, 0x04, b'{', b'}', 0,
+            0x10, 0, 0x0a, 0x02, 0x01, 0x04, b'a', b'b', 0,
+        ];
+        assert_eq!(record_batch(&batch), expected);
+    }
+
+    #[test]
+    fn read_produce_errors() {
+        let mut response = Vec::new();
+        put_i32(&mut response, 1); // Topics
+        put_str(&mut response, "logs");
+        put_i32(&mut response, 2); // Partitions
+        for (partition, code) in [(0, 0), (3, 6)] {
+            put_i32(&mut response, partition);
+            put_i16(&mut response, code);
+            put_i64(&mut response, 42); // Base offset
+            put_i64(&mut response, -1); // Log append time
+        }
+        assert_eq!(produce_error(&response, 0).unwrap(), 0);
+        assert_eq!(produce_error(&response, 3).unwrap(), 6);
+        assert!(produce_error(&response, 1).is_err());
+        assert!(produce_error(&response[..response.len() - 1], 3).is_err());
+        assert!(is_retriable(6));
+        assert!(!is_retriable(10));
+    }
+}
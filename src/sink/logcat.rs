@@ -0,0 +1,106 @@
+use std::ffi::CString;
+use std::io;
+use std::os::raw::{c_char, c_int};
+
+use log::{Level, LevelFilter};
+
+use crate::{Kind, Record, Sink};
+
+const ANDROID_LOG_VERBOSE: c_int = 2;
+const ANDROID_LOG_DEBUG: c_int = 3;
+const ANDROID_LOG_INFO: c_int = 4;
+const ANDROID_LOG_WARN: c_int = 5;
+const ANDROID_LOG_ERROR: c_int = 6;
+const ANDROID_LOG_FATAL: c_int = 7;
+
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_write(priority: c_int, tag: *const c_char, text: *const c_char) -> c_int;
+}
+
+/// A sink that writes records to the Android log, so that Rust libraries in Android apps show up
+/// in `logcat` next to the logs of the app. Requires the `android` feature, and is only available
+/// when targeting Android.
+///
+/// Trace records are written as verbose, [fatal](crate::fatal!) ones as fatal, and the others at
+/// the priority of their level. The tag is the target of the record unless a fixed
+/// [`tag`](Self::tag) is set, and the text is the plain message followed by the fields of the
+/// record.
+///
+/// # Example
+/// ```rust,ignore
+/// use bunt_logger::Logcat;
+///
+/// bunt_logger::with().add_sink(Box::new(Logcat::new().tag("my-app")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Logcat {
+    tag: Option<CString>,
+    level: Option<LevelFilter>,
+}
+
+impl Logcat {
+    /// Returns a sink that tags records with their target.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tags all records with `tag`, such as the name of the app, instead of their target.
+    #[inline]
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = Some(c_string(tag));
+        self
+    }
+
+    /// Sets the most verbose level that is written. By default, only the filters of the
+    /// preferences apply.
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = Some(level);
+        self
+    }
+}
+
+impl Sink for Logcat {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if matches!(self.level, Some(level) if level < record.level()) {
+            return Ok(());
+        }
+        let priority = match (record.kind(), record.level()) {
+            (Some(Kind::Fatal), _) => ANDROID_LOG_FATAL,
+            (_, Level::Error) => ANDROID_LOG_ERROR,
+            (_, Level::Warn) => ANDROID_LOG_WARN,
+            (_, Level::Info) => ANDROID_LOG_INFO,
+            (_, Level::Debug) => ANDROID_LOG_DEBUG,
+            (_, Level::Trace) => ANDROID_LOG_VERBOSE,
+        };
+
+        let mut text = record.message().to_string();
+        for field in record.fields() {
+            text.push_str(&format!(" {}={}", field.key(), field.value()));
+        }
+        let text = c_string(&text);
+        let target;
+        let tag = match &self.tag {
+            Some(tag) => tag,
+            None => {
+                target = c_string(record.target());
+                &target
+            }
+        };
+
+        // SAFETY: `tag` and `text` are null-terminated strings that outlive the call.
+        let written = unsafe { __android_log_write(priority, tag.as_ptr(), text.as_ptr()) };
+        if written < 0 {
+            return Err(io::Error::from_raw_os_error(-written));
+        }
+        Ok(())
+    }
+}
+
+/// Converts `s` to a C string, replacing null bytes, which would cut it short.
+#[inline]
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "\u{fffd}")).expect("null bytes were replaced")
+}
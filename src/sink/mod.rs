@@ -0,0 +1,99 @@
+mod batch;
+#[cfg(windows)]
+mod debug_output;
+mod email;
+#[cfg(windows)]
+mod eventlog;
+mod fluentd;
+mod gelf;
+mod http;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(all(feature = "android", target_os = "android"))]
+mod logcat;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "sentry")]
+mod sentry;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "tls")]
+mod tls;
+mod webhook;
+
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::Record;
+
+#[cfg(windows)]
+pub use self::debug_output::DebugOutput;
+pub use self::email::{Email, EmailBuilder};
+#[cfg(windows)]
+pub use self::eventlog::EventLog;
+pub use self::fluentd::Fluentd;
+pub use self::gelf::Gelf;
+pub use self::http::{HttpSink, HttpSinkBuilder};
+#[cfg(feature = "kafka")]
+pub use self::kafka::{Kafka, KafkaBuilder};
+#[cfg(all(feature = "android", target_os = "android"))]
+pub use self::logcat::Logcat;
+#[cfg(feature = "sentry")]
+pub use self::sentry::{Sentry, SentryBuilder};
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::Sqlite;
+pub use self::webhook::{Webhook, WebhookBuilder};
+
+/// A logging target that receives whole records rather than rendered text, see
+/// [`LogPrefs::add_sink`](crate::LogPrefs::add_sink).
+///
+/// Sinks are handed every record that passes the filters, after it has been written to the
+/// writers, and are suited to destinations with their own notion of levels and fields.
+///
+/// # Example
+/// ```rust
+/// use std::io;
+///
+/// use bunt_logger::{Level, Record, Sink};
+///
+/// struct ErrorCounter(usize);
+///
+/// impl Sink for ErrorCounter {
+///     fn log(&mut self, record: &Record) -> io::Result<()> {
+///         if record.level() == Level::Error {
+///             self.0 += 1;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() {
+/// bunt_logger::with().add_sink(Box::new(ErrorCounter(0)));
+/// # }
+/// ```
+pub trait Sink: Send {
+    /// Handles a record.
+    fn log(&mut self, record: &Record) -> io::Result<()>;
+
+    /// Flushes buffered records, if any.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A handle to a sink that may be shared between the global preferences and their snapshots.
+#[derive(Clone)]
+pub(crate) struct SharedSink(Arc<Mutex<Box<dyn Sink>>>);
+
+impl SharedSink {
+    #[inline]
+    pub(crate) fn new(sink: Box<dyn Sink>) -> Self {
+        Self(Arc::new(Mutex::new(sink)))
+    }
+
+    #[inline]
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Box<dyn Sink>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
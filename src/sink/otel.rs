@@ -0,0 +1,150 @@
+use std::io::{self, Write};
+use std::time::UNIX_EPOCH;
+
+use log::Level;
+
+use super::http::push_json_str;
+use crate::format::write_json_str;
+use crate::{Kind, Record, Value};
+
+/// Encodes `record` as an OTLP log record in the JSON encoding.
+pub(crate) fn encode(record: &Record) -> io::Result<String> {
+    let mut out = Vec::new();
+    let nanos = record
+        .time()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos());
+    // 64-bit integers are strings in the JSON encoding of protobuf.
+    write!(
+        out,
+        "{{\"timeUnixNano\":\"{}\",\"severityNumber\":{},\"severityText\":\"{}\",\"body\":",
+        nanos,
+        severity(record),
+        record.level()
+    )?;
+    write_string(&record.message().to_string(), &mut out)?;
+
+    write!(out, ",\"attributes\":[")?;
+    write_str_attribute("code.namespace", record.target(), &mut out)?;
+    write!(out, ",")?;
+    write_str_attribute("thread.name", &record.thread_name(), &mut out)?;
+    if let Some(location) = record.location() {
+        write!(out, ",")?;
+        write_str_attribute("code.filepath", location.file(), &mut out)?;
+        write!(
+            out,
+            ",{{\"key\":\"code.lineno\",\"value\":{{\"intValue\":\"{}\"}}}}",
+            location.line()
+        )?;
+    }
+    let mut trace_id = None;
+    let mut span_id = None;
+    for field in record.fields() {
+        match (field.key(), field.value()) {
+            ("trace_id", Value::Str(id)) if is_hex_id(id, 32) => trace_id = Some(id),
+            ("span_id", Value::Str(id)) if is_hex_id(id, 16) => span_id = Some(id),
+            (key, value) => {
+                write!(out, ",")?;
+                write_attribute(key, value, &mut out)?;
+            }
+        }
+    }
+    write!(out, "]")?;
+    if let Some(id) = trace_id {
+        write!(out, ",\"traceId\":\"{}\"", id.to_ascii_lowercase())?;
+    }
+    if let Some(id) = span_id {
+        write!(out, ",\"spanId\":\"{}\"", id.to_ascii_lowercase())?;
+    }
+    write!(out, "}}")?;
+    Ok(String::from_utf8(out).expect("JSON output is UTF-8"))
+}
+
+/// Writes an OTLP logs export of `records`, encoded with [`encode`], from the resource with the
+/// attributes `resource`.
+pub(crate) fn write_body<'a, I>(resource: &[(String, String)], records: I, out: &mut String)
+where
+    I: Iterator<Item = &'a str>,
+{
+    out.push_str("{\"resourceLogs\":[{\"resource\":{\"attributes\":[");
+    for (i, (key, value)) in resource.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"key\":");
+        push_json_str(key, out);
+        out.push_str(",\"value\":{\"stringValue\":");
+        push_json_str(value, out);
+        out.push_str("}}");
+    }
+    out.push_str("]},\"scopeLogs\":[{\"scope\":{\"name\":\"bunt-logger\",\"version\":\"");
+    out.push_str(env!("CARGO_PKG_VERSION"));
+    out.push_str("\"},\"logRecords\":[");
+    for (i, record) in records.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(record);
+    }
+    out.push_str("]}]}]}");
+}
+
+/// Returns the OpenTelemetry severity number of `record`.
+#[inline]
+fn severity(record: &Record) -> u8 {
+    match (record.kind(), record.level()) {
+        (Some(Kind::Fatal), _) => 21,
+        (_, Level::Error) => 17,
+        (_, Level::Warn) => 13,
+        (_, Level::Info) => 9,
+        (_, Level::Debug) => 5,
+        (_, Level::Trace) => 1,
+    }
+}
+
+/// Whether `id` is a trace or span id of `len` hexadecimal digits.
+#[inline]
+fn is_hex_id(id: &str, len: usize) -> bool {
+    id.len() == len && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Writes an attribute with the key of a field and its value.
+fn write_attribute(key: &str, value: &Value, out: &mut Vec<u8>) -> io::Result<()> {
+    write!(out, "{{\"key\":")?;
+    write_json_str(key, out)?;
+    write!(out, ",\"value\":")?;
+    write_any(value, out)?;
+    write!(out, "}}")
+}
+
+/// Writes an attribute with a string value.
+#[inline]
+fn write_str_attribute(key: &str, value: &str, out: &mut Vec<u8>) -> io::Result<()> {
+    write!(out, "{{\"key\":\"{}\",\"value\":", key)?;
+    write_string(value, out)?;
+    write!(out, "}}")
+}
+
+/// Writes `s` as an `AnyValue`.
+#[inline]
+fn write_string(s: &str, out: &mut Vec<u8>) -> io::Result<()> {
+    write!(out, "{{\"stringValue\":")?;
+    write_json_str(s, out)?;
+    write!(out, "}}")
+}
+
+/// Writes `value` as an `AnyValue`.
+fn write_any(value: &Value, out: &mut Vec<u8>) -> io::Result<()> {
+    match value {
+        Value::Str(s) | Value::Debug(s) => write_string(s, out),
+        Value::I64(v) => write!(out, "{{\"intValue\":\"{}\"}}", v),
+        Value::U64(v) if *v <= i64::MAX as u64 => write!(out, "{{\"intValue\":\"{}\"}}", v),
+        Value::U64(v) => write!(out, "{{\"stringValue\":\"{}\"}}", v),
+        Value::F64(v) if v.is_finite() => write!(out, "{{\"doubleValue\":{}}}", v),
+        // The JSON encoding of protobuf spells out non-finite numbers as strings.
+        Value::F64(v) if v.is_nan() => write!(out, "{{\"doubleValue\":\"NaN\"}}"),
+        Value::F64(v) if *v > 0.0 => write!(out, "{{\"doubleValue\":\"Infinity\"}}"),
+        Value::F64(_) => write!(out, "{{\"doubleValue\":\"-Infinity\"}}"),
+        Value::Bool(v) => write!(out, "{{\"boolValue\":{}}}", v),
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{Level, LevelFilter};
+
+use super::http::{report, Endpoint};
+use crate::format::{write_json_str, write_json_value};
+use crate::{Kind, Record, Sink, WorkerGuard};
+
+/// The number of events that may be queued before new ones are dropped.
+const CAPACITY: usize = 1024;
+/// The number of breadcrumbs attached to an event; older ones are dropped.
+const MAX_BREADCRUMBS: usize = 100;
+
+enum Command {
+    /// An envelope with an event.
+    Event(String),
+    Shutdown,
+}
+
+/// A sink that reports records to Sentry, so that existing `error!` calls produce issues.
+/// Requires the `sentry` feature.
+///
+/// Records at or above the [event level](SentryBuilder::event_level), errors by default, are sent
+/// as events with their message, level, target as the logger, thread as a tag, and source
+/// location and [fields](crate::Field) as extra data. Records at or above the
+/// [breadcrumb level](SentryBuilder::breadcrumb_level), warnings by default, are kept as
+/// breadcrumbs and attached to the next event, up to the last 100 of them.
+///
+/// Events are sent in envelopes to the envelope endpoint of the project named by the DSN, over
+/// HTTPS, or HTTP for a self-hosted Sentry with an `http://` DSN. They are sent from a dedicated
+/// thread, which stops once the [`WorkerGuard`] returned alongside the sink is dropped, after
+/// sending everything that was queued. Failures are passed to the
+/// [error handler](crate::LogPrefs::on_error).
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::Sentry;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (sentry, _guard) = Sentry::builder("https://public@o0.ingest.sentry.io/42")
+///     .environment("production")
+///     .release(env!("CARGO_PKG_VERSION"))
+///     .spawn()?;
+/// bunt_logger::with().add_sink(Box::new(sentry));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sentry {
+    sender: SyncSender<Command>,
+    options: SentryBuilder,
+    breadcrumbs: VecDeque<String>,
+    ids: RandomState,
+    count: u64,
+}
+
+impl Sentry {
+    /// Returns a builder for a sink that reports to the project of `dsn`, e.g.
+    /// `"https://public@o0.ingest.sentry.io/42"`.
+    #[inline]
+    pub fn builder<T: Into<String>>(dsn: T) -> SentryBuilder {
+        SentryBuilder {
+            dsn: dsn.into(),
+            event_level: LevelFilter::Error,
+            breadcrumb_level: LevelFilter::Warn,
+            environment: None,
+            release: None,
+            server_name: crate::process::host_name().to_owned(),
+        }
+    }
+
+    /// Returns a new random event id, as 32 hexadecimal digits.
+    fn next_id(&mut self) -> String {
+        let mut id = String::with_capacity(32);
+        for _ in 0..2 {
+            self.count += 1;
+            let mut hasher = self.ids.build_hasher();
+            hasher.write_u64(self.count);
+            id.push_str(&format!("{:016x}", hasher.finish()));
+        }
+        id
+    }
+
+    /// Writes `record` as a breadcrumb.
+    fn breadcrumb(record: &Record) -> io::Result<String> {
+        let mut out = Vec::new();
+        write!(out, "{{\"timestamp\":")?;
+        write_timestamp(record.time(), &mut out)?;
+        write!(out, ",\"level\":\"{}\",\"category\":", level(record))?;
+        write_json_str(record.target(), &mut out)?;
+        write!(out, ",\"message\":")?;
+        write_json_str(&record.message().to_string(), &mut out)?;
+        write!(out, "}}")?;
+        Ok(String::from_utf8(out).expect("JSON output is UTF-8"))
+    }
+
+    /// Writes `record` as an envelope with an event, with the breadcrumbs collected so far.
+    fn envelope(&mut self, record: &Record) -> io::Result<String> {
+        let id = self.next_id();
+        let event = self.event(&id, record)?;
+        // The envelope and item headers, and the item, each on their own line.
+        Ok(format!(
+            "{{\"event_id\":\"{}\"}}\n{{\"type\":\"event\",\"length\":{}}}\n{}\n",
+            id,
+            event.len(),
+            event
+        ))
+    }
+
+    /// Writes `record` as the event `id`, with the breadcrumbs collected so far.
+    fn event(&mut self, id: &str, record: &Record) -> io::Result<String> {
+        let mut out = Vec::new();
+        write!(out, "{{\"event_id\":\"{}\",\"timestamp\":", id)?;
+        write_timestamp(record.time(), &mut out)?;
+        write!(
+            out,
+            ",\"platform\":\"other\",\"level\":\"{}\"",
+            level(record)
+        )?;
+        write!(out, ",\"logger\":")?;
+        write_json_str(record.target(), &mut out)?;
+        write!(out, ",\"message\":{{\"formatted\":")?;
+        write_json_str(&record.message().to_string(), &mut out)?;
+        write!(out, "}},\"server_name\":")?;
+        write_json_str(&self.options.server_name, &mut out)?;
+        if let Some(environment) = &self.options.environment {
+            write!(out, ",\"environment\":")?;
+            write_json_str(environment, &mut out)?;
+        }
+        if let Some(release) = &self.options.release {
+            write!(out, ",\"release\":")?;
+            write_json_str(release, &mut out)?;
+        }
+        write!(out, ",\"tags\":{{\"thread\":")?;
+        write_json_str(&record.thread_name(), &mut out)?;
+        write!(out, "}},\"extra\":{{")?;
+        let mut first = true;
+        if let Some(location) = record.location() {
+            write!(out, "\"location\":")?;
+            write_json_str(
+                &format!("{}:{}", location.file(), location.line()),
+                &mut out,
+            )?;
+            first = false;
+        }
+        for field in record.fields() {
+            if !first {
+                write!(out, ",")?;
+            }
+            write_json_str(field.key(), &mut out)?;
+            write!(out, ":")?;
+            write_json_value(field.value(), &mut out)?;
+            first = false;
+        }
+        write!(out, "}}")?;
+        if !self.breadcrumbs.is_empty() {
+            write!(out, ",\"breadcrumbs\":{{\"values\":[")?;
+            for (i, breadcrumb) in self.breadcrumbs.drain(..).enumerate() {
+                if i > 0 {
+                    write!(out, ",")?;
+                }
+                out.write_all(breadcrumb.as_bytes())?;
+            }
+            write!(out, "]}}")?;
+        }
+        write!(out, "}}")?;
+        Ok(String::from_utf8(out).expect("JSON output is UTF-8"))
+    }
+}
+
+impl Sink for Sentry {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if record.level() <= self.options.event_level {
+            let envelope = self.envelope(record)?;
+            return match self.sender.try_send(Command::Event(envelope)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "the Sentry sink fell behind and dropped an event",
+                )),
+                Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "the bunt-logger Sentry thread has stopped",
+                )),
+            };
+        }
+
+        if record.level() <= self.options.breadcrumb_level {
+            if self.breadcrumbs.len() == MAX_BREADCRUMBS {
+                self.breadcrumbs.pop_front();
+            }
+            self.breadcrumbs.push_back(Self::breadcrumb(record)?);
+        }
+        Ok(())
+    }
+}
+
+/// A builder for a [`Sentry`] sink.
+#[derive(Debug, Clone)]
+pub struct SentryBuilder {
+    dsn: String,
+    event_level: LevelFilter,
+    breadcrumb_level: LevelFilter,
+    environment: Option<String>,
+    release: Option<String>,
+    server_name: String,
+}
+
+impl SentryBuilder {
+    /// Sets the most verbose level that is sent as an event. Defaults to [`LevelFilter::Error`].
+    #[inline]
+    pub fn event_level(mut self, level: LevelFilter) -> Self {
+        self.event_level = level;
+        self
+    }
+
+    /// Sets the most verbose level that is kept as a breadcrumb. Defaults to
+    /// [`LevelFilter::Warn`].
+    #[inline]
+    pub fn breadcrumb_level(mut self, level: LevelFilter) -> Self {
+        self.breadcrumb_level = level;
+        self
+    }
+
+    /// Sets the environment of events, e.g. `production`.
+    #[inline]
+    pub fn environment<T: Into<String>>(mut self, environment: T) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Sets the release of events, e.g. the version of the application.
+    #[inline]
+    pub fn release<T: Into<String>>(mut self, release: T) -> Self {
+        self.release = Some(release.into());
+        self
+    }
+
+    /// Sets the server name of events. Defaults to the host name of the machine.
+    #[inline]
+    pub fn server_name<T: Into<String>>(mut self, name: T) -> Self {
+        self.server_name = name.into();
+        self
+    }
+
+    /// Spawns the thread that sends the events. Fails if the DSN is invalid or its host can't be
+    /// resolved.
+    pub fn spawn(self) -> io::Result<(Sentry, WorkerGuard)> {
+        let (endpoint, key) = parse_dsn(&self.dsn)?;
+        let auth = format!(
+            "Sentry sentry_version=7, sentry_key={}, sentry_client=bunt-logger/{}",
+            key,
+            env!("CARGO_PKG_VERSION")
+        );
+        let headers = vec![("X-Sentry-Auth".to_owned(), auth)];
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let handle = thread::Builder::new()
+            .name("bunt-logger-sentry".to_owned())
+            .spawn(move || work(endpoint, headers, receiver))?;
+
+        let sink = Sentry {
+            sender: sender.clone(),
+            options: self,
+            breadcrumbs: VecDeque::new(),
+            ids: RandomState::new(),
+            count: 0,
+        };
+        let guard = WorkerGuard::new(
+            Box::new(move || {
+                let _ = sender.send(Command::Shutdown);
+            }),
+            handle,
+        );
+        Ok((sink, guard))
+    }
+}
+
+/// Parses `dsn`, of the form `https://<key>@<host>[/<path>]/<project>`, into the envelope endpoint
+/// of the project and the public key.
+fn parse_dsn(dsn: &str) -> io::Result<(Endpoint, String)> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid Sentry DSN `{}`", dsn),
+        )
+    };
+    let (scheme, rest) = dsn.split_once("://").ok_or_else(invalid)?;
+    let (credentials, rest) = rest.split_once('@').ok_or_else(invalid)?;
+    // Old DSNs also contain a secret key, which is no longer needed.
+    let key = credentials.split(':').next().unwrap_or_default();
+    let (prefix, project) = rest.rsplit_once('/').ok_or_else(invalid)?;
+    if key.is_empty() || project.is_empty() {
+        return Err(invalid());
+    }
+    let url = format!("{}://{}/api/{}/envelope/", scheme, prefix, project);
+    let endpoint = Endpoint::parse(&url)?.content_type("application/x-sentry-envelope");
+    Ok((endpoint, key.to_owned()))
+}
+
+fn work(endpoint: Endpoint, headers: Vec<(String, String)>, receiver: Receiver<Command>) {
+    for command in receiver {
+        match command {
+            Command::Event(event) => {
+                if let Err(err) = endpoint.post(&headers, &event, 2) {
+                    report(err);
+                }
+            }
+            Command::Shutdown => break,
+        }
+    }
+}
+
+/// Returns the Sentry level of `record`.
+#[inline]
+fn level(record: &Record) -> &'static str {
+    match (record.kind(), record.level()) {
+        (Some(Kind::Fatal), _) => "fatal",
+        (_, Level::Error) => "error",
+        (_, Level::Warn) => "warning",
+        (_, Level::Info) => "info",
+        (_, Level::Debug) | (_, Level::Trace) => "debug",
+    }
+}
+
+/// Writes `time` as seconds since the Unix epoch, with millisecond precision.
+#[inline]
+fn write_timestamp(time: SystemTime, out: &mut Vec<u8>) -> io::Result<()> {
+    let time = time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+    write!(out, "{}.{:03}", time.as_secs(), time.subsec_millis())
+}
@@ -0,0 +1,175 @@
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rusqlite::{params, Connection};
+
+use crate::format::write_json_fields;
+use crate::{Record, Sink, TimestampFormat};
+
+/// How long to wait for a lock held by another connection, such as a log viewer.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "\
+    PRAGMA journal_mode = WAL;
+    PRAGMA synchronous = NORMAL;
+    CREATE TABLE IF NOT EXISTS logs (
+        id INTEGER PRIMARY KEY,
+        time TEXT NOT NULL,
+        level TEXT NOT NULL,
+        target TEXT NOT NULL,
+        message TEXT NOT NULL,
+        fields TEXT
+    );
+    CREATE INDEX IF NOT EXISTS logs_time ON logs (time);";
+
+const INSERT: &str =
+    "INSERT INTO logs (time, level, target, message, fields) VALUES (?, ?, ?, ?, ?)";
+
+/// A record waiting to be inserted.
+#[derive(Debug)]
+struct Row {
+    time: String,
+    level: &'static str,
+    target: String,
+    message: String,
+    fields: Option<String>,
+}
+
+/// A sink that writes records into an SQLite database, so that applications can offer a
+/// searchable view of their logs. Requires the `sqlite` feature, which uses `rusqlite` and links
+/// to the system's `libsqlite3`, or `sqlite-bundled` to compile SQLite into the program instead.
+///
+/// Records are inserted into the table `logs`, created if needed, with the columns `id`, `time`
+/// as an RFC 3339 timestamp in UTC, `level`, `target`, `message`, and `fields` as a JSON object,
+/// or `NULL` if the record has none. The database is put in WAL mode, so that it can be read
+/// while records are written.
+///
+/// Records are inserted in batches, one transaction each, once
+/// [`batch_size`](Self::batch_size) of them are pending, when a record arrives
+/// [`batch_interval`](Self::batch_interval) after the last batch, and when the sink is flushed or
+/// dropped. Keep the [`FlushGuard`](crate::FlushGuard) of the preferences alive, or call
+/// [`flush`](fn@crate::flush) before exiting, so that the last batch isn't lost.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::Sqlite;
+///
+/// # fn main() -> std::io::Result<()> {
+/// bunt_logger::with().add_sink(Box::new(Sqlite::open("logs.db")?));
+/// // SELECT time, message FROM logs WHERE level = 'ERROR' ORDER BY time DESC;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sqlite {
+    connection: Connection,
+    pending: Vec<Row>,
+    batch_size: usize,
+    batch_interval: Duration,
+    last_batch: Instant,
+}
+
+impl Sqlite {
+    /// Opens or creates the database at `path` and creates the `logs` table if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let connection = Connection::open(path).map_err(error)?;
+        connection.busy_timeout(BUSY_TIMEOUT).map_err(error)?;
+        connection.execute_batch(SCHEMA).map_err(error)?;
+        Ok(Self {
+            connection,
+            pending: Vec::new(),
+            batch_size: 100,
+            batch_interval: Duration::from_secs(1),
+            last_batch: Instant::now(),
+        })
+    }
+
+    /// Sets the number of pending records after which a batch is inserted. Defaults to 100.
+    #[inline]
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Sets the time after the last batch after which the next record is inserted right away,
+    /// with the others that are pending. Defaults to 1 second.
+    #[inline]
+    pub fn batch_interval(mut self, interval: Duration) -> Self {
+        self.batch_interval = interval;
+        self
+    }
+
+    /// Inserts the pending records in one transaction. They are dropped if that fails.
+    fn write_batch(&mut self) -> io::Result<()> {
+        self.last_batch = Instant::now();
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(&mut self.pending);
+        // The transaction is rolled back if it is dropped before the commit.
+        let transaction = self.connection.transaction().map_err(error)?;
+        {
+            let mut insert = transaction.prepare_cached(INSERT).map_err(error)?;
+            for row in &rows {
+                insert
+                    .execute(params![
+                        row.time,
+                        row.level,
+                        row.target,
+                        row.message,
+                        row.fields
+                    ])
+                    .map_err(error)?;
+            }
+        }
+        transaction.commit().map_err(error)
+    }
+}
+
+impl Sink for Sqlite {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        let fields = if record.fields().is_empty() {
+            None
+        } else {
+            let mut json = Vec::new();
+            write_json_fields(record.fields(), &mut json)?;
+            Some(String::from_utf8(json).expect("JSON output is UTF-8"))
+        };
+        self.pending.push(Row {
+            time: TimestampFormat::Rfc3339
+                .format(record.time())
+                .unwrap_or_default(),
+            level: record.level().as_str(),
+            target: record.target().to_owned(),
+            message: record.message().to_string(),
+            fields,
+        });
+
+        if self.pending.len() >= self.batch_size || self.last_batch.elapsed() >= self.batch_interval
+        {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_batch()
+    }
+}
+
+impl Drop for Sqlite {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.write_batch();
+    }
+}
+
+/// Converts an error of `rusqlite` into an I/O error.
+// `io::Error::other` needs Rust 1.74.
+#[allow(clippy::io_other_error)]
+fn error(err: rusqlite::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("SQLite: {}", err))
+}
@@ -0,0 +1,43 @@
+use std::convert::TryFrom;
+use std::io;
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+/// The configuration of all connections, which trusts the Mozilla root certificates bundled by
+/// `webpki-roots`.
+///
+/// The provider is picked explicitly, so that other crates enabling another one don't make the
+/// choice ambiguous.
+static CONFIG: Lazy<Arc<ClientConfig>> = Lazy::new(|| {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("the provider supports the default protocol versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Arc::new(config)
+});
+
+/// A TLS connection over TCP.
+pub(crate) type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// Starts a TLS session with `host` over `stream`. The handshake happens on the first read or
+/// write.
+pub(crate) fn connect(host: &str, stream: TcpStream) -> io::Result<TlsStream> {
+    let name = ServerName::try_from(host.to_owned()).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid server name `{}`: {}", host, err),
+        )
+    })?;
+    let connection = ClientConnection::new(Arc::clone(&CONFIG), name)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(StreamOwned::new(connection, stream))
+}
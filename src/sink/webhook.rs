@@ -0,0 +1,382 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::LevelFilter;
+
+use super::http::{push_json_str, report, Endpoint};
+use crate::{Record, Sink, WorkerGuard};
+
+/// The number of records that may be queued before new ones are dropped.
+const CAPACITY: usize = 1024;
+/// The number of records listed in one notification; later ones are only counted.
+const MAX_LISTED: usize = 10;
+
+enum Command {
+    Record(String),
+    Shutdown,
+}
+
+/// The chat service that a [`Webhook`] posts to, which determines the shape of the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    Slack,
+    Discord,
+}
+
+impl Service {
+    /// The longest text that the service accepts in one message.
+    #[inline]
+    fn max_len(self) -> usize {
+        match self {
+            Self::Slack => 4000,
+            Self::Discord => 2000,
+        }
+    }
+}
+
+/// A sink that notifies a Slack or Discord channel of error records through an incoming webhook,
+/// so that small services get failure alerts without a monitoring stack.
+///
+/// Each notification lists the level, target and first line of the message of the records. The
+/// first record is posted right away; records that follow within the
+/// [`min_interval`](WebhookBuilder::min_interval) are coalesced into a single notification, posted
+/// once the interval has passed, which lists the first 10 of them and counts the rest. Posting
+/// happens on a dedicated thread, which stops once the [`WorkerGuard`] returned alongside the sink
+/// is dropped, after posting what is pending. Failures are passed to the
+/// [error handler](crate::LogPrefs::on_error).
+///
+/// Like for [`HttpSink`](crate::HttpSink), the HTTPS webhook URLs of Slack and Discord need the
+/// `tls` feature; without it, they have to be reached through a local forwarding proxy.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use bunt_logger::Webhook;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (webhook, _guard) = Webhook::slack("https://hooks.slack.com/services/T000/B000/XXXX")
+///     .min_interval(Duration::from_secs(300))
+///     .spawn()?;
+/// bunt_logger::with().add_sink(Box::new(webhook));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Webhook {
+    sender: SyncSender<Command>,
+    level: LevelFilter,
+}
+
+impl Webhook {
+    /// Returns a builder for a sink that posts to the Slack incoming webhook at `url`.
+    #[inline]
+    pub fn slack<T: Into<String>>(url: T) -> WebhookBuilder {
+        WebhookBuilder::new(url.into(), Service::Slack)
+    }
+
+    /// Returns a builder for a sink that posts to the Discord webhook at `url`.
+    #[inline]
+    pub fn discord<T: Into<String>>(url: T) -> WebhookBuilder {
+        WebhookBuilder::new(url.into(), Service::Discord)
+    }
+}
+
+impl Sink for Webhook {
+    fn log(&mut self, record: &Record) -> io::Result<()> {
+        if self.level < record.level() {
+            return Ok(());
+        }
+
+        let message = record.message().to_string();
+        let line = format!(
+            "{} {}: {}",
+            record.level(),
+            record.target(),
+            message.lines().next().unwrap_or_default()
+        );
+        match self.sender.try_send(Command::Record(line)) {
+            // Alerts are best-effort; a flood of them shouldn't become a flood of errors.
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the bunt-logger webhook thread has stopped",
+            )),
+        }
+    }
+}
+
+/// A builder for a [`Webhook`].
+#[derive(Debug, Clone)]
+pub struct WebhookBuilder {
+    url: String,
+    service: Service,
+    level: LevelFilter,
+    min_interval: Duration,
+    title: Option<String>,
+}
+
+impl WebhookBuilder {
+    #[inline]
+    fn new(url: String, service: Service) -> Self {
+        Self {
+            url,
+            service,
+            level: LevelFilter::Error,
+            min_interval: Duration::from_secs(60),
+            title: None,
+        }
+    }
+
+    /// Sets the most verbose level that is posted. Defaults to [`LevelFilter::Error`].
+    #[inline]
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the shortest time between two notifications. Defaults to 1 minute.
+    #[inline]
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Sets a line that starts every notification, such as the name of the service.
+    #[inline]
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Spawns the thread that posts the notifications. Fails if the URL is invalid or its host
+    /// can't be resolved.
+    pub fn spawn(self) -> io::Result<(Webhook, WorkerGuard)> {
+        let endpoint = Endpoint::parse(&self.url)?;
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let level = self.level;
+        let handle = thread::Builder::new()
+            .name("bunt-logger-webhook".to_owned())
+            .spawn(move || work(self, endpoint, receiver))?;
+
+        let sink = Webhook {
+            sender: sender.clone(),
+            level,
+        };
+        let guard = WorkerGuard::new(
+            Box::new(move || {
+                let _ = sender.send(Command::Shutdown);
+            }),
+            handle,
+        );
+        Ok((sink, guard))
+    }
+
+    /// Returns the body of the notification for `lines` and `more` records that aren't listed.
+    fn body(&self, lines: &[String], more: usize) -> String {
+        let mut text = String::new();
+        if let Some(title) = &self.title {
+            text.push_str(title);
+            text.push('\n');
+        }
+        for line in lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        if more > 0 {
+            text.push_str(&format!("... and {} more\n", more));
+        }
+        let text = truncate(text.trim_end(), self.service.max_len());
+
+        let mut body = String::new();
+        body.push_str(match self.service {
+            Service::Slack => "{\"text\":",
+            Service::Discord => "{\"content\":",
+        });
+        push_json_str(text, &mut body);
+        body.push('}');
+        body
+    }
+}
+
+/// The records waiting to be posted, coalesced so that notifications are at least an interval
+/// apart.
+#[derive(Debug)]
+struct Pending {
+    lines: Vec<String>,
+    /// The number of records that are only counted.
+    more: usize,
+    min_interval: Duration,
+    /// The earliest time at which the next notification may be posted.
+    next: Option<Instant>,
+}
+
+impl Pending {
+    #[inline]
+    fn new(min_interval: Duration) -> Self {
+        Self {
+            lines: Vec::new(),
+            more: 0,
+            min_interval,
+            next: None,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, line: String) {
+        if self.lines.len() < MAX_LISTED {
+            self.lines.push(line);
+        } else {
+            self.more += 1;
+        }
+    }
+
+    /// Returns when the pending records are due, or `None` if there are none, so that the thread
+    /// can wait for the next one.
+    #[inline]
+    fn due(&self) -> Option<Instant> {
+        match self.next {
+            Some(next) if !self.lines.is_empty() => Some(next),
+            _ => None,
+        }
+    }
+
+    /// Takes the lines and the count of the others for a notification at `now`, if one is due; or
+    /// anyway if `force` is set, such as on shutdown.
+    fn take(&mut self, now: Instant, force: bool) -> Option<(Vec<String>, usize)> {
+        let due = !matches!(self.next, Some(next) if now < next);
+        if self.lines.is_empty() || !(due || force) {
+            return None;
+        }
+        self.next = Some(now + self.min_interval);
+        let more = std::mem::take(&mut self.more);
+        Some((std::mem::take(&mut self.lines), more))
+    }
+}
+
+fn work(builder: WebhookBuilder, endpoint: Endpoint, receiver: Receiver<Command>) {
+    let mut pending = Pending::new(builder.min_interval);
+    loop {
+        let command = match pending.due() {
+            Some(due) => receiver.recv_timeout(due.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let stop = match command {
+            Ok(Command::Record(line)) => {
+                pending.push(line);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        if let Some((lines, more)) = pending.take(Instant::now(), stop) {
+            let body = builder.body(&lines, more);
+            if let Err(err) = endpoint.post(&[], &body, 1) {
+                report(err);
+            }
+        }
+        if stop {
+            break;
+        }
+    }
+}
+
+/// Returns `text` cut to at most `max` bytes, on a character boundary.
+#[inline]
+fn truncate(text: &str, max: usize) -> &str {
+    let mut end = text.len().min(max);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(range: std::ops::RangeInclusive<usize>) -> Vec<String> {
+        range.map(|i| format!("ERROR app: failure {}", i)).collect()
+    }
+
+    #[test]
+    fn coalesce_within_interval() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut pending = Pending::new(Duration::from_secs(60));
+        assert_eq!(pending.take(at(0), false), None);
+
+        // The first record is posted right away.
+        pending.push("ERROR app: failure 1".to_owned());
+        assert_eq!(pending.take(at(0), false), Some((lines(1..=1), 0)));
+        assert_eq!(pending.due(), None);
+
+        // Later ones wait for the end of the interval.
+        pending.push("ERROR app: failure 2".to_owned());
+        pending.push("ERROR app: failure 3".to_owned());
+        assert_eq!(pending.due(), Some(at(60)));
+        assert_eq!(pending.take(at(10), false), None);
+        assert_eq!(pending.take(at(59), false), None);
+        assert_eq!(pending.take(at(60), false), Some((lines(2..=3), 0)));
+
+        // The next interval starts with that notification.
+        pending.push("ERROR app: failure 4".to_owned());
+        assert_eq!(pending.due(), Some(at(120)));
+        assert_eq!(pending.take(at(119), false), None);
+        assert_eq!(pending.take(at(200), false), Some((lines(4..=4), 0)));
+        assert_eq!(pending.take(at(300), false), None);
+
+        // After a quiet interval, a record is posted right away again.
+        pending.push("ERROR app: failure 5".to_owned());
+        assert_eq!(pending.take(at(400), false), Some((lines(5..=5), 0)));
+    }
+
+    #[test]
+    fn count_unlisted_records() {
+        let start = Instant::now();
+        let mut pending = Pending::new(Duration::from_secs(60));
+        pending.push("ERROR app: failure 0".to_owned());
+        assert!(pending.take(start, false).is_some());
+
+        for line in lines(1..=25) {
+            pending.push(line);
+        }
+        let at = start + Duration::from_secs(60);
+        assert_eq!(pending.take(at, false), Some((lines(1..=10), 15)));
+        pending.push("ERROR app: failure 26".to_owned());
+        let at = at + Duration::from_secs(60);
+        assert_eq!(pending.take(at, false), Some((lines(26..=26), 0)));
+    }
+
+    #[test]
+    fn force_on_shutdown() {
+        let start = Instant::now();
+        let mut pending = Pending::new(Duration::from_secs(60));
+        pending.push("ERROR app: failure 1".to_owned());
+        assert!(pending.take(start, false).is_some());
+        assert_eq!(pending.take(start, true), None);
+
+        pending.push("ERROR app: failure 2".to_owned());
+        let at = start + Duration::from_secs(1);
+        assert_eq!(pending.take(at, true), Some((lines(2..=2), 0)));
+    }
+
+    #[test]
+    fn notification_bodies() {
+        let builder = Webhook::slack("http://127.0.0.1/").title("web");
+        assert_eq!(
+            builder.body(&lines(1..=2), 3),
+            r#"{"text":"web\nERROR app: failure 1\nERROR app: failure 2\n... and 3 more"}"#
+        );
+        let builder = Webhook::discord("http://127.0.0.1/");
+        assert_eq!(
+            builder.body(&lines(1..=1), 0),
+            r#"{"content":"ERROR app: failure 1"}"#
+        );
+        let body = builder.body(&["é".repeat(1500)], 0);
+        assert_eq!(body, format!(r#"{{"content":"{}"}}"#, "é".repeat(1000)));
+    }
+}
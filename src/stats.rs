@@ -0,0 +1,108 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::Level;
+
+/// The number of records logged through the global preferences, indexed by
+/// `level as usize - 1`.
+static COUNTS: [AtomicUsize; 5] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// Counts a record of `level`.
+#[inline]
+pub(crate) fn count(level: Level) {
+    COUNTS[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of records of each level logged through the global preferences, returned by
+/// [`stats`].
+///
+/// Records count once they pass the filters, hooks and sampling; repetitions collapsed by
+/// [`dedup`](crate::LogPrefs::dedup) count as well. The [`Display`](fmt::Display)
+/// implementation summarizes the warnings and errors, like `2 warnings, 1 error`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    counts: [usize; 5],
+}
+
+impl Stats {
+    /// Returns the number of records logged at `level`.
+    #[inline]
+    pub fn count(&self, level: Level) -> usize {
+        self.counts[level as usize - 1]
+    }
+
+    /// Returns the number of records logged at the error level.
+    #[inline]
+    pub fn errors(&self) -> usize {
+        self.count(Level::Error)
+    }
+
+    /// Returns the number of records logged at the warning level.
+    #[inline]
+    pub fn warnings(&self) -> usize {
+        self.count(Level::Warn)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        match (self.warnings(), self.errors()) {
+            (0, 0) => write!(f, "no warnings or errors"),
+            (warnings, 0) => write!(f, "{} warning{}", warnings, plural(warnings)),
+            (0, errors) => write!(f, "{} error{}", errors, plural(errors)),
+            (warnings, errors) => write!(
+                f,
+                "{} warning{}, {} error{}",
+                warnings,
+                plural(warnings),
+                errors,
+                plural(errors)
+            ),
+        }
+    }
+}
+
+/// Returns the number of records of each level logged so far through the global preferences.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, warn};
+///
+/// # fn main() {
+/// # if bunt_logger::STATIC_MAX_LEVEL < bunt_logger::LevelFilter::Info { return; }
+/// warn!("Unused variable");
+/// info!("Finished");
+///
+/// let stats = bunt_logger::stats();
+/// assert_eq!(stats.warnings(), 1);
+/// assert_eq!(stats.to_string(), "1 warning");
+/// # }
+/// ```
+pub fn stats() -> Stats {
+    let mut stats = Stats::default();
+    for (count, total) in stats.counts.iter_mut().zip(&COUNTS) {
+        *count = total.load(Ordering::Relaxed);
+    }
+    stats
+}
+
+/// Returns the exit code for the process based on the records logged so far: `1` if any error was
+/// logged through the global preferences, and `0` otherwise.
+///
+/// # Example
+/// ```rust,no_run
+/// # fn main() {
+/// std::process::exit(bunt_logger::exit_code_from_severity());
+/// # }
+/// ```
+#[inline]
+pub fn exit_code_from_severity() -> i32 {
+    (stats().errors() > 0) as i32
+}
@@ -0,0 +1,187 @@
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use bunt::termcolor::{ColorChoice, StandardStream};
+use once_cell::sync::Lazy;
+
+use crate::color::{self, Stream};
+
+/// The frames of the spinner, drawn one after another.
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How long each frame of the spinner is shown.
+const FRAME_INTERVAL: Duration = Duration::from_millis(80);
+
+/// The status line that is currently drawn, if any.
+static LINE: Mutex<Option<Line>> = Mutex::new(None);
+
+/// Whether a status line is drawn, so that records don't take the lock of [`LINE`] otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether stdout is the terminal that the status line is drawn on.
+static STDOUT_TERMINAL: Lazy<bool> = Lazy::new(|| io::stdout().is_terminal());
+
+/// The id of the latest status line.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+struct Line {
+    id: u64,
+    message: String,
+    frame: usize,
+}
+
+impl Line {
+    fn draw(&self) {
+        let mut stderr = StandardStream::stderr(color::resolve(ColorChoice::Auto, Stream::Stderr));
+        let _ = bunt::write!(
+            stderr,
+            "\r\x1b[2K{$cyan}{}{/$} {}",
+            FRAMES[self.frame % FRAMES.len()],
+            self.message
+        );
+        let _ = stderr.flush();
+    }
+}
+
+/// Shows an updatable status line with a spinner at the bottom of the terminal, returning a handle
+/// that removes it when dropped.
+///
+/// Records that are logged to stdout or stderr while the line is shown are written above it; other
+/// logging targets are written to as usual. Only one status line is shown at a time; a new one
+/// replaces the previous. When stderr isn't a terminal, or the thread of the spinner can't be
+/// spawned, the status and every change to it are logged as info records instead.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::info;
+///
+/// # fn main() {
+/// let status = bunt_logger::status("Resolving dependencies…");
+/// info!("Found {[bold]} packages", 12);
+/// status.set_message("Downloading…");
+/// drop(status);
+/// # }
+/// ```
+pub fn status<M: Display>(message: M) -> Status {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut status = Status {
+        id,
+        terminal: io::stderr().is_terminal(),
+    };
+    if !status.terminal {
+        status.set_message(message);
+        return status;
+    }
+
+    let line = Line {
+        id,
+        message: message.to_string(),
+        frame: 0,
+    };
+    let mut current = lock();
+    line.draw();
+    *current = Some(line);
+    ACTIVE.store(true, Ordering::Release);
+    drop(current);
+
+    let spawned = thread::Builder::new()
+        .name("bunt-logger-status".to_owned())
+        .spawn(move || spin(id));
+    if spawned.is_err() {
+        // Without a spinner, the status is logged like when stderr isn't a terminal.
+        status.remove();
+        status.terminal = false;
+        status.set_message(message);
+    }
+    status
+}
+
+/// A status line shown by [`status`], removed when dropped.
+#[must_use = "the status line is removed as soon as the handle is dropped"]
+pub struct Status {
+    id: u64,
+    terminal: bool,
+}
+
+impl Status {
+    /// Replaces the message of the status line.
+    pub fn set_message<M: Display>(&self, message: M) {
+        if !self.terminal {
+            crate::info!(target: "status", "{}", message);
+            return;
+        }
+        if let Some(line) = lock().as_mut().filter(|line| line.id == self.id) {
+            line.message = message.to_string();
+            line.draw();
+        }
+    }
+
+    /// Removes the status line if it is still shown.
+    fn remove(&self) {
+        let mut line = lock();
+        if line.as_ref().is_some_and(|line| line.id == self.id) {
+            *line = None;
+            ACTIVE.store(false, Ordering::Release);
+            clear();
+        }
+    }
+}
+
+impl Drop for Status {
+    #[inline]
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+/// Runs `write`, which writes to `stream`, with the status line cleared before and redrawn after
+/// it if one is shown and the stream is stderr or stdout on the same terminal. Writes to other
+/// targets don't wait for the status line.
+pub(crate) fn around<R>(stream: Option<Stream>, write: impl FnOnce() -> R) -> R {
+    let shares_terminal = match stream {
+        Some(Stream::Stderr) => true,
+        Some(Stream::Stdout) => *STDOUT_TERMINAL,
+        None => false,
+    };
+    if !shares_terminal || !ACTIVE.load(Ordering::Acquire) {
+        return write();
+    }
+    let line = lock();
+    if line.is_some() {
+        clear();
+    }
+    let result = write();
+    if let Some(line) = &*line {
+        line.draw();
+    }
+    result
+}
+
+/// Advances the spinner of the line with `id` until it's removed or replaced.
+fn spin(id: u64) {
+    loop {
+        thread::sleep(FRAME_INTERVAL);
+        match lock().as_mut().filter(|line| line.id == id) {
+            Some(line) => {
+                line.frame += 1;
+                line.draw();
+            }
+            None => return,
+        }
+    }
+}
+
+fn clear() {
+    let mut stderr = io::stderr();
+    let _ = stderr.write_all(b"\r\x1b[2K");
+    let _ = stderr.flush();
+}
+
+#[inline]
+fn lock() -> std::sync::MutexGuard<'static, Option<Line>> {
+    LINE.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+use std::panic;
+
+use crate::{Global, Level, Location, Message, Record};
+
+/// Returns a [`Table`] with the given header.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::Level;
+///
+/// # fn main() {
+/// bunt_logger::table(["Crate", "Version", "Size"])
+///     .row(["bunt", "0.2.9", "12 KiB"])
+///     .row(["bunt-logger", "0.1.1", "240 KiB"])
+///     .log(Level::Info);
+/// # }
+/// ```
+#[inline]
+pub fn table<I>(header: I) -> Table
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    Table {
+        header: cells(header),
+        rows: Vec::new(),
+    }
+}
+
+/// Rows of cells that are logged with their columns aligned, below a bold header.
+///
+/// Each line of the table is logged as a separate record with the target `table` and the source
+/// location of the call to [`log`](Self::log). Columns are as wide as their widest cell, counted
+/// in characters, and separated by two spaces. Rows with fewer cells than the header are padded
+/// with empty cells.
+#[derive(Debug, Clone)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Adds a row.
+    #[inline]
+    pub fn row<I>(mut self, row: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.push_row(row);
+        self
+    }
+
+    /// Adds a row in place, e.g. while iterating over data.
+    #[inline]
+    pub fn push_row<I>(&mut self, row: I)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.rows.push(cells(row));
+    }
+
+    /// Logs the table at the given level through the global preferences.
+    #[track_caller]
+    pub fn log(&self, level: Level) {
+        if level > crate::STATIC_MAX_LEVEL || !Global.enabled(level, "table") {
+            return;
+        }
+        let caller = panic::Location::caller();
+        let location = Location::new(caller.file(), caller.line());
+        for message in self.lines() {
+            let record = Record::new(level, "table", message).with_location(location.clone());
+            Global.log(&record);
+        }
+    }
+
+    /// Returns the lines of the table, starting with the header.
+    fn lines(&self) -> Vec<Message> {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .fold(self.header.len(), usize::max);
+        let mut widths = vec![0; columns];
+        for row in std::iter::once(&self.header).chain(&self.rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        if !self.header.is_empty() {
+            let mut message = Message::new();
+            let _ = bunt::write!(message, "{$bold}{}{/$}", pad(&self.header, &widths));
+            lines.push(message);
+        }
+        lines.extend(self.rows.iter().map(|row| Message::from(pad(row, &widths))));
+        lines
+    }
+}
+
+fn cells<I>(row: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    row.into_iter().map(|cell| cell.to_string()).collect()
+}
+
+/// Joins the cells of a row, padded to the widths of their columns.
+fn pad(row: &[String], widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (i, &width) in widths.iter().enumerate() {
+        let cell = row.get(i).map_or("", String::as_str);
+        if i > 0 {
+            line.push_str("  ");
+        }
+        line.push_str(cell);
+        line.push_str(&" ".repeat(width - cell.chars().count()));
+    }
+    line.truncate(line.trim_end().len());
+    line
+}
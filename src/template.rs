@@ -0,0 +1,365 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use bunt::termcolor::WriteColor;
+
+use crate::theme::Theme;
+use crate::Record;
+
+/// A field that can be referenced in a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Time,
+    Level,
+    Target,
+    Message,
+    Fields,
+    Location,
+    Thread,
+    Host,
+    Pid,
+    Process,
+    Scope,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Field { field: Field, width: usize },
+}
+
+/// A parsed output template, such as `{time} [{level}] {target}: {message}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    /// Parses a template; see [`LogPrefs::template`](crate::LogPrefs::template) for the syntax.
+    pub(crate) fn parse(template: &str) -> Result<Self, ParseTemplateError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let end = template[start..]
+                        .find('}')
+                        .map(|i| start + i)
+                        .ok_or_else(|| ParseTemplateError::new(&template[start..]))?;
+                    let placeholder = &template[start..=end];
+                    let piece = parse_field(&template[start + 1..end])
+                        .ok_or_else(|| ParseTemplateError::new(placeholder))?;
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(piece);
+                    while let Some(&(i, _)) = chars.peek() {
+                        if i > end {
+                            break;
+                        }
+                        chars.next();
+                    }
+                }
+                '}' => return Err(ParseTemplateError::new("}")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Self { pieces })
+    }
+
+    /// Writes `record` laid out according to the template, without a trailing newline.
+    pub(crate) fn render(
+        &self,
+        record: &Record,
+        timestamp: Option<&str>,
+        theme: &Theme,
+        writer: &mut dyn WriteColor,
+    ) -> io::Result<()> {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(text) => write!(writer, "{}", text)?,
+                Piece::Field {
+                    field: Field::Time,
+                    width,
+                } => write!(writer, "{:<width$}", timestamp.unwrap_or(""), width = width)?,
+                Piece::Field {
+                    field: Field::Level,
+                    width,
+                } => {
+                    writer.set_color(theme.style_for(record))?;
+                    write!(
+                        writer,
+                        "{:<width$}",
+                        theme.record_label(record),
+                        width = width
+                    )?;
+                    writer.reset()?;
+                }
+                Piece::Field {
+                    field: Field::Target,
+                    width,
+                } => write!(writer, "{:<width$}", record.target(), width = width)?,
+                Piece::Field {
+                    field: Field::Message,
+                    ..
+                } => theme.write_message(record, writer)?,
+                Piece::Field {
+                    field: Field::Fields,
+                    ..
+                } => theme.write_fields(record, writer)?,
+                Piece::Field {
+                    field: Field::Location,
+                    width,
+                } => match record.location() {
+                    Some(location) => {
+                        let location = format!("{:<width$}", location, width = width);
+                        theme.write_meta(&location, writer)?;
+                    }
+                    None => write!(writer, "{:width$}", "", width = width)?,
+                },
+                Piece::Field {
+                    field: Field::Thread,
+                    width,
+                } => {
+                    let thread = format!("{:<width$}", record.thread_name(), width = width);
+                    theme.write_meta(&thread, writer)?;
+                }
+                Piece::Field {
+                    field: Field::Host,
+                    width,
+                } => {
+                    let host = format!("{:<width$}", crate::process::host_name(), width = width);
+                    theme.write_meta(&host, writer)?;
+                }
+                Piece::Field {
+                    field: Field::Pid,
+                    width,
+                } => {
+                    let pid = format!("{:<width$}", crate::process::id(), width = width);
+                    theme.write_meta(&pid, writer)?;
+                }
+                Piece::Field {
+                    field: Field::Process,
+                    width,
+                } => {
+                    let name = format!("{:<width$}", crate::process::name(), width = width);
+                    theme.write_meta(&name, writer)?;
+                }
+                Piece::Field {
+                    field: Field::Scope,
+                    width,
+                } => {
+                    let scope = record.scope_path().unwrap_or_default();
+                    writer.set_color(theme.scope_style())?;
+                    write!(writer, "{:<width$}", scope, width = width)?;
+                    writer.reset()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the inside of a placeholder, e.g. `level` or `level:5`.
+fn parse_field(placeholder: &str) -> Option<Piece> {
+    let mut parts = placeholder.splitn(2, ':');
+    let field = match parts.next()?.trim() {
+        "time" => Field::Time,
+        "level" => Field::Level,
+        "target" => Field::Target,
+        "message" => Field::Message,
+        "fields" => Field::Fields,
+        "location" => Field::Location,
+        "thread" => Field::Thread,
+        "host" => Field::Host,
+        "pid" => Field::Pid,
+        "process" => Field::Process,
+        "scope" => Field::Scope,
+        _ => return None,
+    };
+    let width = match parts.next() {
+        Some(width) => width.trim().parse().ok()?,
+        None => 0,
+    };
+    Some(Piece::Field { field, width })
+}
+
+/// The error returned when a template contains an unknown or malformed placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTemplateError {
+    placeholder: String,
+}
+
+impl ParseTemplateError {
+    #[inline]
+    fn new(placeholder: &str) -> Self {
+        Self {
+            placeholder: placeholder.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid template placeholder `{}`", self.placeholder)
+    }
+}
+
+impl Error for ParseTemplateError {}
+
+#[cfg(test)]
+mod tests {
+    use bunt::termcolor::NoColor;
+    use log::Level;
+
+    use super::*;
+    use crate::{Location, Message};
+
+    fn literal(text: &str) -> Piece {
+        Piece::Literal(text.to_owned())
+    }
+
+    fn field(field: Field, width: usize) -> Piece {
+        Piece::Field { field, width }
+    }
+
+    fn pieces(template: &str) -> Vec<Piece> {
+        Template::parse(template).unwrap().pieces
+    }
+
+    fn error(template: &str) -> String {
+        Template::parse(template).unwrap_err().placeholder
+    }
+
+    fn render(template: &str, record: &Record, timestamp: Option<&str>) -> String {
+        let template = Template::parse(template).unwrap();
+        let mut out = NoColor::new(Vec::new());
+        let theme = Theme::monochrome();
+        template
+            .render(record, timestamp, &theme, &mut out)
+            .unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn parse_placeholders() {
+        assert_eq!(
+            pieces("{time} [{level}] {target}: {message}"),
+            vec![
+                field(Field::Time, 0),
+                literal(" ["),
+                field(Field::Level, 0),
+                literal("] "),
+                field(Field::Target, 0),
+                literal(": "),
+                field(Field::Message, 0),
+            ]
+        );
+        assert_eq!(
+            pieces("{fields}{location}{thread}{host}{pid}{process}{scope}"),
+            vec![
+                field(Field::Fields, 0),
+                field(Field::Location, 0),
+                field(Field::Thread, 0),
+                field(Field::Host, 0),
+                field(Field::Pid, 0),
+                field(Field::Process, 0),
+                field(Field::Scope, 0),
+            ]
+        );
+        assert_eq!(
+            pieces("{level:5}|{ target : 12 }|"),
+            vec![
+                field(Field::Level, 5),
+                literal("|"),
+                field(Field::Target, 12),
+                literal("|"),
+            ]
+        );
+        assert_eq!(pieces(""), vec![]);
+        assert_eq!(pieces("no placeholders"), vec![literal("no placeholders")]);
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_eq!(pieces("{{level}}"), vec![literal("{level}")]);
+        assert_eq!(
+            pieces("{{{level}}}"),
+            vec![literal("{"), field(Field::Level, 0), literal("}")]
+        );
+        assert_eq!(
+            pieces("a {{ b }} {message} {{"),
+            vec![literal("a { b } "), field(Field::Message, 0), literal(" {")]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_placeholders() {
+        assert_eq!(error("{lvl}"), "{lvl}");
+        assert_eq!(error("{time} {Level}"), "{Level}");
+        assert_eq!(error("{}"), "{}");
+        assert_eq!(error("{level:wide}"), "{level:wide}");
+        assert_eq!(error("{level:-1}"), "{level:-1}");
+        assert_eq!(error("[{level"), "{level");
+        assert_eq!(error("{message} }"), "}");
+        assert_eq!(
+            Template::parse("{lvl}").unwrap_err().to_string(),
+            "invalid template placeholder `{lvl}`"
+        );
+    }
+
+    #[test]
+    fn render_records() {
+        let record = Record::new(Level::Warn, "my_app::db", Message::from("Slow query"))
+            .with_location(Location::new("src/db.rs", 42))
+            .with_field(crate::Field::new("ms", 250));
+        let label = Theme::monochrome().label(Level::Warn).to_owned();
+
+        assert_eq!(
+            render(
+                "{time} [{level}] {target}: {message}",
+                &record,
+                Some("09:30:00")
+            ),
+            format!("09:30:00 [{}] my_app::db: Slow query", label)
+        );
+        assert_eq!(
+            render("{message} {fields} ({location})", &record, None),
+            "Slow query ms=250 (src/db.rs:42)"
+        );
+        assert_eq!(
+            render("[{level:8}|{target:12}|{time:10}]", &record, None),
+            format!("[{:<8}|my_app::db  |          ]", label)
+        );
+        assert_eq!(render("{{{target}}}", &record, None), "{my_app::db}");
+    }
+
+    #[test]
+    fn render_missing_values() {
+        let record = Record::new(Level::Info, "my_app", Message::from("Started"));
+        assert_eq!(
+            render("{time}|{location}|{fields}|{scope}", &record, None),
+            "|||"
+        );
+        assert_eq!(
+            render("[{location:4}] {message}", &record, None),
+            "[    ] Started"
+        );
+    }
+}
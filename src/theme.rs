@@ -0,0 +1,393 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::io;
+
+use bunt::termcolor::{Color, ColorSpec, WriteColor};
+use log::Level;
+use once_cell::sync::Lazy;
+
+use crate::indent::INDENT_WIDTH;
+use crate::{Kind, Record};
+
+/// The default labels of the levels, indexed by `level as usize - 1`.
+const LABELS: [&str; 5] = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+/// The default labels of the kinds, indexed by `kind as usize`.
+const KIND_LABELS: [&str; 3] = ["✓", "NOTE", "FATAL"];
+
+/// The icons of the levels, indexed by `level as usize - 1`.
+const ICONS: [&str; 5] = ["✗", "⚠", "ℹ", "⚙", "·"];
+/// The icons used where Unicode isn't supported.
+const ASCII_ICONS: [&str; 5] = ["x", "!", "i", "*", "."];
+
+/// Whether the terminal is likely to display the Unicode icons.
+static UNICODE: Lazy<bool> = Lazy::new(|| {
+    if cfg!(windows) {
+        env::var_os("WT_SESSION").is_some()
+            || env::var_os("TERM_PROGRAM").is_some_and(|v| v == "vscode")
+    } else {
+        env::var("TERM").ok().as_deref() != Some("linux")
+    }
+});
+
+/// The styles used for the parts of a record that bunt-logger prints itself.
+///
+/// The theme styles the level labels (see [`LogPrefs::show_level`](crate::LogPrefs::show_level)),
+/// the labels of records with a [`Kind`], the [fields](crate::Field) and
+/// [scopes](crate::enter_scope) of records, metadata such as the source location, and, if enabled,
+/// whole messages. It also holds the text of the labels, which can be replaced, e.g. to translate
+/// them.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{Color, ColorSpec, Level, Theme};
+///
+/// # fn main() {
+/// let mut info = ColorSpec::new();
+/// info.set_fg(Some(Color::Blue)).set_bold(true);
+///
+/// let theme = Theme::default()
+///     .with_style(Level::Info, info)
+///     .with_styled_messages(true);
+/// bunt_logger::with().theme(theme);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Indexed by `level as usize - 1`.
+    styles: [ColorSpec; 5],
+    /// Indexed by `kind as usize`.
+    kind_styles: [ColorSpec; 3],
+    field_style: ColorSpec,
+    meta_style: ColorSpec,
+    scope_style: ColorSpec,
+    styled_messages: bool,
+    icons: bool,
+    /// Indexed by `level as usize - 1`.
+    labels: [Cow<'static, str>; 5],
+    /// Indexed by `kind as usize`.
+    kind_labels: [Cow<'static, str>; 3],
+    label_width: Option<usize>,
+}
+
+impl Default for Theme {
+    /// Returns the default theme: bold red errors, bold yellow warnings, green info, cyan debug,
+    /// and dimmed white trace records, bold green successes, bold blue notices, bold white on red
+    /// fatal errors, dimmed fields and metadata, and bold magenta scopes.
+    fn default() -> Self {
+        let mut error = ColorSpec::new();
+        error.set_fg(Some(Color::Red)).set_bold(true);
+        let mut warn = ColorSpec::new();
+        warn.set_fg(Some(Color::Yellow)).set_bold(true);
+        let mut info = ColorSpec::new();
+        info.set_fg(Some(Color::Green));
+        let mut debug = ColorSpec::new();
+        debug.set_fg(Some(Color::Cyan));
+        let mut trace = ColorSpec::new();
+        trace.set_fg(Some(Color::White)).set_dimmed(true);
+
+        let mut success = ColorSpec::new();
+        success.set_fg(Some(Color::Green)).set_bold(true);
+        let mut notice = ColorSpec::new();
+        notice.set_fg(Some(Color::Blue)).set_bold(true);
+        let mut fatal = ColorSpec::new();
+        fatal
+            .set_fg(Some(Color::White))
+            .set_bg(Some(Color::Red))
+            .set_bold(true);
+
+        let mut field = ColorSpec::new();
+        field.set_dimmed(true);
+        let mut scope = ColorSpec::new();
+        scope.set_fg(Some(Color::Magenta)).set_bold(true);
+
+        Self {
+            styles: [error, warn, info, debug, trace],
+            kind_styles: [success, notice, fatal],
+            field_style: field.clone(),
+            meta_style: field,
+            scope_style: scope,
+            styled_messages: false,
+            icons: false,
+            labels: LABELS.map(Cow::Borrowed),
+            kind_labels: KIND_LABELS.map(Cow::Borrowed),
+            label_width: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns a theme without any colors, where only errors, warnings, scopes, and the labels of
+    /// records with a [`Kind`] are bold.
+    pub fn monochrome() -> Self {
+        let mut bold = ColorSpec::new();
+        bold.set_bold(true);
+
+        Self {
+            styles: [
+                bold.clone(),
+                bold.clone(),
+                ColorSpec::new(),
+                ColorSpec::new(),
+                ColorSpec::new(),
+            ],
+            kind_styles: [bold.clone(), bold.clone(), bold.clone()],
+            field_style: ColorSpec::new(),
+            meta_style: ColorSpec::new(),
+            scope_style: bold,
+            styled_messages: false,
+            icons: false,
+            labels: LABELS.map(Cow::Borrowed),
+            kind_labels: KIND_LABELS.map(Cow::Borrowed),
+            label_width: None,
+        }
+    }
+
+    /// Sets the style of records of `level`.
+    #[inline]
+    pub fn with_style(mut self, level: Level, style: ColorSpec) -> Self {
+        self.styles[level as usize - 1] = style;
+        self
+    }
+
+    /// Sets the style of records of `kind`.
+    #[inline]
+    pub fn with_kind_style(mut self, kind: Kind, style: ColorSpec) -> Self {
+        self.kind_styles[kind as usize] = style;
+        self
+    }
+
+    /// Sets the style of the fields printed after messages.
+    #[inline]
+    pub fn with_field_style(mut self, style: ColorSpec) -> Self {
+        self.field_style = style;
+        self
+    }
+
+    /// Sets the style of metadata, such as the source location of records.
+    #[inline]
+    pub fn with_meta_style(mut self, style: ColorSpec) -> Self {
+        self.meta_style = style;
+        self
+    }
+
+    /// Sets the style of the [scopes](crate::enter_scope) printed before messages.
+    #[inline]
+    pub fn with_scope_style(mut self, style: ColorSpec) -> Self {
+        self.scope_style = style;
+        self
+    }
+
+    /// Sets whether messages are printed in the style of their level as well. Styles inside the
+    /// message take precedence. Defaults to `false`.
+    #[inline]
+    pub fn with_styled_messages(mut self, styled: bool) -> Self {
+        self.styled_messages = styled;
+        self
+    }
+
+    /// Sets whether records are prefixed with an icon in the style of their level: `✗` for errors,
+    /// `⚠` for warnings, `ℹ` for info, `⚙` for debug and `·` for trace records. Records with a
+    /// [`Kind`] keep only their label. Where the terminal is unlikely to display these, such as on
+    /// the Linux console or in the legacy Windows console, `x`, `!`, `i`, `*` and `.` are used
+    /// instead. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::Theme;
+    ///
+    /// # fn main() {
+    /// bunt_logger::with().theme(Theme::default().with_icons(true));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_icons(mut self, icons: bool) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Sets the label printed for records of `level`, such as `"EROR"`, `"warning"` or a
+    /// translation, instead of its English name in uppercase. Only text output is affected; JSON
+    /// records keep the name of the level.
+    ///
+    /// Unless a [width](Self::with_label_width) is set, labels are padded to the longest level
+    /// label.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{Level, Theme};
+    ///
+    /// # fn main() {
+    /// let theme = Theme::default()
+    ///     .with_label(Level::Error, "FEHLER")
+    ///     .with_label(Level::Warn, "WARNUNG");
+    /// bunt_logger::with().show_level(true).theme(theme);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_label<T: Into<String>>(mut self, level: Level, label: T) -> Self {
+        self.labels[level as usize - 1] = Cow::Owned(label.into());
+        self
+    }
+
+    /// Sets the label printed for records of `kind`, which defaults to `✓`, `NOTE` or `FATAL`.
+    #[inline]
+    pub fn with_kind_label<T: Into<String>>(mut self, kind: Kind, label: T) -> Self {
+        self.kind_labels[kind as usize] = Cow::Owned(label.into());
+        self
+    }
+
+    /// Sets the number of characters that level labels are padded to, e.g. to line up records
+    /// with those of other programs. By default, they're padded to the longest level label.
+    #[inline]
+    pub fn with_label_width(mut self, width: usize) -> Self {
+        self.label_width = Some(width);
+        self
+    }
+
+    /// Returns the style of records of `level`.
+    #[inline]
+    pub fn style(&self, level: Level) -> &ColorSpec {
+        &self.styles[level as usize - 1]
+    }
+
+    /// Returns the style of records of `kind`.
+    #[inline]
+    pub fn kind_style(&self, kind: Kind) -> &ColorSpec {
+        &self.kind_styles[kind as usize]
+    }
+
+    /// Returns the style of the fields printed after messages.
+    #[inline]
+    pub fn field_style(&self) -> &ColorSpec {
+        &self.field_style
+    }
+
+    /// Returns the style of metadata, such as the source location of records.
+    #[inline]
+    pub fn meta_style(&self) -> &ColorSpec {
+        &self.meta_style
+    }
+
+    /// Returns the style of the scopes printed before messages.
+    #[inline]
+    pub fn scope_style(&self) -> &ColorSpec {
+        &self.scope_style
+    }
+
+    /// Returns whether messages are printed in the style of their level.
+    #[inline]
+    pub fn styled_messages(&self) -> bool {
+        self.styled_messages
+    }
+
+    /// Returns whether records are prefixed with an icon.
+    #[inline]
+    pub fn icons(&self) -> bool {
+        self.icons
+    }
+
+    /// Returns the label printed for records of `level`.
+    #[inline]
+    pub fn label(&self, level: Level) -> &str {
+        &self.labels[level as usize - 1]
+    }
+
+    /// Returns the label printed for records of `kind`.
+    #[inline]
+    pub fn kind_label(&self, kind: Kind) -> &str {
+        &self.kind_labels[kind as usize]
+    }
+
+    /// Returns the number of characters that level labels are padded to.
+    #[inline]
+    pub fn label_width(&self) -> usize {
+        self.label_width.unwrap_or_else(|| {
+            let widths = self.labels.iter().map(|label| label.chars().count());
+            widths.max().unwrap_or_default()
+        })
+    }
+
+    /// Returns the icon printed before `record`, if any.
+    #[inline]
+    pub(crate) fn icon(&self, record: &Record) -> Option<&'static str> {
+        if !self.icons || record.kind().is_some() {
+            return None;
+        }
+        let icons = if *UNICODE { &ICONS } else { &ASCII_ICONS };
+        Some(icons[record.level() as usize - 1])
+    }
+
+    /// Returns the label printed for `record`.
+    #[inline]
+    pub(crate) fn record_label(&self, record: &Record) -> &str {
+        match record.kind() {
+            Some(kind) => self.kind_label(kind),
+            None => self.label(record.level()),
+        }
+    }
+
+    /// Returns the style of the label and, if enabled, message of `record`.
+    #[inline]
+    pub(crate) fn style_for(&self, record: &Record) -> &ColorSpec {
+        match record.kind() {
+            Some(kind) => self.kind_style(kind),
+            None => self.style(record.level()),
+        }
+    }
+
+    /// Writes the message of `record`, indented and styled according to the theme.
+    #[inline]
+    pub(crate) fn write_message(
+        &self,
+        record: &Record,
+        writer: &mut dyn WriteColor,
+    ) -> io::Result<()> {
+        write!(writer, "{:1$}", "", record.indent() * INDENT_WIDTH)?;
+        if self.styled_messages {
+            record
+                .message()
+                .write_with_base(writer, self.style_for(record))
+        } else {
+            record.message().write_to(writer)
+        }
+    }
+
+    /// Writes the fields of `record` as space-separated `key=value` pairs, without a leading
+    /// space.
+    pub(crate) fn write_fields(
+        &self,
+        record: &Record,
+        writer: &mut dyn WriteColor,
+    ) -> io::Result<()> {
+        if record.fields().is_empty() {
+            return Ok(());
+        }
+        writer.set_color(&self.field_style)?;
+        for (i, field) in record.fields().iter().enumerate() {
+            if i > 0 {
+                write!(writer, " ")?;
+            }
+            write!(writer, "{}={}", field.key(), field.value())?;
+        }
+        writer.reset()
+    }
+
+    /// Writes `scope` in brackets in the scope style.
+    pub(crate) fn write_scope(&self, scope: &str, writer: &mut dyn WriteColor) -> io::Result<()> {
+        writer.set_color(&self.scope_style)?;
+        write!(writer, "[{}]", scope)?;
+        writer.reset()
+    }
+
+    /// Writes `meta` in the metadata style.
+    pub(crate) fn write_meta(
+        &self,
+        meta: &dyn fmt::Display,
+        writer: &mut dyn WriteColor,
+    ) -> io::Result<()> {
+        writer.set_color(&self.meta_style)?;
+        write!(writer, "{}", meta)?;
+        writer.reset()
+    }
+}
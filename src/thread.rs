@@ -0,0 +1,61 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use log::{Level, LevelFilter};
+
+thread_local! {
+    static THREAD_FILTER: Cell<Option<LevelFilter>> = const { Cell::new(None) };
+}
+
+/// Overrides the log level of the current thread, returning a guard that restores the previous
+/// level when dropped.
+///
+/// While the override is active, records logged by the thread through the bunt-logger macros are
+/// filtered by `level` alone instead of the global level and module directives; a
+/// [quiet](crate::LogPrefs::quiet) logger still prints nothing. Records from the `log` crate can
+/// only be made more verbose up to the global [`max_level`](crate::max_level), as `log` discards
+/// more verbose records before they reach bunt-logger.
+///
+/// # Example
+/// ```rust
+/// use std::thread;
+///
+/// use bunt_logger::{trace, Level};
+///
+/// # fn main() {
+/// thread::spawn(|| {
+///     let _level = bunt_logger::thread_level(Level::Trace);
+///     trace!("Only this thread logs {$italic}everything{/$}");
+/// })
+/// .join()
+/// .unwrap();
+/// # }
+/// ```
+#[inline]
+pub fn thread_level(level: Level) -> ThreadLevelGuard {
+    let previous = THREAD_FILTER.with(|filter| filter.replace(Some(level.to_level_filter())));
+    ThreadLevelGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+/// Returns the level override of the current thread, if any.
+#[inline]
+pub(crate) fn thread_filter() -> Option<LevelFilter> {
+    THREAD_FILTER.with(Cell::get)
+}
+
+/// A guard that restores the previous level of the thread when dropped; see [`thread_level`].
+#[must_use = "the level is restored as soon as the guard is dropped"]
+pub struct ThreadLevelGuard {
+    previous: Option<LevelFilter>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ThreadLevelGuard {
+    #[inline]
+    fn drop(&mut self) {
+        THREAD_FILTER.with(|filter| filter.set(self.previous));
+    }
+}
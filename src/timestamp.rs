@@ -0,0 +1,635 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+
+use crate::HumanDuration;
+
+/// The time at which the global preferences were first used.
+pub(crate) static START: Lazy<SystemTime> = Lazy::new(crate::clock::now);
+
+/// The time zone of timestamps; see [`LogPrefs::time_zone`](crate::LogPrefs::time_zone).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeZone {
+    /// Coordinated Universal Time, e.g. `2021-08-16T09:30:00Z`, so that logs of machines in
+    /// different time zones line up.
+    Utc,
+    /// The local time zone of the machine, with its UTC offset, e.g. `2021-08-16T11:30:00+02:00`.
+    ///
+    /// The offset is looked up for each record, so that changes to daylight saving time are
+    /// followed. Only supported on Unix; elsewhere, timestamps stay in UTC.
+    Local,
+}
+
+impl Default for TimeZone {
+    #[inline]
+    fn default() -> Self {
+        Self::Utc
+    }
+}
+
+impl TimeZone {
+    /// Returns the offset from UTC in seconds at `time`.
+    pub(crate) fn offset(self, time: SystemTime) -> i64 {
+        match self {
+            Self::Utc => 0,
+            Self::Local => {
+                local_offset(DateTime::new(time, Self::Utc).unix).map_or(0, |(offset, _)| offset)
+            }
+        }
+    }
+}
+
+/// The format of the timestamp printed before each record.
+///
+/// All timestamps other than [`Elapsed`](Self::Elapsed) are in the
+/// [time zone](crate::LogPrefs::time_zone) of the preferences, which is UTC by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// No timestamp is printed.
+    Off,
+    /// RFC 3339 with nanosecond precision, e.g. `2021-08-16T09:30:00.123456789Z`.
+    Rfc3339,
+    /// RFC 3339 with second precision, e.g. `2021-08-16T09:30:00Z`.
+    Seconds,
+    /// RFC 3339 with millisecond precision, e.g. `2021-08-16T09:30:00.123Z`.
+    Millis,
+    /// The time since the global preferences were first used, in seconds with millisecond
+    /// precision, e.g. `[  12.345s]`.
+    Elapsed,
+    /// A custom `strftime`-style format string, e.g. `"%H:%M:%S%.3f"`.
+    ///
+    /// The following sequences are supported:
+    ///
+    /// | Sequence | Meaning |
+    /// | --- | --- |
+    /// | `%Y`, `%C`, `%y` | Year, century, and two-digit year |
+    /// | `%m`, `%b`, `%h`, `%B` | Month as a number, abbreviated name, and full name |
+    /// | `%d`, `%e`, `%j` | Day of month (zero- and space-padded) and day of year |
+    /// | `%a`, `%A`, `%u`, `%w` | Weekday as an abbreviated name, full name, and number |
+    /// | `%H`, `%I`, `%p`, `%M`, `%S` | Hour (24- and 12-hour), AM/PM, minute, and second |
+    /// | `%f`, `%3f`, `%6f`, `%9f` | Fractional seconds with 9, 3, 6, and 9 digits |
+    /// | `%.f`, `%.3f`, `%.6f`, `%.9f` | Like the above, with a leading `.` |
+    /// | `%s` | Seconds since the Unix epoch |
+    /// | `%z`, `%:z`, `%Z` | UTC offset (e.g. `+0200`, `+02:00`) and time zone name (e.g. `CEST`) |
+    /// | `%F`, `%T`, `%D`, `%R` | `%Y-%m-%d`, `%H:%M:%S`, `%m/%d/%y`, and `%H:%M` |
+    /// | `%n`, `%t`, `%%` | Newline, tab, and a literal `%` |
+    ///
+    /// Unknown sequences are printed as-is.
+    Custom(String),
+}
+
+impl Default for TimestampFormat {
+    #[inline]
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+impl TimestampFormat {
+    /// Formats `time` in UTC, or returns `None` if timestamps are disabled.
+    #[inline]
+    pub(crate) fn format(&self, time: SystemTime) -> Option<String> {
+        self.format_in(time, TimeZone::Utc)
+    }
+
+    /// Formats `time` in `zone`, or returns `None` if timestamps are disabled.
+    pub(crate) fn format_in(&self, time: SystemTime, zone: TimeZone) -> Option<String> {
+        // RFC 3339 ends with `Z` in UTC and with the offset otherwise.
+        let (pattern, rfc3339) = match self {
+            Self::Off => return None,
+            Self::Rfc3339 => ("%Y-%m-%dT%H:%M:%S%.9f", true),
+            Self::Seconds => ("%Y-%m-%dT%H:%M:%S", true),
+            Self::Millis => ("%Y-%m-%dT%H:%M:%S%.3f", true),
+            Self::Elapsed => {
+                let elapsed = time.duration_since(*START).unwrap_or_default();
+                return Some(format!("[{:>8.3}s]", elapsed.as_secs_f64()));
+            }
+            Self::Custom(pattern) => (pattern.as_str(), false),
+        };
+
+        let time = DateTime::new(time, zone);
+        let mut out = String::new();
+        time.format_into(pattern, &mut out);
+        if rfc3339 {
+            match time.offset {
+                Some(_) => time.write_offset(true, &mut out),
+                None => out.push('Z'),
+            }
+        }
+        Some(out)
+    }
+}
+
+/// Measures the time between consecutive records; see
+/// [`LogPrefs::show_delta`](crate::LogPrefs::show_delta).
+#[derive(Debug, Clone)]
+pub(crate) struct Delta {
+    /// Deltas of at least this long are highlighted.
+    pub(crate) slow: Duration,
+    /// The time of the last record, shared between clones.
+    last: Arc<Mutex<SystemTime>>,
+}
+
+impl Delta {
+    #[inline]
+    pub(crate) fn new(slow: Duration) -> Self {
+        Self {
+            slow,
+            last: Arc::new(Mutex::new(*START)),
+        }
+    }
+
+    /// Returns the time since the last record, or since the global preferences were first used,
+    /// and makes `time` the time of the last record.
+    pub(crate) fn next(&self, time: SystemTime) -> Duration {
+        let mut last = self.last.lock().unwrap_or_else(|err| err.into_inner());
+        let delta = time.duration_since(*last).unwrap_or_default();
+        *last = time;
+        delta
+    }
+}
+
+/// Formats `delta` in the most readable unit, e.g. `+850µs`, `+3.2ms` or `+1.25s`.
+#[inline]
+pub(crate) fn format_delta(delta: Duration) -> String {
+    format!("+{}", HumanDuration(delta))
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// A broken-down date and time.
+struct DateTime {
+    unix: i64,
+    /// The offset from UTC in seconds, or `None` in UTC.
+    offset: Option<i64>,
+    /// The abbreviated name of the time zone, if it's local and known.
+    zone: Option<String>,
+    year: i64,
+    /// 1-based.
+    month: u32,
+    /// 1-based.
+    day: u32,
+    /// 1-based.
+    ordinal: u32,
+    /// Days since Sunday.
+    weekday: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+}
+
+impl DateTime {
+    fn new(time: SystemTime, zone: TimeZone) -> Self {
+        let (unix, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+            Err(e) => {
+                let d = e.duration();
+                match d.subsec_nanos() {
+                    0 => (-(d.as_secs() as i64), 0),
+                    n => (-(d.as_secs() as i64) - 1, 1_000_000_000 - n),
+                }
+            }
+        };
+
+        let (offset, zone) = match zone {
+            TimeZone::Utc => (None, None),
+            TimeZone::Local => match local_offset(unix) {
+                Some((offset, zone)) => (Some(offset), zone),
+                None => (None, None),
+            },
+        };
+        let local = unix + offset.unwrap_or(0);
+        let days = local.div_euclid(86_400);
+        let secs = local.rem_euclid(86_400) as u32;
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            unix,
+            offset,
+            zone,
+            year,
+            month,
+            day,
+            ordinal: (days - days_from_civil(year, 1, 1)) as u32 + 1,
+            // 1970-01-01 was a Thursday.
+            weekday: (days + 4).rem_euclid(7) as u32,
+            hour: secs / 3600,
+            minute: secs / 60 % 60,
+            second: secs % 60,
+            nanos,
+        }
+    }
+
+    fn format_into(&self, pattern: &str, out: &mut String) {
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+
+            let dot = chars.peek() == Some(&'.');
+            if dot {
+                chars.next();
+            }
+            let colon = !dot && chars.peek() == Some(&':');
+            if colon {
+                chars.next();
+            }
+            let digits = match chars.peek() {
+                Some(&d @ '3') | Some(&d @ '6') | Some(&d @ '9') if !colon => {
+                    chars.next();
+                    d.to_digit(10)
+                }
+                _ => None,
+            };
+
+            let spec = chars.next();
+            match (spec, dot || digits.is_some(), colon) {
+                (Some('f'), _, false) => {
+                    if dot {
+                        out.push('.');
+                    }
+                    self.write_fraction(digits.unwrap_or(9), out);
+                }
+                (Some('z'), false, true) => self.write_offset(true, out),
+                (Some(spec), false, false) => self.write_spec(spec, out),
+                _ => {
+                    // Print unknown sequences as written.
+                    out.push('%');
+                    if dot {
+                        out.push('.');
+                    }
+                    if colon {
+                        out.push(':');
+                    }
+                    if let Some(digits) = digits {
+                        let _ = write!(out, "{}", digits);
+                    }
+                    if let Some(spec) = spec {
+                        out.push(spec);
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_spec(&self, spec: char, out: &mut String) {
+        let _ = match spec {
+            'Y' => write!(out, "{:04}", self.year),
+            'C' => write!(out, "{:02}", self.year.div_euclid(100)),
+            'y' => write!(out, "{:02}", self.year.rem_euclid(100)),
+            'm' => write!(out, "{:02}", self.month),
+            'b' | 'h' => write!(out, "{}", &MONTHS[self.month as usize - 1][..3]),
+            'B' => write!(out, "{}", MONTHS[self.month as usize - 1]),
+            'd' => write!(out, "{:02}", self.day),
+            'e' => write!(out, "{:2}", self.day),
+            'j' => write!(out, "{:03}", self.ordinal),
+            'a' => write!(out, "{}", &WEEKDAYS[self.weekday as usize][..3]),
+            'A' => write!(out, "{}", WEEKDAYS[self.weekday as usize]),
+            'u' => write!(out, "{}", (self.weekday + 6) % 7 + 1),
+            'w' => write!(out, "{}", self.weekday),
+            'H' => write!(out, "{:02}", self.hour),
+            'I' => write!(out, "{:02}", (self.hour + 11) % 12 + 1),
+            'p' => write!(out, "{}", if self.hour < 12 { "AM" } else { "PM" }),
+            'M' => write!(out, "{:02}", self.minute),
+            'S' => write!(out, "{:02}", self.second),
+            's' => write!(out, "{}", self.unix),
+            'z' => {
+                self.write_offset(false, out);
+                Ok(())
+            }
+            'Z' => {
+                match (&self.zone, self.offset) {
+                    (Some(zone), _) => out.push_str(zone),
+                    (None, Some(_)) => self.write_offset(true, out),
+                    (None, None) => out.push_str("UTC"),
+                }
+                Ok(())
+            }
+            'F' => {
+                self.format_into("%Y-%m-%d", out);
+                Ok(())
+            }
+            'T' => {
+                self.format_into("%H:%M:%S", out);
+                Ok(())
+            }
+            'D' => {
+                self.format_into("%m/%d/%y", out);
+                Ok(())
+            }
+            'R' => {
+                self.format_into("%H:%M", out);
+                Ok(())
+            }
+            'n' | 't' | '%' => {
+                out.push(match spec {
+                    'n' => '\n',
+                    't' => '\t',
+                    _ => '%',
+                });
+                Ok(())
+            }
+            other => write!(out, "%{}", other),
+        };
+    }
+
+    /// Writes the offset from UTC, e.g. `+0200`, or `+02:00` with a `colon`.
+    fn write_offset(&self, colon: bool, out: &mut String) {
+        let offset = self.offset.unwrap_or(0);
+        let sign = if offset < 0 { '-' } else { '+' };
+        let minutes = offset.abs() / 60;
+        let colon = if colon { ":" } else { "" };
+        let _ = write!(
+            out,
+            "{}{:02}{}{:02}",
+            sign,
+            minutes / 60,
+            colon,
+            minutes % 60
+        );
+    }
+
+    #[inline]
+    fn write_fraction(&self, digits: u32, out: &mut String) {
+        let value = self.nanos / 10u32.pow(9 - digits);
+        let _ = write!(out, "{:0width$}", value, width = digits as usize);
+    }
+}
+
+/// Returns the offset from UTC in seconds and the abbreviated name of the local time zone at
+/// `unix`, or `None` if it's unknown.
+#[cfg(unix)]
+fn local_offset(unix: i64) -> Option<(i64, Option<String>)> {
+    use std::convert::TryFrom;
+
+    let time = libc::time_t::try_from(unix).ok()?;
+    // SAFETY: `tm` is plain data, for which zeros are valid.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: Both pointers are valid for the call.
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+
+    // The offset is derived from the broken-down time, since not all platforms have `tm_gmtoff`.
+    let days = days_from_civil(
+        i64::from(tm.tm_year) + 1900,
+        tm.tm_mon as u32 + 1,
+        tm.tm_mday as u32,
+    );
+    let local = days * 86_400
+        + i64::from(tm.tm_hour) * 3600
+        + i64::from(tm.tm_min) * 60
+        + i64::from(tm.tm_sec);
+    Some((local - unix, zone_name(&tm)))
+}
+
+/// Returns the abbreviated name of the time zone of `tm`.
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "aix",
+        target_os = "illumos",
+        target_os = "solaris",
+        target_env = "newlib"
+    ))
+))]
+fn zone_name(tm: &libc::tm) -> Option<String> {
+    if tm.tm_zone.is_null() {
+        return None;
+    }
+    // SAFETY: `tm_zone` points to a null-terminated string that lives until the time zone
+    // changes, and it's copied right away.
+    let zone = unsafe { std::ffi::CStr::from_ptr(tm.tm_zone) };
+    Some(zone.to_string_lossy().into_owned()).filter(|zone| !zone.is_empty())
+}
+
+/// Returns `None`, since `tm` has no time zone name on these platforms.
+#[cfg(any(
+    target_os = "aix",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_env = "newlib"
+))]
+#[inline]
+fn zone_name(_tm: &libc::tm) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn local_offset(_unix: i64) -> Option<(i64, Option<String>)> {
+    None
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian `(year, month, day)`.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The inverse of [`civil_from_days`].
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Returns the time `unix` seconds from the Unix epoch.
+    fn at(unix: i64) -> SystemTime {
+        match u64::try_from(unix) {
+            Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(unix.unsigned_abs()),
+        }
+    }
+
+    #[inline]
+    fn utc(unix: i64) -> DateTime {
+        DateTime::new(at(unix), TimeZone::Utc)
+    }
+
+    /// Returns `unix` broken down in a time zone `offset` seconds from UTC, named `zone`.
+    fn local(unix: i64, offset: i64, zone: Option<&str>) -> DateTime {
+        DateTime {
+            unix,
+            offset: Some(offset),
+            zone: zone.map(str::to_owned),
+            ..utc(unix + offset)
+        }
+    }
+
+    fn format(time: &DateTime, pattern: &str) -> String {
+        let mut out = String::new();
+        time.format_into(pattern, &mut out);
+        out
+    }
+
+    #[test]
+    fn count_days() {
+        for (unix, date, weekday, ordinal) in [
+            (0, (1970, 1, 1), 4, 1),
+            (-1, (1969, 12, 31), 3, 365),
+            (951_782_400, (2000, 2, 29), 2, 60),
+            (951_868_800, (2000, 3, 1), 3, 61),
+            (1_709_164_800, (2024, 2, 29), 4, 60),
+            (1_735_603_200, (2024, 12, 31), 2, 366),
+            (-2_203_977_600, (1900, 2, 28), 3, 59),
+            (-2_203_891_200, (1900, 3, 1), 4, 60),
+            (4_107_456_000, (2100, 2, 28), 0, 59),
+            (4_107_542_400, (2100, 3, 1), 1, 60),
+            (-11_670_998_400, (1600, 2, 29), 2, 60),
+            (-58_017_600, (1968, 2, 29), 4, 60),
+            (-62_135_596_800, (1, 1, 1), 1, 1),
+        ] {
+            let time = utc(unix);
+            assert_eq!((time.year, time.month, time.day), date, "{}", unix);
+            assert_eq!(time.weekday, weekday, "{}", unix);
+            assert_eq!(time.ordinal, ordinal, "{}", unix);
+            let days = days_from_civil(date.0, date.1, date.2);
+            assert_eq!(days, unix.div_euclid(86_400), "{}", unix);
+        }
+    }
+
+    #[test]
+    fn split_times_before_1970() {
+        let time = DateTime::new(UNIX_EPOCH - Duration::from_millis(1500), TimeZone::Utc);
+        assert_eq!((time.unix, time.nanos), (-2, 500_000_000));
+        assert_eq!(format(&time, "%F %T%.3f %s"), "1969-12-31 23:59:58.500 -2");
+        assert_eq!(format(&utc(-58_017_600), "%F %T"), "1968-02-29 12:00:00");
+    }
+
+    #[test]
+    fn format_specs() {
+        let time = DateTime::new(
+            at(1_629_106_200) + Duration::from_nanos(12_345_678),
+            TimeZone::Utc,
+        );
+        assert_eq!(
+            format(&time, "%Y %C %y %m %b %h %B %d %e %j"),
+            "2021 20 21 08 Aug Aug August 16 16 228"
+        );
+        assert_eq!(format(&time, "%a %A %u %w"), "Mon Monday 1 1");
+        assert_eq!(
+            format(&time, "%H %I %p %M %S %s"),
+            "09 09 AM 30 00 1629106200"
+        );
+        assert_eq!(
+            format(&time, "%f %3f %6f %9f"),
+            "012345678 012 012345 012345678"
+        );
+        assert_eq!(
+            format(&time, "%.f %.3f %.6f %.9f"),
+            ".012345678 .012 .012345 .012345678"
+        );
+        assert_eq!(
+            format(&time, "%F %T %D %R"),
+            "2021-08-16 09:30:00 08/16/21 09:30"
+        );
+        assert_eq!(format(&time, "a%nb%tc%%d"), "a\nb\tc%d");
+        assert_eq!(format(&time, "%z %:z %Z"), "+0000 +00:00 UTC");
+    }
+
+    #[test]
+    fn pad_specs() {
+        let time = utc(-62_135_596_800 + 3 * 3600);
+        assert_eq!(
+            format(&time, "%Y-%m-%d|%e|%j|%y|%C"),
+            "0001-01-01| 1|001|01|00"
+        );
+        assert_eq!(format(&time, "%H %I %p"), "03 03 AM");
+
+        let time = utc(1_735_603_200 + 23 * 3600 + 5);
+        assert_eq!(format(&time, "%j %H %I %p %M %S"), "366 23 11 PM 00 05");
+        assert_eq!(format(&utc(1_735_603_200 + 12 * 3600), "%I %p"), "12 PM");
+        assert_eq!(format(&utc(1_735_603_200), "%I %p %u %w"), "12 AM 2 2");
+        assert_eq!(format(&utc(4_107_456_000), "%a %u %w"), "Sun 7 0");
+    }
+
+    #[test]
+    fn format_offsets() {
+        let time = local(1_629_106_200, 2 * 3600, Some("CEST"));
+        assert_eq!(format(&time, "%T %z %:z %Z"), "11:30:00 +0200 +02:00 CEST");
+
+        // Without a name, `%Z` falls back to the offset.
+        let time = local(1_629_106_200 - 3600, -(9 * 3600 + 30 * 60), None);
+        assert_eq!(
+            format(&time, "%F %T %z %:z %Z"),
+            "2021-08-15 23:00:00 -0930 -09:30 -09:30"
+        );
+
+        let time = local(0, 5 * 3600 + 45 * 60, Some("+0545"));
+        assert_eq!(format(&time, "%F %R %z %Z"), "1970-01-01 05:45 +0545 +0545");
+        // `%s` stays in UTC.
+        assert_eq!(format(&time, "%s"), "0");
+    }
+
+    #[test]
+    fn keep_unknown_sequences() {
+        let time = utc(0);
+        assert_eq!(format(&time, "%q %.q %:Y %3Y %"), "%q %.q %:Y %3Y %");
+        assert_eq!(format(&time, "%:f %.z %3"), "%:f %.z %3");
+    }
+
+    #[test]
+    fn format_rfc3339() {
+        let time = at(1_629_106_200) + Duration::from_millis(123);
+        let rfc3339 = TimestampFormat::Rfc3339.format(time);
+        assert_eq!(rfc3339.unwrap(), "2021-08-16T09:30:00.123000000Z");
+        let seconds = TimestampFormat::Seconds.format(time);
+        assert_eq!(seconds.unwrap(), "2021-08-16T09:30:00Z");
+        let millis = TimestampFormat::Millis.format(time);
+        assert_eq!(millis.unwrap(), "2021-08-16T09:30:00.123Z");
+        let custom = TimestampFormat::Custom("%d.%m.%Y".to_owned()).format(time);
+        assert_eq!(custom.unwrap(), "16.08.2021");
+        assert_eq!(TimestampFormat::Off.format(time), None);
+    }
+}
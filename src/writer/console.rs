@@ -0,0 +1,169 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+use js_sys::Array;
+use log::Level;
+use wasm_bindgen::JsValue;
+
+use crate::color;
+
+/// A writer that logs lines to the console of the browser or JavaScript runtime, with their
+/// styles translated to CSS. Requires the `wasm` feature, and is only available when targeting
+/// `wasm32`.
+///
+/// Each line is passed to the console method for `level`: `console.error`, `console.warn`,
+/// `console.info`, or `console.debug` for debug and trace records, so that the developer tools
+/// can filter them. [`LogPrefs::console`](crate::LogPrefs::console) routes every level to its
+/// method.
+///
+/// # Example
+/// ```rust,ignore
+/// use bunt_logger::{ConsoleWriter, Level};
+///
+/// bunt_logger::with().writer(Box::new(ConsoleWriter::new(Level::Info)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConsoleWriter {
+    level: Level,
+    /// The line that is being written, as a format string with a `%c` where the style changes.
+    line: String,
+    /// The CSS of each `%c` in the line.
+    styles: Vec<String>,
+    /// The CSS of the current style, which carries over to the next line.
+    style: String,
+    /// The end of a UTF-8 sequence that was cut off by the previous write.
+    partial: Vec<u8>,
+}
+
+impl ConsoleWriter {
+    /// Returns a writer that logs to the console method for `level`.
+    #[inline]
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            line: String::new(),
+            styles: Vec::new(),
+            style: String::new(),
+            partial: Vec::new(),
+        }
+    }
+
+    /// Appends `text` to the line, logging every line that it ends.
+    fn push(&mut self, text: &str) {
+        let mut lines = text.split('\n');
+        if let Some(first) = lines.next() {
+            self.push_text(first);
+        }
+        for line in lines {
+            self.log_line();
+            if !self.style.is_empty() {
+                self.line.push_str("%c");
+                self.styles.push(self.style.clone());
+            }
+            self.push_text(line);
+        }
+    }
+
+    #[inline]
+    fn push_text(&mut self, text: &str) {
+        // A `%` would start a substitution in the format string.
+        self.line.push_str(&text.replace('%', "%%"));
+    }
+
+    /// Starts a new style for the text that follows.
+    fn set_style(&mut self, style: String) {
+        if style != self.style {
+            self.line.push_str("%c");
+            self.styles.push(style.clone());
+            self.style = style;
+        }
+    }
+
+    fn log_line(&mut self) {
+        let args: Array = std::iter::once(&self.line)
+            .chain(&self.styles)
+            .map(|s| JsValue::from_str(s))
+            .collect();
+        match self.level {
+            Level::Error => web_sys::console::error(&args),
+            Level::Warn => web_sys::console::warn(&args),
+            Level::Info => web_sys::console::info(&args),
+            Level::Debug | Level::Trace => web_sys::console::debug(&args),
+        }
+        self.line.clear();
+        self.styles.clear();
+    }
+}
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => {
+                let text = String::from_utf8_lossy(&self.partial).into_owned();
+                self.partial.clear();
+                self.push(&text);
+                return Ok(buf.len());
+            }
+        };
+        let rest = self.partial.split_off(valid);
+        let text = String::from_utf8(std::mem::replace(&mut self.partial, rest))
+            .expect("the bytes were validated");
+        self.push(&text);
+        Ok(buf.len())
+    }
+
+    /// Logs the unfinished line, if any.
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.log_line();
+        }
+        Ok(())
+    }
+}
+
+impl WriteColor for ConsoleWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.set_style(css(spec));
+        Ok(())
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.set_style(String::new());
+        Ok(())
+    }
+}
+
+/// Returns the CSS declarations for `spec`.
+fn css(spec: &ColorSpec) -> String {
+    let mut css = String::new();
+    if let Some((r, g, b)) = spec.fg().and_then(|&fg| color::to_rgb(fg, spec.intense())) {
+        let _ = write!(css, "color: rgb({}, {}, {});", r, g, b);
+    }
+    if let Some((r, g, b)) = spec.bg().and_then(|&bg| color::to_rgb(bg, spec.intense())) {
+        let _ = write!(css, "background-color: rgb({}, {}, {});", r, g, b);
+    }
+    if spec.bold() {
+        css.push_str("font-weight: bold;");
+    }
+    if spec.dimmed() {
+        css.push_str("opacity: 0.7;");
+    }
+    if spec.italic() {
+        css.push_str("font-style: italic;");
+    }
+    if spec.underline() {
+        css.push_str("text-decoration: underline;");
+    }
+    css
+}
@@ -0,0 +1,349 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bunt::termcolor::{Ansi, ColorSpec, WriteColor};
+
+/// Incremented by [`reopen_files`], so that file writers notice that they should reopen their
+/// files.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes every [`FileWriter`] and [`RotatingFileWriter`](crate::RotatingFileWriter) reopen its
+/// file before its next write, e.g. after `logrotate` moved the file away. The files are opened by
+/// path again, and created if they don't exist.
+///
+/// # Example
+/// ```rust
+/// # fn main() {
+/// bunt_logger::reopen_files();
+/// # }
+/// ```
+#[inline]
+pub fn reopen_files() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// How a [`FileWriter`] or [`RotatingFileWriter`](crate::RotatingFileWriter) buffers its output
+/// before writing it to the file.
+///
+/// Records are rendered in many small pieces, so without a buffer, each of them is a system call
+/// of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Buffering {
+    /// Every piece is written to the file right away.
+    None,
+    /// Output is written to the file at the end of every line, so that each record takes a single
+    /// write and shows up in the file as soon as it is logged.
+    Line,
+    /// Output is written to the file once this many bytes are buffered, up to the end of the
+    /// last complete line, or when the writer is [flushed](fn@crate::flush) or dropped. This is the
+    /// cheapest mode for verbose logging, but the last records are lost if the process is killed
+    /// before flushing.
+    Block(usize),
+}
+
+impl Default for Buffering {
+    #[inline]
+    fn default() -> Self {
+        Self::Line
+    }
+}
+
+/// How a [`FileWriter`] or [`RotatingFileWriter`](crate::RotatingFileWriter) opens its file.
+///
+/// By default, the file is appended to, created with the default permissions if it doesn't
+/// exist, and so are its parent directories.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::{FileOptions, FileWriter};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let options = FileOptions::new().truncate(true);
+/// #[cfg(unix)]
+/// let options = options.mode(0o600);
+/// let writer = FileWriter::open_with("logs/app.log", options)?;
+/// bunt_logger::with().writer(Box::new(writer));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOptions {
+    truncate: bool,
+    #[cfg(unix)]
+    mode: Option<u32>,
+    #[cfg(unix)]
+    lock: bool,
+    create_dirs: bool,
+}
+
+impl Default for FileOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            truncate: false,
+            #[cfg(unix)]
+            mode: None,
+            #[cfg(unix)]
+            lock: false,
+            create_dirs: true,
+        }
+    }
+}
+
+impl FileOptions {
+    /// Returns the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the file is truncated when it is first opened, rather than appended to.
+    /// Files are never truncated when they are reopened. Defaults to `false`.
+    #[inline]
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Sets the permissions of the file if it is created, such as `0o600` so that only its owner
+    /// can read it. They are restricted by the umask of the process, and rotated files keep them.
+    /// Defaults to `0o666`.
+    #[cfg(unix)]
+    #[inline]
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Sets whether every write holds an exclusive advisory lock on the file (`flock`), so that
+    /// records of processes that append to the same file never interleave, even where appending
+    /// writes aren't atomic, such as on network filesystems. The other processes have to lock the
+    /// file as well. Defaults to `false`.
+    #[cfg(unix)]
+    #[inline]
+    pub fn lock(mut self, lock: bool) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Sets whether missing parent directories of the file are created. Defaults to `true`.
+    #[inline]
+    pub fn create_dirs(mut self, create_dirs: bool) -> Self {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// Opens `path` with these options.
+    pub(crate) fn open(&self, path: &Path) -> io::Result<File> {
+        if self.create_dirs {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let mut options = OpenOptions::new();
+        options.create(true);
+        if self.truncate {
+            options.write(true).truncate(true);
+        } else {
+            options.append(true);
+        }
+        #[cfg(unix)]
+        if let Some(mode) = self.mode {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.mode(mode);
+        }
+        options.open(path)
+    }
+}
+
+/// A writer that appends to a file.
+///
+/// Styles are dropped by default, so that the file contains plain text; use
+/// [`ansi`](Self::ansi) to keep them as ANSI escape sequences instead. Output is
+/// [buffered](Self::buffering) by line. Use [`open_with`](Self::open_with) to truncate the file
+/// or to set its permissions instead.
+///
+/// Records without styles are written with a single system call each, so several processes can
+/// append to the same file without interleaving partial lines; see also
+/// [`FileOptions::lock`]. Rotation isn't coordinated between processes.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::FileWriter;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let writer = FileWriter::open("logs/app.log")?.ansi(true);
+/// bunt_logger::with().writer(Box::new(writer));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FileWriter {
+    path: PathBuf,
+    file: File,
+    /// The options for reopening the file, which never truncate it.
+    options: FileOptions,
+    ansi: bool,
+    buffering: Buffering,
+    /// Output that hasn't been written to the file yet.
+    buffer: Vec<u8>,
+    /// The value of [`GENERATION`] when the file was opened.
+    generation: usize,
+}
+
+impl FileWriter {
+    /// Opens `path` for appending, creating the file and its parent directories if necessary.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with(path, FileOptions::default())
+    }
+
+    /// Opens `path` with the given options.
+    pub fn open_with<P: AsRef<Path>>(path: P, options: FileOptions) -> io::Result<Self> {
+        let path = path.as_ref();
+        let generation = GENERATION.load(Ordering::Relaxed);
+        Ok(Self {
+            path: path.to_owned(),
+            file: options.open(path)?,
+            options: options.truncate(false),
+            ansi: false,
+            buffering: Buffering::default(),
+            buffer: Vec::new(),
+            generation,
+        })
+    }
+
+    /// Sets whether styles are written as ANSI escape sequences.
+    #[inline]
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    /// Sets how output is buffered. Defaults to [`Buffering::Line`].
+    #[inline]
+    pub fn buffering(mut self, buffering: Buffering) -> Self {
+        self.buffering = buffering;
+        self
+    }
+
+    /// Writes the first `len` buffered bytes to the file. They are dropped even if that fails, so
+    /// that a full disk doesn't make the buffer grow without bounds.
+    fn write_buffer(&mut self, len: usize) -> io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        let result = self.write_file(&self.buffer[..len]);
+        self.buffer.drain(..len);
+        result
+    }
+
+    /// Writes `buf` to the file, holding the lock on it if enabled.
+    fn write_file(&self, buf: &[u8]) -> io::Result<()> {
+        #[cfg(unix)]
+        if self.options.lock {
+            let fd = self.file.as_raw_fd();
+            // SAFETY: The file descriptor is open for the lifetime of `self.file`.
+            while unsafe { libc::flock(fd, libc::LOCK_EX) } != 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            let result = (&self.file).write_all(buf);
+            // SAFETY: As above; the lock is released with the descriptor anyway.
+            unsafe { libc::flock(fd, libc::LOCK_UN) };
+            return result;
+        }
+        (&self.file).write_all(buf)
+    }
+
+    /// Reopens the file if [`reopen_files`] was called since it was opened, returning whether it
+    /// was.
+    pub(crate) fn reopen_if_requested(&mut self) -> io::Result<bool> {
+        let generation = GENERATION.load(Ordering::Relaxed);
+        if generation == self.generation {
+            return Ok(false);
+        }
+        // Output buffered before the request still belongs to the old file.
+        self.write_buffer(self.buffer.len())?;
+        self.file = self.options.open(&self.path)?;
+        self.generation = generation;
+        Ok(true)
+    }
+
+    /// Returns the current size of the file, including buffered output.
+    #[inline]
+    pub(crate) fn len(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len() + self.buffer.len() as u64)
+    }
+}
+
+impl Write for FileWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.reopen_if_requested()?;
+        let len = match self.buffering {
+            Buffering::None => {
+                self.write_file(buf)?;
+                return Ok(buf.len());
+            }
+            Buffering::Line => match buf.iter().rposition(|&b| b == b'\n') {
+                Some(i) => self.buffer.len() + i + 1,
+                None => 0,
+            },
+            // Records are written whole, so that processes don't interleave them.
+            Buffering::Block(size) if self.buffer.len() + buf.len() >= size => {
+                match buf.iter().rposition(|&b| b == b'\n') {
+                    Some(i) => self.buffer.len() + i + 1,
+                    None => 0,
+                }
+            }
+            Buffering::Block(_) => 0,
+        };
+        self.buffer.extend_from_slice(buf);
+        self.write_buffer(len)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer(self.buffer.len())?;
+        self.file.flush()
+    }
+}
+
+impl Drop for FileWriter {
+    fn drop(&mut self) {
+        let _ = self.write_buffer(self.buffer.len());
+    }
+}
+
+impl WriteColor for FileWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.ansi
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        if self.ansi {
+            // Escape sequences go through the buffer, so that they stay in order with the text.
+            Ansi::new(self).set_color(spec)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        if self.ansi {
+            Ansi::new(self).reset()?;
+        }
+        Ok(())
+    }
+}
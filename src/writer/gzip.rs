@@ -0,0 +1,75 @@
+#[cfg(feature = "gzip")]
+use std::fs::{self, File};
+#[cfg(feature = "gzip")]
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+#[cfg(feature = "gzip")]
+use flate2::Compression;
+
+/// Returns the path of the compressed version of `path`.
+pub(crate) fn gz_path(path: &Path) -> PathBuf {
+    let mut path = path.to_owned().into_os_string();
+    path.push(".gz");
+    path.into()
+}
+
+/// Compresses the file at `path` to `path.gz`, and deletes it once that is complete.
+#[cfg(feature = "gzip")]
+pub(crate) fn compress(path: &Path) -> io::Result<()> {
+    let gz = gz_path(path);
+    let mut partial = gz.clone().into_os_string();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let result = File::open(path).and_then(|mut input| {
+        let output = File::create(&partial)?;
+        // The archive is as private as the file.
+        output.set_permissions(input.metadata()?.permissions())?;
+        let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        let mut output = encoder.finish()?;
+        output.flush()?;
+        output.get_ref().sync_all()
+    });
+    if let Err(err) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+    fs::rename(&partial, gz)?;
+    fs::remove_file(path)
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use std::fs;
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn compress_replaces_the_file() {
+        let dir = std::env::temp_dir().join(format!("bunt-logger-gzip-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in [
+            ("empty.log", Vec::new()),
+            ("large.log", b"A log line that repeats\n".repeat(4096)),
+        ] {
+            let path = dir.join(name);
+            fs::write(&path, &contents).unwrap();
+            compress(&path).unwrap();
+
+            assert!(!path.exists());
+            let mut decoded = Vec::new();
+            GzDecoder::new(File::open(gz_path(&path)).unwrap())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, contents);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
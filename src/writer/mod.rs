@@ -0,0 +1,23 @@
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod console;
+mod file;
+mod gzip;
+mod multi;
+mod net;
+mod non_blocking;
+mod output;
+mod rotating;
+mod shared;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub use self::console::ConsoleWriter;
+pub use self::file::{reopen_files, Buffering, FileOptions, FileWriter};
+pub use self::multi::MultiWriter;
+pub use self::net::NetWriter;
+pub use self::non_blocking::{NonBlocking, NonBlockingBuilder, Overflow, WorkerGuard};
+pub use self::output::Output;
+pub use self::rotating::{RotatingFileBuilder, RotatingFileWriter, Rotation};
+
+pub(crate) use self::net::{Client, Connection};
+pub(crate) use self::non_blocking::stop_workers;
+pub(crate) use self::shared::SharedWriter;
@@ -0,0 +1,105 @@
+use std::io::{self, Write};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+
+/// A writer that forwards everything written to it to several other writers.
+///
+/// Every writer receives all output. If some writers fail, the remaining ones are still written to
+/// and the first error is returned.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{ColorChoice, MultiWriter, StandardStream};
+///
+/// # fn main() {
+/// let mut writer = MultiWriter::new();
+/// writer.push(Box::new(StandardStream::stdout(ColorChoice::Auto)));
+/// writer.push(Box::new(StandardStream::stderr(ColorChoice::Never)));
+///
+/// bunt_logger::with().writer(Box::new(writer));
+/// # }
+/// ```
+#[derive(Default)]
+pub struct MultiWriter {
+    writers: Vec<Box<dyn WriteColor + Send>>,
+}
+
+impl MultiWriter {
+    /// Creates a writer without any targets.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a target.
+    #[inline]
+    pub fn push(&mut self, writer: Box<dyn WriteColor + Send>) {
+        self.writers.push(writer);
+    }
+
+    /// Returns the number of targets.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.writers.len()
+    }
+
+    /// Returns whether there are no targets.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.writers.is_empty()
+    }
+
+    /// Calls `f` on every target, returning the first error.
+    fn for_each<F>(&mut self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&mut Box<dyn WriteColor + Send>) -> io::Result<()>,
+    {
+        let mut result = Ok(());
+        for writer in &mut self.writers {
+            let next = f(writer);
+            if result.is_ok() {
+                result = next;
+            }
+        }
+        result
+    }
+}
+
+impl From<Box<dyn WriteColor + Send>> for MultiWriter {
+    #[inline]
+    fn from(writer: Box<dyn WriteColor + Send>) -> Self {
+        Self {
+            writers: vec![writer],
+        }
+    }
+}
+
+impl Write for MultiWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.for_each(|writer| writer.write_all(buf))?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.for_each(|writer| writer.flush())
+    }
+}
+
+impl WriteColor for MultiWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.writers.iter().any(|writer| writer.supports_color())
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.for_each(|writer| writer.set_color(spec))
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.for_each(|writer| writer.reset())
+    }
+}
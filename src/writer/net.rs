@@ -0,0 +1,273 @@
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+
+/// How long connecting and sending may take before they are given up.
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// The delay before the first reconnection attempt, doubled after every failed one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// The number of bytes kept while disconnected; older lines are dropped beyond it.
+const DEFAULT_MAX_PENDING: usize = 1024 * 1024;
+
+/// A connection made by a [`Client`].
+#[derive(Debug)]
+pub(crate) enum Connection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+}
+
+/// A TCP or UDP client that connects on demand and reconnects after errors, waiting longer after
+/// every failed attempt.
+#[derive(Debug)]
+pub(crate) struct Client {
+    addrs: Vec<SocketAddr>,
+    udp: bool,
+    connection: Option<Connection>,
+    backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    /// The earliest time at which to reconnect after a failure.
+    retry_at: Option<Instant>,
+}
+
+impl Client {
+    /// Creates a client for `addr`, failing if the address can't be resolved.
+    pub(crate) fn new<A: ToSocketAddrs>(addr: A, udp: bool) -> io::Result<Self> {
+        let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the address resolved to nothing",
+            ));
+        }
+        Ok(Self {
+            addrs,
+            udp,
+            connection: None,
+            backoff: INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            retry_at: None,
+        })
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        let mut last_err = None;
+        for addr in &self.addrs {
+            let result = if self.udp {
+                let local: SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                UdpSocket::bind(local)
+                    .and_then(|socket| socket.connect(addr).map(|()| Connection::Udp(socket)))
+            } else {
+                TcpStream::connect_timeout(addr, TIMEOUT).and_then(|stream| {
+                    stream.set_write_timeout(Some(TIMEOUT))?;
+                    Ok(Connection::Tcp(stream))
+                })
+            };
+            match result {
+                Ok(connection) => return Ok(connection),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.expect("there is at least one address"))
+    }
+
+    /// Sends with `send` over the connection, connecting first if necessary. Returns `false`
+    /// without sending while waiting to reconnect.
+    pub(crate) fn send<F>(&mut self, send: F) -> io::Result<bool>
+    where
+        F: FnOnce(&mut Connection) -> io::Result<()>,
+    {
+        if self.connection.is_none() {
+            if self.retry_at.is_some_and(|at| Instant::now() < at) {
+                return Ok(false);
+            }
+            match self.connect() {
+                Ok(connection) => self.connection = Some(connection),
+                Err(err) => {
+                    self.fail();
+                    return Err(err);
+                }
+            }
+        }
+
+        let connection = self.connection.as_mut().expect("connected above");
+        match send(connection) {
+            Ok(()) => {
+                self.backoff = INITIAL_BACKOFF;
+                self.retry_at = None;
+                Ok(true)
+            }
+            Err(err) => {
+                self.connection = None;
+                self.fail();
+                Err(err)
+            }
+        }
+    }
+
+    /// Schedules the next attempt to reconnect.
+    fn fail(&mut self) {
+        self.retry_at = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+    }
+
+    /// Flushes the connection, if any.
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        match self.connection.as_mut() {
+            Some(Connection::Tcp(stream)) => stream.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A writer that ships rendered records over the network, to a collector such as rsyslog or
+/// Vector.
+///
+/// Output is sent line by line: over TCP as a stream of newline-terminated lines, and over UDP as
+/// one datagram per line, without the newline. Use [`Format::Json`](crate::Format::Json) to keep
+/// records with multi-line messages on a single line. Styles are dropped.
+///
+/// The connection is made on the first write and remade after errors, waiting longer after every
+/// failed attempt, up to [`max_backoff`](Self::max_backoff). Meanwhile, output is kept in memory
+/// up to a limit, dropping the oldest lines beyond it. Writes never fail, so that records aren't
+/// cut off; instead, errors are returned from the next [`flush`](Write::flush). Connecting and
+/// sending can block for up to 5 seconds, so the writer is best wrapped in a
+/// [`NonBlocking`](crate::NonBlocking).
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::{Format, NetWriter, NonBlocking, Output};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (writer, _guard) = NonBlocking::new(Box::new(NetWriter::tcp("127.0.0.1:9000")?));
+/// bunt_logger::with().add_output(Output::new(Box::new(writer)).with_format(Format::Json));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NetWriter {
+    client: Client,
+    /// Output that hasn't been sent yet.
+    pending: Vec<u8>,
+    max_pending: usize,
+    /// The first error since the last flush.
+    error: Option<io::Error>,
+}
+
+impl NetWriter {
+    /// Creates a writer that sends output over TCP to `addr`, e.g. `"logs.internal:5140"`.
+    /// Fails if the address can't be resolved.
+    #[inline]
+    pub fn tcp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(addr, false)
+    }
+
+    /// Creates a writer that sends output over UDP to `addr`, one datagram per line. Fails if the
+    /// address can't be resolved.
+    #[inline]
+    pub fn udp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(addr, true)
+    }
+
+    fn new<A: ToSocketAddrs>(addr: A, udp: bool) -> io::Result<Self> {
+        Ok(Self {
+            client: Client::new(addr, udp)?,
+            pending: Vec::new(),
+            max_pending: DEFAULT_MAX_PENDING,
+            error: None,
+        })
+    }
+
+    /// Sets the longest time to wait between attempts to reconnect. Defaults to 30 seconds.
+    #[inline]
+    pub fn max_backoff(mut self, max: Duration) -> Self {
+        self.client.max_backoff = max;
+        self
+    }
+
+    /// Sets the number of bytes of output kept while disconnected. Defaults to 1 MiB.
+    #[inline]
+    pub fn max_pending(mut self, max: usize) -> Self {
+        self.max_pending = max;
+        self
+    }
+
+    /// Sends the complete lines of the pending output. Output is kept while waiting to reconnect.
+    fn send(&mut self) -> io::Result<()> {
+        let end = match self.pending.iter().rposition(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None => return Ok(()),
+        };
+        let lines = &self.pending[..end];
+        let result = self.client.send(|connection| match connection {
+            Connection::Tcp(stream) => stream.write_all(lines),
+            Connection::Udp(socket) => lines
+                .split(|&b| b == b'\n')
+                .filter(|line| !line.is_empty())
+                .try_for_each(|line| socket.send(line).map(drop)),
+        });
+        if let Ok(true) = result {
+            self.pending.drain(..end);
+        } else {
+            self.trim_pending();
+        }
+        result.map(drop)
+    }
+
+    /// Drops the oldest lines of the pending output beyond the limit.
+    fn trim_pending(&mut self) {
+        if self.pending.len() <= self.max_pending {
+            return;
+        }
+        let excess = self.pending.len() - self.max_pending;
+        let end = self.pending[excess..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.pending.len(), |i| excess + i + 1);
+        self.pending.drain(..end);
+    }
+}
+
+impl Write for NetWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        if buf.contains(&b'\n') {
+            if let Err(err) = self.send() {
+                self.error.get_or_insert(err);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.send();
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        result?;
+        self.client.flush()
+    }
+}
+
+impl WriteColor for NetWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn set_color(&mut self, _spec: &ColorSpec) -> io::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
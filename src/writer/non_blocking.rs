@@ -0,0 +1,460 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+use once_cell::sync::Lazy;
+
+use crate::Message;
+
+/// The number of records that may be queued before the [overflow policy](Overflow) applies.
+const DEFAULT_CAPACITY: usize = 128_000;
+
+enum Command {
+    Write(Message),
+    /// Stands in for this many records that were dropped.
+    Dropped(u64),
+    Flush,
+    Shutdown,
+}
+
+/// What a [`NonBlocking`] writer does with a record when its queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Logging waits until the writer thread has caught up, so that no record is lost.
+    Block,
+    /// The new record is dropped, keeping those that are already queued.
+    DropNewest,
+    /// The oldest queued record is dropped to make room for the new one, so that the most recent
+    /// records are kept.
+    DropOldest,
+}
+
+impl Default for Overflow {
+    #[inline]
+    fn default() -> Self {
+        Self::Block
+    }
+}
+
+/// A writer that hands output off to a dedicated thread, so that logging doesn't stall on slow
+/// targets such as pipes or network filesystems.
+///
+/// Output is queued record by record and written to the wrapped writer in order. Once 128,000
+/// records are queued, logging blocks until the thread catches up, unless the
+/// [builder](Self::builder) sets another [`Overflow`] policy; dropped records are counted, and a
+/// line with their number is written in their place. The writer stops once the [`WorkerGuard`]
+/// returned alongside it is dropped, after writing and flushing everything that was queued; keep
+/// the guard alive for as long as records should be written.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{info, ColorChoice, NonBlocking, StandardStream};
+///
+/// # fn main() {
+/// let stderr = StandardStream::stderr(ColorChoice::Auto);
+/// let (writer, _guard) = NonBlocking::new(Box::new(stderr));
+/// bunt_logger::with().writer(Box::new(writer));
+///
+/// info!("Written from the background thread");
+/// # }
+/// ```
+pub struct NonBlocking {
+    queue: Arc<Queue>,
+    supports_color: bool,
+    line: Message,
+}
+
+impl NonBlocking {
+    /// Spawns the thread that writes to `writer`, with the default capacity and
+    /// [`Overflow::Block`].
+    #[inline]
+    pub fn new(writer: Box<dyn WriteColor + Send>) -> (Self, WorkerGuard) {
+        Self::builder(writer)
+            .spawn()
+            .expect("failed to spawn the bunt-logger writer thread")
+    }
+
+    /// Returns a builder for a writer that writes to `writer`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bunt_logger::{ColorChoice, NonBlocking, Overflow, StandardStream};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let stderr = StandardStream::stderr(ColorChoice::Auto);
+    /// let (writer, _guard) = NonBlocking::builder(Box::new(stderr))
+    ///     .capacity(10_000)
+    ///     .overflow(Overflow::DropOldest)
+    ///     .spawn()?;
+    /// bunt_logger::with().writer(Box::new(writer));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn builder(writer: Box<dyn WriteColor + Send>) -> NonBlockingBuilder {
+        NonBlockingBuilder {
+            writer,
+            capacity: DEFAULT_CAPACITY,
+            overflow: Overflow::default(),
+        }
+    }
+
+    /// Queues the buffered line, carrying its last style over to the next one.
+    fn send_line(&mut self) -> io::Result<()> {
+        let style = self.line.current_style().cloned();
+        let line = mem::take(&mut self.line);
+        if let Some(spec) = style {
+            self.line.set_color(&spec)?;
+        }
+        self.queue.push(Command::Write(line))
+    }
+}
+
+impl Write for NonBlocking {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.write_all(buf)?;
+        if buf.ends_with(b"\n") {
+            self.send_line()?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Queues a flush of the wrapped writer, without waiting for it.
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.send_line()?;
+        }
+        self.queue.push(Command::Flush)
+    }
+}
+
+impl WriteColor for NonBlocking {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.supports_color
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.line.set_color(spec)
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.line.reset()
+    }
+}
+
+/// A builder for a [`NonBlocking`] writer.
+pub struct NonBlockingBuilder {
+    writer: Box<dyn WriteColor + Send>,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl NonBlockingBuilder {
+    /// Sets the number of records that may be queued. Defaults to 128,000.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets what happens to records when the queue is full. Defaults to [`Overflow::Block`].
+    #[inline]
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Spawns the thread that writes to the writer.
+    pub fn spawn(self) -> io::Result<(NonBlocking, WorkerGuard)> {
+        let supports_color = self.writer.supports_color();
+        let queue = Arc::new(Queue {
+            state: Mutex::new(State {
+                commands: VecDeque::new(),
+                records: 0,
+                stopped: false,
+            }),
+            pushed: Condvar::new(),
+            popped: Condvar::new(),
+            capacity: self.capacity,
+            overflow: self.overflow,
+        });
+        let writer = self.writer;
+        let worker_queue = Arc::clone(&queue);
+        let handle = thread::Builder::new()
+            .name("bunt-logger".to_owned())
+            .spawn(move || work(writer, &worker_queue))?;
+
+        let stop_queue = Arc::clone(&queue);
+        let writer = NonBlocking {
+            queue,
+            supports_color,
+            line: Message::new(),
+        };
+        let guard = WorkerGuard::new(
+            Box::new(move || {
+                let _ = stop_queue.push(Command::Shutdown);
+            }),
+            handle,
+        );
+        Ok((writer, guard))
+    }
+}
+
+/// The queue between a [`NonBlocking`] writer and its thread.
+struct Queue {
+    state: Mutex<State>,
+    /// Notified when a command is queued.
+    pushed: Condvar,
+    /// Notified when a record is taken off the queue, or the thread stops.
+    popped: Condvar,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+struct State {
+    commands: VecDeque<Command>,
+    /// The number of records among the commands.
+    records: usize,
+    /// Whether the thread has stopped, so that nothing waits for it anymore.
+    stopped: bool,
+}
+
+impl Queue {
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Queues `command`, applying the overflow policy to records if the queue is full. Flushes and
+    /// shutdowns are always queued.
+    fn push(&self, command: Command) -> io::Result<()> {
+        let mut state = self.lock();
+        let is_record = matches!(command, Command::Write(_));
+        while is_record && state.records >= self.capacity && !state.stopped {
+            match self.overflow {
+                Overflow::Block => {
+                    state = self
+                        .popped
+                        .wait(state)
+                        .unwrap_or_else(|err| err.into_inner());
+                }
+                Overflow::DropNewest => {
+                    match state.commands.back_mut() {
+                        Some(Command::Dropped(dropped)) => *dropped += 1,
+                        _ => state.commands.push_back(Command::Dropped(1)),
+                    }
+                    return Ok(());
+                }
+                Overflow::DropOldest => {
+                    let oldest = state
+                        .commands
+                        .iter()
+                        .position(|command| matches!(command, Command::Write(_)));
+                    if let Some(i) = oldest {
+                        state.commands.remove(i);
+                        state.records -= 1;
+                        // The count goes where the dropped record was.
+                        match i.checked_sub(1).and_then(|i| state.commands.get_mut(i)) {
+                            Some(Command::Dropped(dropped)) => *dropped += 1,
+                            _ => state.commands.insert(i, Command::Dropped(1)),
+                        }
+                    }
+                }
+            }
+        }
+        if state.stopped {
+            return Err(disconnected());
+        }
+        state.records += is_record as usize;
+        state.commands.push_back(command);
+        self.pushed.notify_one();
+        Ok(())
+    }
+
+    /// Waits for the next command.
+    fn pop(&self) -> Command {
+        let mut state = self.lock();
+        loop {
+            if let Some(command) = state.commands.pop_front() {
+                if matches!(command, Command::Write(_)) {
+                    state.records -= 1;
+                    self.popped.notify_one();
+                }
+                return command;
+            }
+            state = self
+                .pushed
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+}
+
+/// Marks the queue as stopped when the thread exits, even by panicking, so that blocked writers
+/// are released.
+struct StopOnDrop<'a>(&'a Queue);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.lock().stopped = true;
+        self.0.popped.notify_all();
+    }
+}
+
+/// Asks a worker thread to stop.
+type Stop = Box<dyn FnOnce() + Send>;
+
+/// The worker threads that haven't been stopped by their guard, for [`shutdown`](crate::shutdown).
+static WORKERS: Lazy<Mutex<Vec<Weak<Worker>>>> = Lazy::new(Default::default);
+
+/// A worker thread, shared between its guard and [`WORKERS`].
+struct Worker {
+    stop: Mutex<Option<Stop>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Worker {
+    /// Asks the thread to stop, unless it already was.
+    fn stop(&self) {
+        let stop = self
+            .stop
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(stop) = stop {
+            stop();
+        }
+    }
+
+    /// Waits for the thread to finish, unless it already was waited for.
+    fn join(&self) {
+        let handle = self
+            .handle
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+    }
+
+    #[inline]
+    fn is_finished(&self) -> bool {
+        let handle = self.handle.lock().unwrap_or_else(|err| err.into_inner());
+        !matches!(&*handle, Some(handle) if !handle.is_finished())
+    }
+}
+
+/// Stops the threads of all [`WorkerGuard`]s that are still alive, returning whether they all
+/// finished before `deadline`.
+pub(crate) fn stop_workers(deadline: Instant) -> bool {
+    let workers: Vec<_> = WORKERS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .drain(..)
+        .filter_map(|worker| worker.upgrade())
+        .collect();
+    for worker in &workers {
+        worker.stop();
+    }
+    loop {
+        let finished = workers.iter().all(|worker| worker.is_finished());
+        if finished || Instant::now() >= deadline {
+            for worker in workers.iter().filter(|worker| worker.is_finished()) {
+                worker.join();
+            }
+            return finished;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Stops the thread of a [`NonBlocking`] writer or an [`HttpSink`](crate::HttpSink) when dropped,
+/// after it has written and flushed all queued output.
+///
+/// [`shutdown`](crate::shutdown) stops the threads of all guards that are alive without waiting
+/// longer than a timeout, for programs that exit without unwinding.
+#[must_use = "the writer thread stops as soon as the guard is dropped"]
+pub struct WorkerGuard {
+    workers: Vec<Arc<Worker>>,
+}
+
+impl WorkerGuard {
+    /// Returns a guard that calls `stop` and then waits for the thread of `handle` to finish.
+    pub(crate) fn new(stop: Stop, handle: JoinHandle<()>) -> Self {
+        let worker = Arc::new(Worker {
+            stop: Mutex::new(Some(stop)),
+            handle: Mutex::new(Some(handle)),
+        });
+        let mut workers = WORKERS.lock().unwrap_or_else(|err| err.into_inner());
+        workers.retain(|worker| worker.strong_count() > 0);
+        workers.push(Arc::downgrade(&worker));
+        Self {
+            workers: vec![worker],
+        }
+    }
+
+    /// Returns a guard without any threads.
+    #[inline]
+    pub(crate) fn empty() -> Self {
+        Self {
+            workers: Vec::new(),
+        }
+    }
+
+    /// Makes the guard stop the threads of `other` as well.
+    #[inline]
+    pub(crate) fn merge(&mut self, mut other: WorkerGuard) {
+        self.workers.append(&mut other.workers);
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        for worker in &self.workers {
+            worker.stop();
+        }
+        for worker in &self.workers {
+            worker.join();
+        }
+    }
+}
+
+fn work(mut writer: Box<dyn WriteColor + Send>, queue: &Queue) {
+    let _stop = StopOnDrop(queue);
+    loop {
+        match queue.pop() {
+            Command::Write(line) => {
+                let _ = line.write_to(&mut writer);
+            }
+            Command::Dropped(dropped) => {
+                let _ = writeln!(
+                    writer,
+                    "bunt-logger: dropped {} record{} because the writer fell behind",
+                    dropped,
+                    if dropped == 1 { "" } else { "s" }
+                );
+            }
+            Command::Flush => {
+                let _ = writer.flush();
+            }
+            Command::Shutdown => break,
+        }
+    }
+    let _ = writer.flush();
+}
+
+#[inline]
+fn disconnected() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        "the bunt-logger writer thread has stopped",
+    )
+}
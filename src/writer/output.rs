@@ -0,0 +1,134 @@
+use std::sync::MutexGuard;
+
+use bunt::termcolor::{ColorChoice, StandardStream, WriteColor};
+use log::{Level, LevelFilter};
+
+use crate::color::Stream;
+use crate::writer::SharedWriter;
+use crate::{Format, Theme};
+
+/// A logging target together with its own output settings, see
+/// [`LogPrefs::add_output`](crate::LogPrefs::add_output).
+///
+/// Settings that aren't set on the output are taken from the preferences, so an output created
+/// with [`new`](Self::new) alone behaves like a writer passed to
+/// [`add_writer`](crate::LogPrefs::add_writer). The output only receives records that pass both
+/// the filters of the preferences and its own.
+///
+/// # Example
+/// ```rust
+/// use bunt_logger::{ColorChoice, Format, Level, LevelFilter, Output, StandardStream};
+///
+/// # fn main() {
+/// let stderr = StandardStream::stderr(ColorChoice::Auto);
+/// let stdout = StandardStream::stdout(ColorChoice::Never);
+/// bunt_logger::with()
+///     .level(Level::Debug)
+///     .writer(Box::new(stdout))
+///     .add_output(Output::new(Box::new(stderr)).with_filter(LevelFilter::Warn));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Output {
+    writer: SharedWriter,
+    format: Option<Format>,
+    theme: Option<Theme>,
+    filter: LevelFilter,
+    /// The standard stream that the output writes to, if it's known.
+    stream: Option<Stream>,
+}
+
+impl Output {
+    /// Creates an output that writes to `writer` with the settings of the preferences.
+    #[inline]
+    pub fn new(writer: Box<dyn WriteColor + Send>) -> Self {
+        Self {
+            writer: SharedWriter::new(writer),
+            format: None,
+            theme: None,
+            filter: LevelFilter::Trace,
+            stream: None,
+        }
+    }
+
+    /// Creates an output that writes to `stream` with the resolved `color`.
+    #[inline]
+    pub(crate) fn standard(stream: Stream, color: ColorChoice) -> Self {
+        let writer = match stream {
+            Stream::Stdout => StandardStream::stdout(color),
+            Stream::Stderr => StandardStream::stderr(color),
+        };
+        Self {
+            stream: Some(stream),
+            ..Self::new(Box::new(writer))
+        }
+    }
+
+    /// Sets the format of the records written to the output, overriding
+    /// [`LogPrefs::format`](crate::LogPrefs::format).
+    #[inline]
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets the theme of the records written to the output, overriding
+    /// [`LogPrefs::theme`](crate::LogPrefs::theme).
+    #[inline]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Sets the most verbose level of the records written to the output. Defaults to
+    /// [`LevelFilter::Trace`], so that only the filters of the preferences apply.
+    #[inline]
+    pub fn with_filter(mut self, filter: LevelFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns whether records of `level` are written to the output.
+    #[inline]
+    pub(crate) fn enabled(&self, level: Level) -> bool {
+        self.filter >= level
+    }
+
+    /// Returns the format of the output, if it overrides that of the preferences.
+    #[inline]
+    pub(crate) fn format(&self) -> Option<Format> {
+        self.format
+    }
+
+    /// Returns the theme of the output, if it overrides that of the preferences.
+    #[inline]
+    pub(crate) fn theme(&self) -> Option<&Theme> {
+        self.theme.as_ref()
+    }
+
+    /// Returns the standard stream that the output writes to, if it's known.
+    #[inline]
+    pub(crate) fn stream(&self) -> Option<Stream> {
+        self.stream
+    }
+
+    /// Returns an output with the same settings that writes to `writer` instead.
+    #[inline]
+    pub(crate) fn replace_writer(&self, writer: Box<dyn WriteColor + Send>) -> Self {
+        Self {
+            writer: SharedWriter::new(writer),
+            stream: None,
+            ..self.clone()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Box<dyn WriteColor + Send>> {
+        self.writer.lock()
+    }
+
+    #[inline]
+    pub(crate) fn into_writer(self) -> SharedWriter {
+        self.writer
+    }
+}
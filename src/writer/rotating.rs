@@ -0,0 +1,754 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+
+use super::gzip::gz_path;
+use super::{Buffering, FileOptions, FileWriter};
+use crate::{TimeZone, TimestampFormat};
+
+/// When a [`RotatingFileWriter`] starts a new file regardless of its size.
+///
+/// Periods start at calendar boundaries in the [time zone](RotatingFileBuilder::time_zone) of the
+/// writer, UTC by default, and each one is written to a file with its start
+/// in the name, before the extension: `app.log` becomes `app-2024-05-01.log` for daily rotation,
+/// and `app-2024-05-01-13.log` for hourly rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Files are only rotated by size, and the path is used as-is.
+    Never,
+    /// A new file is started every hour.
+    Hourly,
+    /// A new file is started every day at midnight.
+    Daily,
+}
+
+impl Default for Rotation {
+    #[inline]
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+impl Rotation {
+    /// Returns the number of the period in `zone` that contains `time`, counted from the Unix
+    /// epoch.
+    fn period(self, time: SystemTime, zone: TimeZone) -> i64 {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64)
+            + zone.offset(time);
+        match self {
+            Self::Never => 0,
+            Self::Hourly => secs.div_euclid(3600),
+            Self::Daily => secs.div_euclid(86400),
+        }
+    }
+
+    /// Returns the path of the file for `time` in `zone`, derived from `path`.
+    fn path(self, path: &Path, time: SystemTime, zone: TimeZone) -> PathBuf {
+        let format = match self {
+            Self::Never => return path.to_owned(),
+            Self::Hourly => "%Y-%m-%d-%H",
+            Self::Daily => "%Y-%m-%d",
+        };
+        let date = TimestampFormat::Custom(format.to_owned())
+            .format_in(time, zone)
+            .unwrap_or_default();
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push("-");
+        name.push(date);
+        if let Some(extension) = path.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        path.with_file_name(name)
+    }
+
+    /// Returns whether `name` is the name of a file of a past period or a backup for `path`.
+    fn is_archive(self, path: &Path, name: &str) -> bool {
+        let name = name.strip_suffix(".gz").unwrap_or(name);
+        let name = strip_index(name).unwrap_or(name);
+        let date_len = match self {
+            Self::Never => return Some(name.as_ref()) == path.file_name(),
+            Self::Hourly => "2024-05-01-13".len(),
+            Self::Daily => "2024-05-01".len(),
+        };
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        let extension = match path.extension() {
+            Some(extension) => match extension.to_str() {
+                Some(extension) => format!(".{}", extension),
+                None => return false,
+            },
+            None => String::new(),
+        };
+        let date = stem
+            .and_then(|stem| name.strip_prefix(stem))
+            .and_then(|name| name.strip_prefix('-'))
+            .and_then(|name| name.strip_suffix(extension.as_str()));
+        matches!(date, Some(date) if date.len() == date_len
+            && date.bytes().all(|b| b.is_ascii_digit() || b == b'-'))
+    }
+}
+
+/// A template for the names of rotated files; see [`RotatingFileBuilder::archive_name`].
+#[derive(Debug, Clone)]
+struct ArchiveName(String);
+
+impl ArchiveName {
+    /// Returns the name for the file at `path` that was started at `time` in `zone`, with an
+    /// index above those of the `taken` names, so that later files sort after earlier ones.
+    fn next_name<'a, I: IntoIterator<Item = &'a str>>(
+        &self,
+        path: &Path,
+        time: SystemTime,
+        zone: TimeZone,
+        taken: I,
+    ) -> String {
+        let template = self.0.replace("{timestamp}", "%Y%m%d-%H%M%S");
+        let name = TimestampFormat::Custom(template)
+            .format_in(time, zone)
+            .unwrap_or_default()
+            .replace("{name}", &stem(path));
+        let (prefix, suffix) = match name.split_once("{index}") {
+            Some((prefix, suffix)) => (prefix.to_owned(), suffix),
+            None => (format!("{}.", name), ""),
+        };
+
+        let mut last = None;
+        for taken in taken {
+            let taken = taken.strip_suffix(".gz").unwrap_or(taken);
+            let index = if taken == name {
+                Some(0)
+            } else {
+                taken
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|taken| taken.strip_suffix(suffix))
+                    .filter(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+                    .and_then(|index| index.parse::<usize>().ok())
+            };
+            last = last.max(index);
+        }
+        match last {
+            None if !name.contains("{index}") => name,
+            _ => format!("{}{}{}", prefix, last.map_or(1, |last| last + 1), suffix),
+        }
+    }
+
+    /// Returns whether `name` may have been rendered from the template for `path`.
+    fn matches(&self, path: &Path, name: &str) -> bool {
+        // Literal parts, and `None` for each placeholder.
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut wildcard = |literal: &mut String| {
+            if !literal.is_empty() {
+                pieces.push(Some(std::mem::take(literal)));
+            }
+            pieces.push(None);
+        };
+        let mut rest = self.0.as_str();
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix("{name}") {
+                literal.push_str(&stem(path));
+                rest = after;
+            } else if let Some(after) = rest
+                .strip_prefix("{timestamp}")
+                .or_else(|| rest.strip_prefix("{index}"))
+            {
+                wildcard(&mut literal);
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("%%") {
+                literal.push('%');
+                rest = after;
+            } else if c == '%' {
+                // A sequence such as `%Y` or `%.3f` ends with a letter.
+                let end = rest[1..]
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .map_or(rest.len(), |i| i + 2);
+                wildcard(&mut literal);
+                rest = &rest[end..];
+            } else {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Some(literal));
+        }
+
+        let name = name.strip_suffix(".gz").unwrap_or(name);
+        glob(&pieces, name) || matches!(strip_index(name), Some(name) if glob(&pieces, name))
+    }
+}
+
+/// Returns whether `name` consists of the literal pieces in order, with at least one character
+/// in place of each `None`.
+fn glob(pieces: &[Option<String>], name: &str) -> bool {
+    match pieces.split_first() {
+        None => name.is_empty(),
+        Some((Some(literal), rest)) => {
+            matches!(name.strip_prefix(literal.as_str()), Some(name) if glob(rest, name))
+        }
+        Some((None, rest)) => name
+            .char_indices()
+            .skip(1)
+            .map(|(i, _)| i)
+            .chain(Some(name.len()))
+            .filter(|&i| i > 0)
+            .any(|i| glob(rest, &name[i..])),
+    }
+}
+
+/// Returns `name` without the index of a backup such as `.1` at its end.
+fn strip_index(name: &str) -> Option<&str> {
+    match name.rsplit_once('.') {
+        Some((rest, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => {
+            Some(rest)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the file name of `path` without its extension.
+#[inline]
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// A file writer that rotates the file once it grows past a maximum size, and optionally at
+/// calendar boundaries.
+///
+/// On rotation, `app.log` is renamed to `app.log.1`, `app.log.1` to `app.log.2`, and so on; the
+/// oldest backup beyond the configured number is deleted. With a time-based [`Rotation`], each
+/// period has its own file, such as `app-2024-05-01.log`, whose backups are rotated the same way
+/// if it grows too large. Rotation only happens between records, so a record is never split
+/// across files.
+///
+/// With an [`archive_name`](RotatingFileBuilder::archive_name) template, such as
+/// `{name}.%Y%m%d.{index}.log`, the file keeps its path and rotated files are renamed after the
+/// template instead, for both kinds of rotation.
+///
+/// Old files can pile up, as time-based rotation keeps one per period. Set
+/// [`keep_files`](RotatingFileBuilder::keep_files) or
+/// [`max_total_size`](RotatingFileBuilder::max_total_size) to delete the oldest ones after each
+/// rotation, or compress them with `gzip`, which requires the `gzip` feature.
+///
+/// # Example
+/// ```rust,no_run
+/// use bunt_logger::RotatingFileWriter;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let writer = RotatingFileWriter::builder("logs/app.log")
+///     .max_bytes(10 * 1024 * 1024)
+///     .max_backups(5)
+///     .open()?;
+/// bunt_logger::with().writer(Box::new(writer));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Rotating daily, to `logs/app-2024-05-01.log` and so on, and keeping the files of the last
+/// week:
+/// ```rust,no_run
+/// use bunt_logger::{RotatingFileWriter, Rotation};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let writer = RotatingFileWriter::builder("logs/app.log")
+///     .rotation(Rotation::Daily)
+///     .keep_files(7)
+///     .open()?;
+/// bunt_logger::with().writer(Box::new(writer));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    max_bytes: u64,
+    max_backups: usize,
+    rotation: Rotation,
+    time_zone: TimeZone,
+    keep_files: Option<usize>,
+    max_total_size: Option<u64>,
+    gzip: bool,
+    archive_name: Option<ArchiveName>,
+    /// The options for opening files, which never truncate them.
+    options: FileOptions,
+    ansi: bool,
+    buffering: Buffering,
+
+    /// The file of the current period, which is `path` unless rotating by time without a
+    /// template.
+    current: PathBuf,
+    period: i64,
+    /// The time at which the current file was started, for the names of templated archives.
+    started: SystemTime,
+    file: FileWriter,
+    size: u64,
+    at_line_start: bool,
+    /// The thread compressing the last rotated file.
+    compressing: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl RotatingFileWriter {
+    /// Opens `path` with the given limits; see [`RotatingFileBuilder`] for the defaults and
+    /// other options.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P, max_bytes: u64, max_backups: usize) -> io::Result<Self> {
+        Self::builder(path)
+            .max_bytes(max_bytes)
+            .max_backups(max_backups)
+            .open()
+    }
+
+    /// Returns a builder for a writer that logs to `path`.
+    #[inline]
+    pub fn builder<P: AsRef<Path>>(path: P) -> RotatingFileBuilder {
+        RotatingFileBuilder::new(path.as_ref())
+    }
+
+    /// Returns the path of the backup with the given index.
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut path = self.current.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        // The backups are renamed, so the last one has to be compressed first.
+        self.wait_for_compression()?;
+
+        let archived = if self.max_backups == 0 {
+            fs::remove_file(&self.current)?;
+            None
+        } else if let Some(archive_name) = &self.archive_name {
+            let mut taken = Vec::new();
+            for entry in fs::read_dir(self.dir())? {
+                taken.extend(entry?.file_name().into_string());
+            }
+            let name = archive_name.next_name(
+                &self.path,
+                self.started,
+                self.time_zone,
+                taken.iter().map(String::as_str),
+            );
+            let archive = self.path.with_file_name(name);
+            fs::rename(&self.current, &archive)?;
+            Some(archive)
+        } else {
+            let oldest = self.backup_path(self.max_backups);
+            remove_if_exists(&oldest)?;
+            remove_if_exists(&gz_path(&oldest))?;
+            for index in (1..self.max_backups).rev() {
+                let from = self.backup_path(index);
+                let to = self.backup_path(index + 1);
+                for (from, to) in [(gz_path(&from), gz_path(&to)), (from, to)] {
+                    if from.exists() {
+                        fs::rename(from, to)?;
+                    }
+                }
+            }
+            fs::rename(&self.current, self.backup_path(1))?;
+            Some(self.backup_path(1))
+        };
+
+        self.file = FileWriter::open_with(&self.current, self.options.clone())?
+            .ansi(self.ansi)
+            .buffering(self.buffering);
+        self.size = 0;
+        self.started = SystemTime::now();
+        match archived {
+            Some(archived) if self.gzip => self.compress(archived)?,
+            _ => {}
+        }
+        self.prune()
+    }
+
+    /// Switches to the file of the period of `now`, which may already have been written to.
+    fn start_period(&mut self, now: SystemTime) -> io::Result<()> {
+        if self.archive_name.is_some() {
+            self.period = self.rotation.period(now, self.time_zone);
+            return if self.size > 0 { self.rotate() } else { Ok(()) };
+        }
+
+        self.file.flush()?;
+        self.wait_for_compression()?;
+        let current = self.rotation.path(&self.path, now, self.time_zone);
+        self.file = FileWriter::open_with(&current, self.options.clone())?
+            .ansi(self.ansi)
+            .buffering(self.buffering);
+        let previous = std::mem::replace(&mut self.current, current);
+        self.period = self.rotation.period(now, self.time_zone);
+        self.size = self.file.len()?;
+        self.started = now;
+        if self.gzip {
+            self.compress(previous)?;
+        }
+        self.prune()
+    }
+
+    /// Compresses the file at `path`, which isn't written to anymore, in a background thread.
+    #[cfg(feature = "gzip")]
+    fn compress(&mut self, path: PathBuf) -> io::Result<()> {
+        let handle = std::thread::Builder::new()
+            .name("bunt-logger-gzip".to_owned())
+            .spawn(move || {
+                super::gzip::compress(&path).map_err(|err| {
+                    io::Error::new(
+                        err.kind(),
+                        format!("failed to compress {}: {}", path.display(), err),
+                    )
+                })
+            })?;
+        self.compressing = Some(handle);
+        Ok(())
+    }
+
+    /// Without the `gzip` feature, compression can't be enabled.
+    #[cfg(not(feature = "gzip"))]
+    #[inline]
+    fn compress(&mut self, _: PathBuf) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Waits for the last rotated file to be compressed, returning the error if that failed.
+    ///
+    /// The thread doesn't report its errors itself: it would need the global preferences, which
+    /// may be locked while the writer waits for it.
+    fn wait_for_compression(&mut self) -> io::Result<()> {
+        let result = match self.compressing.take() {
+            Some(handle) => handle.join().unwrap_or(Ok(())),
+            None => Ok(()),
+        };
+        match result {
+            // The file may have been deleted by the retention limits in the meantime.
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the directory of the file.
+    #[inline]
+    fn dir(&self) -> &Path {
+        match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        }
+    }
+
+    /// Deletes the oldest files of past periods and backups beyond the retention limits.
+    fn prune(&self) -> io::Result<()> {
+        // Templated archives aren't shifted, so the number of backups is enforced here.
+        let keep_files = match (&self.archive_name, self.keep_files) {
+            (Some(_), keep_files) => Some(keep_files.map_or(self.max_backups, |keep_files| {
+                keep_files.min(self.max_backups)
+            })),
+            (None, keep_files) => keep_files,
+        };
+        if keep_files.is_none() && self.max_total_size.is_none() {
+            return Ok(());
+        }
+
+        let mut archives = Vec::new();
+        for entry in fs::read_dir(self.dir())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let is_archive = match (name.to_str(), &self.archive_name) {
+                (Some(name), Some(archive_name)) => archive_name.matches(&self.path, name),
+                (Some(name), None) => self.rotation.is_archive(&self.path, name),
+                (None, _) => false,
+            };
+            if !is_archive || Some(name.as_os_str()) == self.current.file_name() {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                archives.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        archives.sort_by_key(|&(modified, _, _)| std::cmp::Reverse(modified));
+
+        let mut total = self.size;
+        for (i, (_, len, path)) in archives.into_iter().enumerate() {
+            total = total.saturating_add(len);
+            let too_many = matches!(keep_files, Some(keep) if i >= keep);
+            let too_large = matches!(self.max_total_size, Some(max) if total > max);
+            if too_many || too_large {
+                remove_if_exists(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RotatingFileWriter {
+    fn drop(&mut self) {
+        let _ = self.wait_for_compression();
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.reopen_if_requested()? {
+            self.size = self.file.len()?;
+        }
+        if self.at_line_start && !buf.is_empty() {
+            if self.rotation != Rotation::Never {
+                let now = SystemTime::now();
+                if self.rotation.period(now, self.time_zone) != self.period {
+                    self.start_period(now)?;
+                }
+            }
+            if self.size >= self.max_bytes {
+                self.rotate()?;
+            }
+        }
+
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+        if n > 0 {
+            self.at_line_start = buf[n - 1] == b'\n';
+        }
+        Ok(n)
+    }
+
+    /// Also waits for the last rotated file to be compressed.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.wait_for_compression()
+    }
+}
+
+impl WriteColor for RotatingFileWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.file.supports_color()
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.file.set_color(spec)
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.file.reset()
+    }
+}
+
+/// A builder for [`RotatingFileWriter`].
+#[derive(Debug, Clone)]
+pub struct RotatingFileBuilder {
+    path: PathBuf,
+    max_bytes: u64,
+    max_backups: usize,
+    rotation: Rotation,
+    time_zone: TimeZone,
+    keep_files: Option<usize>,
+    max_total_size: Option<u64>,
+    gzip: bool,
+    archive_name: Option<ArchiveName>,
+    options: FileOptions,
+    ansi: bool,
+    buffering: Buffering,
+}
+
+impl RotatingFileBuilder {
+    #[inline]
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            max_bytes: 10 * 1024 * 1024,
+            max_backups: 5,
+            rotation: Rotation::default(),
+            time_zone: TimeZone::default(),
+            keep_files: None,
+            max_total_size: None,
+            gzip: false,
+            archive_name: None,
+            options: FileOptions::default(),
+            ansi: false,
+            buffering: Buffering::default(),
+        }
+    }
+
+    /// Sets the size in bytes after which the file is rotated. Defaults to 10 MiB.
+    #[inline]
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Sets the number of rotated files that are kept. Defaults to 5; with no backups, the file
+    /// is simply truncated on rotation.
+    #[inline]
+    pub fn max_backups(mut self, max_backups: usize) -> Self {
+        self.max_backups = max_backups;
+        self
+    }
+
+    /// Sets when a new file is started regardless of its size. Defaults to [`Rotation::Never`].
+    #[inline]
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the time zone of the periods of the [`rotation`](Self::rotation) and of the dates in
+    /// the names of files, e.g. so that daily files start at local midnight. Defaults to
+    /// [`TimeZone::Utc`]; see [`TimeZone::Local`] for the platforms that support local time.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use bunt_logger::{RotatingFileWriter, Rotation, TimeZone};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let writer = RotatingFileWriter::builder("logs/app.log")
+    ///     .rotation(Rotation::Daily)
+    ///     .time_zone(TimeZone::Local)
+    ///     .open()?;
+    /// bunt_logger::with()
+    ///     .time_zone(TimeZone::Local)
+    ///     .writer(Box::new(writer));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    /// Sets the number of old files that are kept, counting both the files of past periods and
+    /// backups, after which the oldest ones are deleted. Unlimited by default.
+    #[inline]
+    pub fn keep_files(mut self, keep_files: usize) -> Self {
+        self.keep_files = Some(keep_files);
+        self
+    }
+
+    /// Sets the total size in bytes of the current and old files, beyond which the oldest old
+    /// files are deleted. Unlimited by default.
+    #[inline]
+    pub fn max_total_size(mut self, max_total_size: u64) -> Self {
+        self.max_total_size = Some(max_total_size);
+        self
+    }
+
+    /// Sets whether rotated files are compressed with gzip in a background thread, with `.gz`
+    /// appended to their names, e.g. `app.log.1.gz` or `app-2024-05-01.log.gz`. Flushing the
+    /// writer waits for the compression to finish, so that it completes before the process
+    /// exits. Defaults to `false`. Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    #[inline]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Names rotated files after `template` instead of numbering them, in the directory of the
+    /// file, which then keeps its path with time-based rotation as well. Rotated files aren't
+    /// renamed again, and the oldest ones beyond [`max_backups`](Self::max_backups) are deleted.
+    ///
+    /// In the template, `{name}` is the file name without its extension, `{index}` is 1 or one
+    /// more than that of the last file with the same name otherwise, `{timestamp}` is the time at
+    /// which the file was started as `%Y%m%d-%H%M%S`, and the sequences of
+    /// [`TimestampFormat::Custom`] are replaced with that time as well. Without `{index}`, `.1`,
+    /// `.2` and so on are appended to names that are taken.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use bunt_logger::{RotatingFileWriter, Rotation};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// // Rotated to `logs/app.log-20240501` at midnight, like `logrotate` with `dateext`.
+    /// let writer = RotatingFileWriter::builder("logs/app.log")
+    ///     .rotation(Rotation::Daily)
+    ///     .archive_name("{name}.log-%Y%m%d")
+    ///     .max_backups(30)
+    ///     .open()?;
+    /// bunt_logger::with().writer(Box::new(writer));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn archive_name(mut self, template: &str) -> Self {
+        self.archive_name = Some(ArchiveName(template.to_owned()));
+        self
+    }
+
+    /// Sets how files are opened. Only the file opened by [`open`](Self::open) may be truncated.
+    #[inline]
+    pub fn options(mut self, options: FileOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets whether styles are written as ANSI escape sequences. Defaults to `false`.
+    #[inline]
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    /// Sets how output is buffered. Defaults to [`Buffering::Line`].
+    #[inline]
+    pub fn buffering(mut self, buffering: Buffering) -> Self {
+        self.buffering = buffering;
+        self
+    }
+
+    /// Opens the file, creating it and by default its parent directories if necessary, and
+    /// deletes old files beyond the retention limits.
+    pub fn open(self) -> io::Result<RotatingFileWriter> {
+        let now = SystemTime::now();
+        let current = match self.archive_name {
+            Some(_) => self.path.clone(),
+            None => self.rotation.path(&self.path, now, self.time_zone),
+        };
+        let file = FileWriter::open_with(&current, self.options.clone())?
+            .ansi(self.ansi)
+            .buffering(self.buffering);
+        let size = file.len()?;
+
+        let writer = RotatingFileWriter {
+            path: self.path,
+            max_bytes: self.max_bytes,
+            max_backups: self.max_backups,
+            rotation: self.rotation,
+            time_zone: self.time_zone,
+            keep_files: self.keep_files,
+            max_total_size: self.max_total_size,
+            gzip: self.gzip,
+            archive_name: self.archive_name,
+            options: self.options.truncate(false),
+            ansi: self.ansi,
+            buffering: self.buffering,
+            current,
+            period: self.rotation.period(now, self.time_zone),
+            started: now,
+            file,
+            size,
+            at_line_start: true,
+            compressing: None,
+        };
+        writer.prune()?;
+        Ok(writer)
+    }
+}
+
+/// Deletes the file at `path` unless it doesn't exist.
+#[inline]
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bunt::termcolor::{ColorSpec, WriteColor};
+
+/// A handle to a writer that may be shared between the global preferences and their snapshots.
+#[derive(Clone)]
+pub(crate) struct SharedWriter(Arc<Mutex<Box<dyn WriteColor + Send>>>);
+
+impl SharedWriter {
+    #[inline]
+    pub(crate) fn new(writer: Box<dyn WriteColor + Send>) -> Self {
+        Self(Arc::new(Mutex::new(writer)))
+    }
+
+    #[inline]
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Box<dyn WriteColor + Send>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Write for SharedWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+impl WriteColor for SharedWriter {
+    #[inline]
+    fn supports_color(&self) -> bool {
+        self.lock().supports_color()
+    }
+
+    #[inline]
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.lock().set_color(spec)
+    }
+
+    #[inline]
+    fn reset(&mut self) -> io::Result<()> {
+        self.lock().reset()
+    }
+}